@@ -0,0 +1,217 @@
+//A*算法（路径寻找）
+//A算法是一种用于寻找目标最短路径的算法，特别常用于游戏和导航系统中。可以使用Rust的数据结构和 BinaryHeap类来实现A算法。
+// heuristic 估计节点到目标的剩余代价，不能高估(可采纳)时结果是最短路。
+// 节点的代价变小后允许重新扩展，所以不一致的启发函数也能得到正确结果。
+use super::{trace, Graph, Path, Weight};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Node<W> {
+    // cost + heuristic
+    estimate: W,
+    cost: W,
+    position: usize,
+}
+
+impl<W: Ord> Ord for Node<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            // 估计相同时优先扩展离目标更近(已走得更远)的节点
+            .then_with(|| self.cost.cmp(&other.cost))
+            .then_with(|| other.position.cmp(&self.position))
+    }
+}
+
+impl<W: Ord> PartialOrd for Node<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn a_star<N, W>(
+    graph: &Graph<N, W>,
+    start: &N,
+    goal: &N,
+    heuristic: impl FnMut(&N) -> W,
+) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    a_star_until(graph, start, |node| node == goal, heuristic)
+}
+
+// 多个目标时 heuristic 应取到各目标估计值的最小值
+pub fn a_star_until<N, W>(
+    graph: &Graph<N, W>,
+    start: &N,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut heuristic: impl FnMut(&N) -> W,
+) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let start = graph.index_of(start)?;
+    let mut dist: Vec<Option<W>> = vec![None; graph.node_count()];
+    let mut pred = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    dist[start] = Some(W::zero());
+    heap.push(Node {
+        estimate: heuristic(graph.node(start)),
+        cost: W::zero(),
+        position: start,
+    });
+
+    while let Some(Node { cost, position, .. }) = heap.pop() {
+        if dist[position].is_some_and(|best| cost > best) {
+            continue;
+        }
+        if is_goal(graph.node(position)) {
+            return Some(Path {
+                cost,
+                nodes: graph.trace(&pred, position),
+            });
+        }
+        for edge in graph.neighbors(position) {
+            let Some(next_cost) = cost.checked_add(edge.weight) else {
+                continue;
+            };
+            if dist[edge.to].is_none_or(|best| next_cost < best) {
+                let Some(estimate) = next_cost.checked_add(heuristic(graph.node(edge.to))) else {
+                    continue;
+                };
+                heap.push(Node {
+                    estimate,
+                    cost: next_cost,
+                    position: edge.to,
+                });
+                dist[edge.to] = Some(next_cost);
+                pred[edge.to] = Some(position);
+            }
+        }
+    }
+    None
+}
+
+// 隐式图上的 A*：不预先建图，successors 按需给出节点的后继和边权，
+// 适合很大或无限的状态空间。只有被访问到的节点会占用内存
+pub fn a_star_implicit<N, W, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut heuristic: impl FnMut(&N) -> W,
+) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+    I: IntoIterator<Item = (N, W)>,
+{
+    // 访问到的节点按发现顺序编号
+    let mut nodes = vec![start.clone()];
+    let mut index = HashMap::from([(start, 0)]);
+    let mut dist = vec![W::zero()];
+    let mut pred: Vec<Option<usize>> = vec![None];
+    let mut heap = BinaryHeap::new();
+    heap.push(Node {
+        estimate: heuristic(&nodes[0]),
+        cost: W::zero(),
+        position: 0,
+    });
+
+    while let Some(Node { cost, position, .. }) = heap.pop() {
+        if cost > dist[position] {
+            continue;
+        }
+        if is_goal(&nodes[position]) {
+            return Some(Path {
+                cost,
+                nodes: trace(&pred, position)
+                    .into_iter()
+                    .map(|i| nodes[i].clone())
+                    .collect(),
+            });
+        }
+        for (next, weight) in successors(&nodes[position]) {
+            let Some(next_cost) = cost.checked_add(weight) else {
+                continue;
+            };
+            let next_position = match index.get(&next) {
+                Some(&i) if next_cost >= dist[i] => continue,
+                Some(&i) => i,
+                None => {
+                    index.insert(next.clone(), nodes.len());
+                    nodes.push(next);
+                    dist.push(next_cost);
+                    pred.push(None);
+                    nodes.len() - 1
+                }
+            };
+            let Some(estimate) = next_cost.checked_add(heuristic(&nodes[next_position])) else {
+                continue;
+            };
+            heap.push(Node {
+                estimate,
+                cost: next_cost,
+                position: next_position,
+            });
+            dist[next_position] = next_cost;
+            pred[next_position] = Some(position);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+
+    // 5x5 网格上的四连通图，中间一堵墙
+    fn grid() -> Graph<(i32, i32), u32> {
+        let wall = |x: i32, y: i32| x == 2 && y < 4;
+        let mut graph = Graph::new();
+        for x in 0..5 {
+            for y in 0..5 {
+                for (dx, dy) in [(1, 0), (0, 1)] {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 5 && ny < 5 && !wall(x, y) && !wall(nx, ny) {
+                        graph.add_undirected_edge((x, y), (nx, ny), 1);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn matches_dijkstra_with_manhattan_heuristic() {
+        let graph = grid();
+        let goal = (4, 0);
+        let manhattan =
+            |&(x, y): &(i32, i32)| (goal.0 - x).unsigned_abs() + (goal.1 - y).unsigned_abs();
+        let path = a_star(&graph, &(0, 0), &goal, manhattan).unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(path.nodes.first(), Some(&(0, 0)));
+        assert_eq!(path.nodes.last(), Some(&goal));
+        assert_eq!(path.nodes.len(), 13);
+        assert_eq!(path.cost, dijkstra(&graph, &(0, 0), &goal).unwrap().cost);
+
+        // 任一目标，启发函数为 0 时退化为 Dijkstra
+        let near = a_star_until(&graph, &(0, 0), |&(x, _)| x == 1, |_| 0).unwrap();
+        assert_eq!(near.cost, 1);
+    }
+
+    #[test]
+    fn searches_implicit_state_space() {
+        // 无限的数轴，每步 +1 或 *2，求 1 到 100 的最少步数
+        let path =
+            a_star_implicit(1u64, |&n| [(n + 1, 1u32), (n * 2, 1)], |&n| n == 100, |_| 0).unwrap();
+        assert_eq!(path.cost, 8);
+        assert_eq!(path.nodes, [1, 2, 3, 6, 12, 24, 25, 50, 100]);
+    }
+}
@@ -0,0 +1,161 @@
+// Bellman-Ford算法（允许负权边的单源最短路）
+// 对所有边做最多 n 轮松弛，一轮没有变化就提前结束。第 n 轮仍能松弛说明存在负环，
+// 从最后被松弛的节点沿前驱走 n 步一定落在环上，再沿前驱绕一圈就得到这个环。复杂度 O(nm)。
+use super::{Graph, Path, PathError, ShortestPaths, Weight};
+use std::hash::Hash;
+
+// 只能发现从 start 可达的负环
+pub fn bellman_ford<'a, N, W>(
+    graph: &'a Graph<N, W>,
+    start: &N,
+) -> Result<ShortestPaths<'a, N, W>, PathError<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let start = graph.index_of(start).ok_or(PathError::UnknownNode)?;
+    let mut dist = vec![None; graph.node_count()];
+    dist[start] = Some(W::zero());
+    let (dist, pred) =
+        relax(graph, dist).map_err(|cycle| PathError::NegativeCycle(cycle_path(graph, &cycle)))?;
+    Ok(ShortestPaths::new(graph, start, dist, pred))
+}
+
+// 图中任意位置的一个负环，没有负环时返回 None
+pub fn find_negative_cycle<N, W>(graph: &Graph<N, W>) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    relax(graph, vec![Some(W::zero()); graph.node_count()])
+        .err()
+        .map(|cycle| cycle_path(graph, &cycle))
+}
+
+// 按下标排列的距离和前驱
+type Relaxed<W> = (Vec<Option<W>>, Vec<Option<usize>>);
+
+// 从 dist 给出的初始距离开始反复松弛，返回最终的距离和前驱，或者一个负环上的节点下标(首尾相同)。
+// 所有节点初始距离都为 0，相当于从一个向每个节点连 0 权边的虚拟源点出发，能找到所有负环
+pub(crate) fn relax<N, W: Weight>(
+    graph: &Graph<N, W>,
+    mut dist: Vec<Option<W>>,
+) -> Result<Relaxed<W>, Vec<usize>> {
+    let n = graph.node_count();
+    let mut pred = vec![None; n];
+    let mut relaxed = None;
+    for _ in 0..n {
+        relaxed = None;
+        for (from, to, weight) in graph.edges() {
+            let Some(next) = dist[from].and_then(|cost| cost.checked_add(weight)) else {
+                continue;
+            };
+            if dist[to].is_none_or(|best| next < best) {
+                dist[to] = Some(next);
+                pred[to] = Some(from);
+                relaxed = Some(to);
+            }
+        }
+        if relaxed.is_none() {
+            break;
+        }
+    }
+    let Some(mut node) = relaxed else {
+        return Ok((dist, pred));
+    };
+
+    for _ in 0..n {
+        node = pred[node].expect("第 n 轮被松弛的节点沿前驱一定走进负环");
+    }
+    let mut cycle = vec![node];
+    let mut current = node;
+    loop {
+        current = pred[current].expect("负环上的节点都有前驱");
+        cycle.push(current);
+        if current == node {
+            break;
+        }
+    }
+    cycle.reverse();
+    Err(cycle)
+}
+
+// 环上相邻两个节点之间有多条边时取最小的权重
+pub(crate) fn cycle_path<N, W>(graph: &Graph<N, W>, cycle: &[usize]) -> Path<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let cost = cycle.windows(2).fold(W::zero(), |total, pair| {
+        let weight = graph
+            .neighbors(pair[0])
+            .iter()
+            .filter(|edge| edge.to == pair[1])
+            .map(|edge| edge.weight)
+            .min()
+            .expect("前驱来自图中的边");
+        total.checked_add(weight).expect("负环的权重之和溢出")
+    });
+    Path {
+        nodes: cycle.iter().map(|&i| graph.node(i).clone()).collect(),
+        cost,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // C -> B 是一条返利边
+    fn rebates() -> Graph<&'static str, i32> {
+        Graph::from_edges([
+            ("A", "B", 4),
+            ("A", "C", 2),
+            ("C", "B", -3),
+            ("B", "D", 1),
+            ("X", "Y", -2),
+        ])
+    }
+
+    #[test]
+    fn handles_negative_edges() {
+        let graph = rebates();
+        let paths = bellman_ford(&graph, &"A").unwrap();
+        let path = paths.path_to(&"D").unwrap();
+        assert_eq!((path.nodes, path.cost), (vec!["A", "C", "B", "D"], 0));
+        assert_eq!(paths.cost(&"B"), Some(-1));
+        assert_eq!(paths.cost(&"X"), None);
+        assert_eq!(find_negative_cycle(&graph), None);
+        assert_eq!(
+            bellman_ford(&graph, &"Z").err(),
+            Some(PathError::UnknownNode)
+        );
+    }
+
+    #[test]
+    fn reports_negative_cycles() {
+        let mut graph = rebates();
+        graph.add_edge("D", "C", 1);
+        graph.add_edge("D", "C", 5);
+        let Err(PathError::NegativeCycle(cycle)) = bellman_ford(&graph, &"A") else {
+            panic!("应当发现负环");
+        };
+        assert_eq!(cycle.cost, -1);
+        assert_eq!(cycle.nodes.len(), 4);
+        assert_eq!(cycle.nodes.first(), cycle.nodes.last());
+        for node in ["B", "C", "D"] {
+            assert!(cycle.nodes.contains(&node));
+        }
+
+        // 从 A 走不到的负环不影响 A 的最短路，但 find_negative_cycle 能找到
+        let mut graph = rebates();
+        graph.add_edge("Y", "X", 1);
+        assert!(bellman_ford(&graph, &"A").is_ok());
+        let cycle = find_negative_cycle(&graph).unwrap();
+        assert_eq!(cycle.cost, -1);
+        assert!(matches!(
+            bellman_ford(&graph, &"X"),
+            Err(PathError::NegativeCycle(_))
+        ));
+    }
+}
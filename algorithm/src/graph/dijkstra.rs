@@ -0,0 +1,166 @@
+// Dijkstra算法（最短路径）
+// Dijkstra算法是一种经典算法，用于在图中寻找两个节点之间的最短路径。可以使用Rust的 HashMap和 BinaryHeap结构来高效实现。
+// 要求边权非负。节点第一次出堆时距离已经确定，找到目标后立即返回，不再扩展其余节点。
+use super::{Graph, Path, ShortestPaths, Weight};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct State<W> {
+    cost: W,
+    position: usize,
+}
+
+impl<W: Ord> Ord for State<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| other.position.cmp(&self.position))
+    }
+}
+impl<W: Ord> PartialOrd for State<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn dijkstra<N, W>(graph: &Graph<N, W>, start: &N, goal: &N) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    dijkstra_until(graph, start, |node| node == goal)
+}
+
+// 到 goals 中最近的一个
+pub fn dijkstra_to_any<N, W>(graph: &Graph<N, W>, start: &N, goals: &[N]) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    dijkstra_until(graph, start, |node| goals.contains(node))
+}
+
+// 到第一个满足 is_goal 的节点(即满足条件的节点中最近的)
+pub fn dijkstra_until<N, W>(
+    graph: &Graph<N, W>,
+    start: &N,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let start = graph.index_of(start)?;
+    let mut goal = None;
+    let (dist, pred) = search(graph, start, |i| {
+        let reached = is_goal(graph.node(i));
+        if reached {
+            goal = Some(i);
+        }
+        reached
+    });
+    let goal = goal?;
+    Some(Path {
+        cost: dist[goal]?,
+        nodes: graph.trace(&pred, goal),
+    })
+}
+
+// 从 start 到所有节点的最短路
+pub fn dijkstra_all<'a, N, W>(graph: &'a Graph<N, W>, start: &N) -> Option<ShortestPaths<'a, N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let start = graph.index_of(start)?;
+    let (dist, pred) = search(graph, start, |_| false);
+    Some(ShortestPaths::new(graph, start, dist, pred))
+}
+
+// 按下标搜索，stop 对出堆的节点返回 true 时提前结束
+pub(crate) fn search<N, W: Weight>(
+    graph: &Graph<N, W>,
+    start: usize,
+    mut stop: impl FnMut(usize) -> bool,
+) -> (Vec<Option<W>>, Vec<Option<usize>>) {
+    let mut dist: Vec<Option<W>> = vec![None; graph.node_count()];
+    let mut pred = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    dist[start] = Some(W::zero());
+    heap.push(State {
+        cost: W::zero(),
+        position: start,
+    });
+    while let Some(State { cost, position }) = heap.pop() {
+        if dist[position].is_some_and(|best| cost > best) {
+            continue;
+        }
+        if stop(position) {
+            break;
+        }
+        for edge in graph.neighbors(position) {
+            let Some(next_cost) = cost.checked_add(edge.weight) else {
+                continue;
+            };
+            if dist[edge.to].is_none_or(|best| next_cost < best) {
+                heap.push(State {
+                    cost: next_cost,
+                    position: edge.to,
+                });
+                dist[edge.to] = Some(next_cost);
+                pred[edge.to] = Some(position);
+            }
+        }
+    }
+    (dist, pred)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn roads() -> Graph<&'static str, u32> {
+        Graph::from_edges([
+            ("A", "B", 7),
+            ("A", "C", 9),
+            ("A", "F", 14),
+            ("B", "C", 10),
+            ("B", "D", 15),
+            ("C", "D", 11),
+            ("C", "F", 2),
+            ("D", "E", 6),
+            ("F", "E", 9),
+        ])
+    }
+
+    #[test]
+    fn returns_route_and_cost() {
+        let graph = roads();
+        let path = dijkstra(&graph, &"A", &"E").unwrap();
+        assert_eq!(path.nodes, ["A", "C", "F", "E"]);
+        assert_eq!(path.cost, 20);
+        assert_eq!(dijkstra(&graph, &"E", &"A"), None);
+        assert_eq!(dijkstra(&graph, &"A", &"Z"), None);
+
+        let nearest = dijkstra_to_any(&graph, &"A", &["D", "F"]).unwrap();
+        assert_eq!(nearest.nodes, ["A", "C", "F"]);
+
+        let all = dijkstra_all(&graph, &"A").unwrap();
+        assert_eq!(all.cost(&"D"), Some(20));
+        assert_eq!(all.path_to(&"E"), Some(path));
+    }
+
+    #[test]
+    fn accepts_old_adjacency_maps() {
+        let mut adjacency: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+        adjacency.insert(0, vec![(1, 4), (2, 1)]);
+        adjacency.insert(2, vec![(1, 2)]);
+        let graph = Graph::from_adjacency(&adjacency);
+        let path = dijkstra(&graph, &0, &1).unwrap();
+        assert_eq!((path.nodes, path.cost), (vec![0, 2, 1], 3));
+    }
+}
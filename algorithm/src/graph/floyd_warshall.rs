@@ -0,0 +1,75 @@
+// Floyd-Warshall算法（稠密图的全源最短路，允许负权边）
+// 依次允许经过第 k 个节点中转，dist[i][j] = min(dist[i][j], dist[i][k] + dist[k][j])，复杂度 O(n³)。
+// 结束后某个 dist[i][i] < 0 说明 i 在负环上，再用 Bellman-Ford 找出具体的环。
+use super::{find_negative_cycle, AllPairs, Graph, PathError, Weight};
+use std::hash::Hash;
+
+pub fn floyd_warshall<N, W>(graph: &Graph<N, W>) -> Result<AllPairs<'_, N, W>, PathError<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let n = graph.node_count();
+    let mut dist = vec![vec![None; n]; n];
+    let mut pred = vec![vec![None; n]; n];
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[i] = Some(W::zero());
+    }
+    for (from, to, weight) in graph.edges() {
+        if dist[from][to].is_none_or(|best| weight < best) {
+            dist[from][to] = Some(weight);
+            pred[from][to] = Some(from);
+        }
+    }
+
+    for k in 0..n {
+        for i in 0..n {
+            let Some(to_k) = dist[i][k] else {
+                continue;
+            };
+            for j in 0..n {
+                let Some(through) = dist[k][j].and_then(|from_k| to_k.checked_add(from_k)) else {
+                    continue;
+                };
+                if dist[i][j].is_none_or(|best| through < best) {
+                    dist[i][j] = Some(through);
+                    pred[i][j] = pred[k][j];
+                }
+            }
+        }
+    }
+
+    if (0..n).any(|i| dist[i][i].is_some_and(|cost| cost < W::zero())) {
+        let cycle = find_negative_cycle(graph).expect("dist[i][i] < 0 时一定有负环");
+        return Err(PathError::NegativeCycle(cycle));
+    }
+    Ok(AllPairs::new(graph, dist, pred))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_distance_and_predecessor_matrices() {
+        let graph = Graph::from_edges([(1, 2, 3), (2, 3, -2), (1, 3, 4), (3, 4, 2), (4, 1, 1)]);
+        let all = floyd_warshall(&graph).unwrap();
+        assert_eq!(all.cost(&1, &4), Some(3));
+        assert_eq!(all.cost(&4, &3), Some(2));
+        let path = all.path(&4, &3).unwrap();
+        assert_eq!(path.nodes, [4, 1, 2, 3]);
+        assert_eq!(all.path(&2, &2).unwrap().nodes, [2]);
+        assert_eq!(all.cost(&1, &5), None);
+
+        // 节点按 1, 2, 3, 4 的顺序编号，pred[0][3] 是 1 到 4 的路径上 4 的前驱，即下标为 2 的节点 3
+        assert_eq!(all.predecessors()[0][3], Some(2));
+        assert_eq!(all.distances()[2][0], Some(3));
+
+        let mut cyclic = graph.clone();
+        cyclic.add_edge(4, 2, -1);
+        let Err(PathError::NegativeCycle(cycle)) = floyd_warshall(&cyclic) else {
+            panic!("应当发现负环");
+        };
+        assert_eq!(cycle.cost, -1);
+    }
+}
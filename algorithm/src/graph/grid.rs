@@ -0,0 +1,524 @@
+// 二维网格寻路。格子坐标为 (x, y)，x 是列、y 是行，原点在左上角。
+// 直走代价 STRAIGHT_COST，斜走代价 DIAGONAL_COST(约为 √2 倍)，斜走时两侧的直邻格都必须可走，
+// 不能从障碍物的角上穿过。
+// A* 用 a_star_implicit 按需生成邻格；Jump Point Search 沿直线和对角线跳过对称的路径，
+// 只把"跳点"放进堆里，在空旷的大地图上扩展的节点少得多，结果与 A* 的代价相同。
+//
+// ASCII 地图每行一行格子: '.' 空地，'#' '@' 'T' 'W' 障碍，'S' 起点，'G' 终点
+use super::{a_star_implicit, Path};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+pub type Point = (usize, usize);
+
+pub const STRAIGHT_COST: u32 = 10;
+pub const DIAGONAL_COST: u32 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    // 上下左右
+    Four,
+    // 再加四个对角
+    Eight,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    // 第 row 行(从 0 开始)的长度与第一行不同
+    UnevenRows {
+        row: usize,
+    },
+    UnknownCell {
+        row: usize,
+        column: usize,
+        cell: char,
+    },
+    // 'S' 或 'G' 出现了不止一次
+    Duplicate(char),
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "地图为空"),
+            GridError::UnevenRows { row } => write!(f, "第 {} 行的长度与第一行不同", row + 1),
+            GridError::UnknownCell { row, column, cell } => {
+                write!(
+                    f,
+                    "第 {} 行第 {} 列的字符 {:?} 无法识别",
+                    row + 1,
+                    column + 1,
+                    cell
+                )
+            }
+            GridError::Duplicate(cell) => write!(f, "{} 出现了不止一次", cell),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    // 按行存放，blocked[y * width + x]
+    blocked: Vec<bool>,
+}
+
+// 从 ASCII 文本读出的地图和其中标出的起点、终点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsciiMap {
+    pub grid: Grid,
+    pub start: Option<Point>,
+    pub goal: Option<Point>,
+}
+
+impl Grid {
+    // 全部可走的空地图
+    pub fn new(width: usize, height: usize) -> Self {
+        Grid {
+            width,
+            height,
+            blocked: vec![false; width * height],
+        }
+    }
+
+    // 忽略空行和行尾空白
+    pub fn parse(text: &str) -> Result<AsciiMap, GridError> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let width = rows.first().ok_or(GridError::Empty)?.chars().count();
+        let mut grid = Grid::new(width, rows.len());
+        let (mut start, mut goal) = (None, None);
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(GridError::UnevenRows { row: y });
+            }
+            for (x, cell) in row.chars().enumerate() {
+                let marker = match cell {
+                    '.' => None,
+                    '#' | '@' | 'T' | 'W' => {
+                        grid.set_blocked((x, y), true);
+                        None
+                    }
+                    'S' => Some(&mut start),
+                    'G' => Some(&mut goal),
+                    _ => {
+                        return Err(GridError::UnknownCell {
+                            row: y,
+                            column: x,
+                            cell,
+                        })
+                    }
+                };
+                if let Some(marker) = marker {
+                    if marker.replace((x, y)).is_some() {
+                        return Err(GridError::Duplicate(cell));
+                    }
+                }
+            }
+        }
+        Ok(AsciiMap { grid, start, goal })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn set_blocked(&mut self, (x, y): Point, blocked: bool) {
+        self.blocked[y * self.width + x] = blocked;
+    }
+
+    // 越界的格子当作障碍
+    pub fn is_open(&self, x: isize, y: isize) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && !self.blocked[y as usize * self.width + x as usize]
+    }
+
+    fn open(&self, (x, y): Point) -> bool {
+        self.is_open(x as isize, y as isize)
+    }
+
+    // 可以一步走到的邻格和代价
+    pub fn neighbors(&self, (x, y): Point, connectivity: Connectivity) -> Vec<(Point, u32)> {
+        let (x, y) = (x as isize, y as isize);
+        let mut result = Vec::with_capacity(8);
+        for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+            if self.is_open(x + dx, y + dy) {
+                result.push((((x + dx) as usize, (y + dy) as usize), STRAIGHT_COST));
+            }
+        }
+        if connectivity == Connectivity::Eight {
+            for (dx, dy) in [(1, 1), (-1, 1), (-1, -1), (1, -1)] {
+                if self.is_open(x + dx, y + dy)
+                    && self.is_open(x + dx, y)
+                    && self.is_open(x, y + dy)
+                {
+                    result.push((((x + dx) as usize, (y + dy) as usize), DIAGONAL_COST));
+                }
+            }
+        }
+        result
+    }
+
+    // 用 '*' 标出路径，起点和终点标为 'S' 和 'G'
+    pub fn render(&self, path: &[Point]) -> String {
+        let mut cells: Vec<Vec<char>> = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| if self.open((x, y)) { '.' } else { '#' })
+                    .collect()
+            })
+            .collect();
+        for &(x, y) in path {
+            cells[y][x] = '*';
+        }
+        if let (Some(&(sx, sy)), Some(&(gx, gy))) = (path.first(), path.last()) {
+            cells[sy][sx] = 'S';
+            cells[gy][gx] = 'G';
+        }
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in cells {
+            text.extend(row);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// 四连通时的准确剩余代价下界
+pub fn manhattan(a: Point, b: Point) -> u32 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32 * STRAIGHT_COST
+}
+
+// 八连通时的准确剩余代价下界：先斜走到同一行或列，再直走
+pub fn octile(a: Point, b: Point) -> u32 {
+    let (dx, dy) = (a.0.abs_diff(b.0) as u32, a.1.abs_diff(b.1) as u32);
+    DIAGONAL_COST * dx.min(dy) + STRAIGHT_COST * dx.abs_diff(dy)
+}
+
+// 用 A* 在网格上寻路，启发函数按连通方式选 manhattan 或 octile
+pub fn find_path(
+    grid: &Grid,
+    start: Point,
+    goal: Point,
+    connectivity: Connectivity,
+) -> Option<Path<Point, u32>> {
+    if !grid.open(start) || !grid.open(goal) {
+        return None;
+    }
+    let heuristic = match connectivity {
+        Connectivity::Four => manhattan,
+        Connectivity::Eight => octile,
+    };
+    a_star_implicit(
+        start,
+        |&p| grid.neighbors(p, connectivity),
+        |&p| p == goal,
+        |&p| heuristic(p, goal),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JumpPoint {
+    estimate: u32,
+    cost: u32,
+    point: Point,
+}
+
+impl Ord for JumpPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+            .then_with(|| other.point.cmp(&self.point))
+    }
+}
+
+impl PartialOrd for JumpPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// 八连通网格上的 Jump Point Search，返回的路径包含沿途每一个格子
+pub fn jump_point_search(grid: &Grid, start: Point, goal: Point) -> Option<Path<Point, u32>> {
+    if !grid.open(start) || !grid.open(goal) {
+        return None;
+    }
+    let mut cost = HashMap::from([(start, 0)]);
+    let mut parent: HashMap<Point, Point> = HashMap::new();
+    let mut heap = BinaryHeap::from([JumpPoint {
+        estimate: octile(start, goal),
+        cost: 0,
+        point: start,
+    }]);
+
+    while let Some(JumpPoint {
+        cost: current_cost,
+        point: current,
+        ..
+    }) = heap.pop()
+    {
+        if current_cost > cost[&current] {
+            continue;
+        }
+        if current == goal {
+            return Some(Path {
+                cost: current_cost,
+                nodes: expand(&parent, goal),
+            });
+        }
+        for (dx, dy) in pruned_directions(grid, current, parent.get(&current).copied()) {
+            let Some(next) = jump(grid, current, dx, dy, goal) else {
+                continue;
+            };
+            let next_cost = current_cost + octile(current, next);
+            if cost.get(&next).is_none_or(|&best| next_cost < best) {
+                cost.insert(next, next_cost);
+                parent.insert(next, current);
+                heap.push(JumpPoint {
+                    estimate: next_cost + octile(next, goal),
+                    cost: next_cost,
+                    point: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+fn step((x, y): Point, dx: isize, dy: isize) -> (isize, isize) {
+    (x as isize + dx, y as isize + dy)
+}
+
+// 从 parent 跳到 point 之后值得继续搜索的方向。起点搜索所有方向；
+// 其余节点只沿原方向前进，再加上被障碍物"强迫"出来的转向
+fn pruned_directions(grid: &Grid, point: Point, parent: Option<Point>) -> Vec<(isize, isize)> {
+    let Some(parent) = parent else {
+        return grid
+            .neighbors(point, Connectivity::Eight)
+            .into_iter()
+            .map(|((nx, ny), _)| {
+                (
+                    nx as isize - point.0 as isize,
+                    ny as isize - point.1 as isize,
+                )
+            })
+            .collect();
+    };
+    let (x, y) = (point.0 as isize, point.1 as isize);
+    let dx = (x - parent.0 as isize).signum();
+    let dy = (y - parent.1 as isize).signum();
+    let open = |dx: isize, dy: isize| grid.is_open(x + dx, y + dy);
+    let mut directions = Vec::with_capacity(5);
+
+    if dx != 0 && dy != 0 {
+        if open(0, dy) {
+            directions.push((0, dy));
+        }
+        if open(dx, 0) {
+            directions.push((dx, 0));
+        }
+        if open(0, dy) && open(dx, 0) {
+            directions.push((dx, dy));
+        }
+    } else {
+        // 水平和竖直统一处理：forward 是前进方向，side 是它的垂直方向
+        let (forward, side) = if dx != 0 {
+            ((dx, 0), (0, 1))
+        } else {
+            ((0, dy), (1, 0))
+        };
+        let ahead = open(forward.0, forward.1);
+        for sign in [1, -1] {
+            let (sx, sy) = (side.0 * sign, side.1 * sign);
+            if open(sx, sy) {
+                if ahead {
+                    directions.push((forward.0 + sx, forward.1 + sy));
+                }
+                directions.push((sx, sy));
+            }
+        }
+        if ahead {
+            directions.push(forward);
+        }
+    }
+    directions
+}
+
+// 从 from 沿 (dx, dy) 前进，返回遇到的第一个跳点：终点、有强迫邻居的格子，
+// 或者斜走时能沿水平/竖直方向找到跳点的格子
+fn jump(grid: &Grid, from: Point, dx: isize, dy: isize, goal: Point) -> Option<Point> {
+    let (mut x, mut y) = step(from, dx, dy);
+    loop {
+        if !grid.is_open(x, y) {
+            return None;
+        }
+        let point = (x as usize, y as usize);
+        if point == goal {
+            return Some(point);
+        }
+        let open = |ox: isize, oy: isize| grid.is_open(x + ox, y + oy);
+        if dx != 0 && dy != 0 {
+            if jump(grid, point, dx, 0, goal).is_some() || jump(grid, point, 0, dy, goal).is_some()
+            {
+                return Some(point);
+            }
+        } else if dx != 0 {
+            if (open(0, -1) && !open(-dx, -1)) || (open(0, 1) && !open(-dx, 1)) {
+                return Some(point);
+            }
+        } else if (open(-1, 0) && !open(-1, -dy)) || (open(1, 0) && !open(1, -dy)) {
+            return Some(point);
+        }
+        // 斜走要求两侧直邻格都可走；直走时第二个条件就是当前格
+        if !(open(dx, 0) && open(0, dy)) {
+            return None;
+        }
+        x += dx;
+        y += dy;
+    }
+}
+
+// 把跳点之间的直线或对角线段展开成逐格的路径
+fn expand(parent: &HashMap<Point, Point>, goal: Point) -> Vec<Point> {
+    let mut jump_points = vec![goal];
+    while let Some(&previous) = parent.get(jump_points.last().unwrap()) {
+        jump_points.push(previous);
+    }
+    jump_points.reverse();
+
+    let mut path = vec![jump_points[0]];
+    for pair in jump_points.windows(2) {
+        let (mut current, target) = (pair[0], pair[1]);
+        let dx = (target.0 as isize - current.0 as isize).signum();
+        let dy = (target.1 as isize - current.1 as isize).signum();
+        while current != target {
+            let (x, y) = step(current, dx, dy);
+            current = (x as usize, y as usize);
+            path.push(current);
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Lcg;
+
+    const MAP: &str = "
+S....#....
+.###.#.##.
+...#...#..
+.#.#####.#
+.#.......G
+";
+
+    // 路径上每一步都是合法的一步移动，代价之和等于 cost
+    fn check(grid: &Grid, path: &Path<Point, u32>, connectivity: Connectivity) {
+        let mut total = 0;
+        for pair in path.nodes.windows(2) {
+            let (_, step_cost) = grid
+                .neighbors(pair[0], connectivity)
+                .into_iter()
+                .find(|&(next, _)| next == pair[1])
+                .unwrap_or_else(|| panic!("{:?} -> {:?} 不是合法的一步", pair[0], pair[1]));
+            total += step_cost;
+        }
+        assert_eq!(total, path.cost);
+    }
+
+    #[test]
+    fn parses_and_renders_maps() {
+        let map = Grid::parse(MAP).unwrap();
+        assert_eq!((map.grid.width(), map.grid.height()), (10, 5));
+        assert_eq!((map.start, map.goal), (Some((0, 0)), Some((9, 4))));
+        assert!(!map.grid.is_open(5, 0));
+        assert!(!map.grid.is_open(-1, 0));
+
+        assert_eq!(Grid::parse(""), Err(GridError::Empty));
+        assert_eq!(
+            Grid::parse("..\n...\n"),
+            Err(GridError::UnevenRows { row: 1 })
+        );
+        assert_eq!(Grid::parse("S.S"), Err(GridError::Duplicate('S')));
+        assert!(matches!(
+            Grid::parse(".?."),
+            Err(GridError::UnknownCell { column: 1, .. })
+        ));
+
+        let path = find_path(&map.grid, (0, 0), (9, 4), Connectivity::Four).unwrap();
+        let rendered = map.grid.render(&path.nodes);
+        assert_eq!(Grid::parse(&rendered.replace('*', ".")).unwrap(), map);
+    }
+
+    #[test]
+    fn four_and_eight_connected_paths() {
+        let map = Grid::parse(MAP).unwrap();
+        let (start, goal) = (map.start.unwrap(), map.goal.unwrap());
+
+        let four = find_path(&map.grid, start, goal, Connectivity::Four).unwrap();
+        check(&map.grid, &four, Connectivity::Four);
+        assert_eq!(four.cost, 13 * STRAIGHT_COST);
+
+        let eight = find_path(&map.grid, start, goal, Connectivity::Eight).unwrap();
+        check(&map.grid, &eight, Connectivity::Eight);
+        // 走廊里不能切角，斜走帮不上忙；空地上斜走更短
+        assert!(eight.cost <= four.cost);
+        let open = Grid::new(5, 5);
+        let diagonal = find_path(&open, (0, 0), (4, 4), Connectivity::Eight).unwrap();
+        assert_eq!(diagonal.cost, 4 * DIAGONAL_COST);
+        assert_eq!(diagonal, jump_point_search(&open, (0, 0), (4, 4)).unwrap());
+
+        let mut walled = map.grid.clone();
+        walled.set_blocked((8, 4), true);
+        walled.set_blocked((8, 3), true);
+        assert_eq!(find_path(&walled, start, goal, Connectivity::Eight), None);
+        assert_eq!(jump_point_search(&walled, start, goal), None);
+    }
+
+    #[test]
+    fn jump_point_search_matches_a_star() {
+        let mut lcg = Lcg::new(0x2545_f491);
+        let mut random = move |n: usize| lcg.below(n as u64) as usize;
+        for _ in 0..50 {
+            let (width, height) = (5 + random(30), 5 + random(30));
+            let mut grid = Grid::new(width, height);
+            for _ in 0..width * height / 4 {
+                grid.set_blocked((random(width), random(height)), true);
+            }
+            let start = (random(width), random(height));
+            let goal = (random(width), random(height));
+
+            let expected = find_path(&grid, start, goal, Connectivity::Eight);
+            let found = jump_point_search(&grid, start, goal);
+            assert_eq!(
+                found.as_ref().map(|path| path.cost),
+                expected.as_ref().map(|path| path.cost),
+                "\n{}",
+                grid.render(&[start, goal])
+            );
+            if let Some(path) = found {
+                assert_eq!((path.nodes[0], *path.nodes.last().unwrap()), (start, goal));
+                check(&grid, &path, Connectivity::Eight);
+            }
+        }
+    }
+}
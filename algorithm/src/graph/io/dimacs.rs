@@ -0,0 +1,170 @@
+// DIMACS 最短路格式
+//   c 注释
+//   p sp <节点数> <边数>
+//   a <起点> <终点> <权重>
+// 坐标文件(.co)里是 "v <节点> <x> <y>"，路网中是经纬度乘以 10⁶，可以给 A* 做启发函数。
+// 节点按 1..=n 的顺序加入图，所以第 i 个节点的下标是 i - 1。
+use super::{number, ParseError};
+use crate::graph::{Graph, Weight};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+pub fn read_dimacs<W>(reader: impl BufRead) -> Result<Graph<usize, W>, ParseError>
+where
+    W: Weight + FromStr,
+{
+    let mut graph = Graph::new();
+    // (节点数, 边数)
+    let mut problem: Option<(usize, usize)> = None;
+    let mut found = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let (line, text) = (i + 1, line?);
+        let fields: Vec<&str> = text.split_whitespace().collect();
+        match fields.as_slice() {
+            [] | ["c", ..] => {}
+            ["p", "sp", nodes, edges] => {
+                let nodes = number(nodes, line)?;
+                problem = Some((nodes, number(edges, line)?));
+                for node in 1..=nodes {
+                    graph.add_node(node);
+                }
+            }
+            ["a", from, to, weight] => {
+                let (nodes, _) = problem.ok_or(ParseError::MissingProblemLine)?;
+                let from = node(from, nodes, line)?;
+                let to = node(to, nodes, line)?;
+                graph.add_edge(from, to, number(weight, line)?);
+                found += 1;
+            }
+            _ => {
+                return Err(ParseError::Syntax {
+                    line,
+                    expected: "c ...、p sp <节点数> <边数> 或 a <起点> <终点> <权重>",
+                })
+            }
+        }
+    }
+    let (_, expected) = problem.ok_or(ParseError::MissingProblemLine)?;
+    if expected != found {
+        return Err(ParseError::EdgeCount { expected, found });
+    }
+    Ok(graph)
+}
+
+// 节点编号 -> (x, y)
+pub fn read_dimacs_coordinates(
+    reader: impl BufRead,
+) -> Result<HashMap<usize, (i64, i64)>, ParseError> {
+    let mut coordinates = HashMap::new();
+    for (i, line) in reader.lines().enumerate() {
+        let (line, text) = (i + 1, line?);
+        let fields: Vec<&str> = text.split_whitespace().collect();
+        match fields.as_slice() {
+            [] | ["c", ..] | ["p", ..] => {}
+            ["v", id, x, y] => {
+                coordinates.insert(number(id, line)?, (number(x, line)?, number(y, line)?));
+            }
+            _ => {
+                return Err(ParseError::Syntax {
+                    line,
+                    expected: "v <节点> <x> <y>",
+                })
+            }
+        }
+    }
+    Ok(coordinates)
+}
+
+// 按节点下标编号为 1..=n 写出，节点本身的值不写入文件
+pub fn write_dimacs<N, W>(graph: &Graph<N, W>, mut out: impl Write) -> io::Result<()>
+where
+    W: Weight + Display,
+{
+    writeln!(out, "p sp {} {}", graph.node_count(), graph.edge_count())?;
+    for (from, to, weight) in graph.edges() {
+        writeln!(out, "a {} {} {}", from + 1, to + 1, weight)?;
+    }
+    out.flush()
+}
+
+fn node(text: &str, nodes: usize, line: usize) -> Result<usize, ParseError> {
+    let node = number(text, line)?;
+    if node == 0 || node > nodes {
+        return Err(ParseError::NodeOutOfRange { line, node });
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+
+    const SAMPLE: &str = "c 9th DIMACS Implementation Challenge 的示例
+c
+p sp 4 5
+a 1 2 7
+a 1 3 2
+a 3 2 3
+a 2 4 1
+a 3 4 9
+";
+
+    #[test]
+    fn reads_and_writes_shortest_path_files() {
+        let graph: Graph<usize, u32> = read_dimacs(SAMPLE.as_bytes()).unwrap();
+        assert_eq!((graph.node_count(), graph.edge_count()), (4, 5));
+        let path = dijkstra(&graph, &1, &4).unwrap();
+        assert_eq!((path.nodes, path.cost), (vec![1, 3, 2, 4], 6));
+
+        let mut written = Vec::new();
+        write_dimacs(&graph, &mut written).unwrap();
+        let reread: Graph<usize, u32> = read_dimacs(written.as_slice()).unwrap();
+        assert_eq!(
+            reread.edges().collect::<Vec<_>>(),
+            graph.edges().collect::<Vec<_>>()
+        );
+
+        let coordinates = read_dimacs_coordinates(
+            "p aux sp co 2\nv 1 -73530767 41085396\nv 2 -73530538 41086098\n".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(coordinates[&2], (-73530538, 41086098));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let read = |text: &str| read_dimacs::<u32>(text.as_bytes());
+        assert!(matches!(
+            read("a 1 2 3\n"),
+            Err(ParseError::MissingProblemLine)
+        ));
+        assert!(matches!(
+            read("c only\n"),
+            Err(ParseError::MissingProblemLine)
+        ));
+        assert!(matches!(
+            read("p sp 2 1\na 1 3 4\n"),
+            Err(ParseError::NodeOutOfRange { line: 2, node: 3 })
+        ));
+        assert!(matches!(
+            read("p sp 2 2\na 1 2 4\n"),
+            Err(ParseError::EdgeCount {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            read("p sp 2 1\na 1 2 -4\n"),
+            Err(ParseError::BadNumber { line: 2, .. })
+        ));
+        assert!(matches!(
+            read("p sp 2 1\nx\n"),
+            Err(ParseError::Syntax { line: 2, .. })
+        ));
+        // 有符号的权重可以是负数
+        assert!(read_dimacs::<i64>("p sp 2 1\na 1 2 -4\n".as_bytes()).is_ok());
+    }
+}
@@ -0,0 +1,427 @@
+// Graphviz DOT
+// 读取支持常见的子集：[strict] graph/digraph、节点语句、边链 a -> b -> c、属性列表、
+// 图属性 key = value 以及 graph/node/edge 默认属性(后两者被忽略)；不支持 subgraph 和端口。
+// 边权取 weight 属性，没有时取 label 属性。无向图(graph 和 --)的每条边按两个方向加入。
+// 写出时边权写在 label 上，可以给出一条路径，路径上的节点和边标成红色加粗。
+use super::{number, ParseError};
+use crate::graph::{Graph, Path, Weight};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use std::iter::Peekable;
+use std::str::FromStr;
+
+const HIGHLIGHT: &str = "color=red, penwidth=2";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Id(String),
+    // -> 为 true，-- 为 false
+    Edge(bool),
+    Open,
+    Close,
+    OpenAttributes,
+    CloseAttributes,
+    Equals,
+    // ; 或 ,
+    Separator,
+}
+
+pub fn read_dot<W>(mut reader: impl Read) -> Result<Graph<String, W>, ParseError>
+where
+    W: Weight + FromStr,
+{
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut tokens = tokenize(&text)?.into_iter().peekable();
+    let mut graph = Graph::new();
+
+    let (mut line, mut keyword) = expect_id(&mut tokens, "graph 或 digraph")?;
+    if keyword == "strict" {
+        (line, keyword) = expect_id(&mut tokens, "graph 或 digraph")?;
+    }
+    let directed = match keyword.as_str() {
+        "digraph" => true,
+        "graph" => false,
+        _ => {
+            return Err(ParseError::Syntax {
+                line,
+                expected: "graph 或 digraph",
+            })
+        }
+    };
+    if let Some((_, Token::Id(_))) = tokens.peek() {
+        tokens.next();
+    }
+    expect(&mut tokens, Token::Open, "{")?;
+
+    loop {
+        let (line, token) = tokens
+            .next()
+            .ok_or(ParseError::UnexpectedEnd { expected: "}" })?;
+        let id = match token {
+            Token::Close => break,
+            Token::Separator => continue,
+            Token::Id(id) => id,
+            _ => {
+                return Err(ParseError::Syntax {
+                    line,
+                    expected: "节点、边或属性语句",
+                })
+            }
+        };
+        match (id.as_str(), tokens.peek()) {
+            ("graph" | "node" | "edge", Some((_, Token::OpenAttributes))) => {
+                attributes(&mut tokens)?;
+            }
+            ("subgraph", _) => {
+                return Err(ParseError::Syntax {
+                    line,
+                    expected: "不含 subgraph 的语句",
+                })
+            }
+            (_, Some((_, Token::Equals))) => {
+                tokens.next();
+                expect_id(&mut tokens, "属性值")?;
+            }
+            _ => {
+                let mut chain = vec![id];
+                while let Some(&(line, Token::Edge(arrow))) = tokens.peek() {
+                    if arrow != directed {
+                        let expected = if directed { "->" } else { "--" };
+                        return Err(ParseError::Syntax { line, expected });
+                    }
+                    tokens.next();
+                    chain.push(expect_id(&mut tokens, "节点")?.1);
+                }
+                let attributes = attributes(&mut tokens)?;
+                if chain.len() == 1 {
+                    graph.add_node(chain.pop().expect("只有一个节点"));
+                    continue;
+                }
+                let weight = attributes
+                    .get("weight")
+                    .or_else(|| attributes.get("label"))
+                    .ok_or(ParseError::Syntax {
+                        line,
+                        expected: "带 weight 或 label 属性的边",
+                    })?;
+                let weight: W = number(weight, line)?;
+                for pair in chain.windows(2) {
+                    if directed {
+                        graph.add_edge(pair[0].clone(), pair[1].clone(), weight);
+                    } else {
+                        graph.add_undirected_edge(pair[0].clone(), pair[1].clone(), weight);
+                    }
+                }
+            }
+        }
+    }
+    Ok(graph)
+}
+
+// path 不为 None 时高亮路径上的节点，以及每一步中权重最小的那条边
+pub fn write_dot<N, W>(
+    graph: &Graph<N, W>,
+    path: Option<&Path<N, W>>,
+    mut out: impl Write,
+) -> io::Result<()>
+where
+    N: Clone + Eq + Hash + Display,
+    W: Weight + Display,
+{
+    let mut on_path = vec![false; graph.node_count()];
+    // (起点下标, 终点下标) -> 这一步要高亮的边的权重
+    let mut steps = HashMap::new();
+    if let Some(path) = path {
+        let indices: Vec<usize> = path
+            .nodes
+            .iter()
+            .filter_map(|node| graph.index_of(node))
+            .collect();
+        for &i in &indices {
+            on_path[i] = true;
+        }
+        for pair in indices.windows(2) {
+            let lightest = graph
+                .neighbors(pair[0])
+                .iter()
+                .filter(|edge| edge.to == pair[1])
+                .map(|edge| edge.weight)
+                .min();
+            if let Some(weight) = lightest {
+                steps.insert((pair[0], pair[1]), weight);
+            }
+        }
+    }
+
+    writeln!(out, "digraph G {{")?;
+    for (i, node) in graph.nodes().iter().enumerate() {
+        let style = if on_path[i] {
+            format!(" [{}]", HIGHLIGHT)
+        } else {
+            String::new()
+        };
+        writeln!(out, "  {}{};", quote(&node.to_string()), style)?;
+    }
+    for (from, to, weight) in graph.edges() {
+        let highlight = steps.get(&(from, to)) == Some(&weight);
+        if highlight {
+            // 平行边只高亮一条
+            steps.remove(&(from, to));
+        }
+        writeln!(
+            out,
+            "  {} -> {} [label={}{}];",
+            quote(&graph.node(from).to_string()),
+            quote(&graph.node(to).to_string()),
+            quote(&weight.to_string()),
+            if highlight {
+                format!(", {}", HIGHLIGHT)
+            } else {
+                String::new()
+            }
+        )?;
+    }
+    writeln!(out, "}}")?;
+    out.flush()
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '#' => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                continue;
+            }
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let mut previous = ' ';
+                loop {
+                    let c = chars
+                        .next()
+                        .ok_or(ParseError::UnexpectedEnd { expected: "*/" })?;
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                continue;
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '[' => Token::OpenAttributes,
+            ']' => Token::CloseAttributes,
+            '=' => Token::Equals,
+            ';' | ',' => Token::Separator,
+            '-' if chars.next_if_eq(&'>').is_some() => Token::Edge(true),
+            '-' if chars.next_if_eq(&'-').is_some() => Token::Edge(false),
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                loop {
+                    match chars
+                        .next()
+                        .ok_or(ParseError::UnexpectedEnd { expected: "\"" })?
+                    {
+                        '"' => break,
+                        // 只有 \" 是转义，其余反斜杠原样保留(DOT 的 \n 等是给标签排版用的)
+                        '\\' if chars.peek() == Some(&'"') => {
+                            id.push(chars.next().expect("刚看过"))
+                        }
+                        '\\' if chars.peek() == Some(&'\\') => {
+                            id.push(chars.next().expect("刚看过"))
+                        }
+                        c => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                        }
+                    }
+                }
+                tokens.push((start, Token::Id(id)));
+                continue;
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = c.to_string();
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == '.')
+                {
+                    id.push(c);
+                }
+                Token::Id(id)
+            }
+            _ => {
+                return Err(ParseError::Syntax {
+                    line,
+                    expected: "DOT 的标识符、字符串或符号",
+                })
+            }
+        };
+        tokens.push((line, token));
+    }
+    Ok(tokens)
+}
+
+type Tokens = Peekable<std::vec::IntoIter<(usize, Token)>>;
+
+fn expect(
+    tokens: &mut Tokens,
+    expected: Token,
+    description: &'static str,
+) -> Result<usize, ParseError> {
+    match tokens.next() {
+        Some((line, token)) if token == expected => Ok(line),
+        Some((line, _)) => Err(ParseError::Syntax {
+            line,
+            expected: description,
+        }),
+        None => Err(ParseError::UnexpectedEnd {
+            expected: description,
+        }),
+    }
+}
+
+fn expect_id(
+    tokens: &mut Tokens,
+    description: &'static str,
+) -> Result<(usize, String), ParseError> {
+    match tokens.next() {
+        Some((line, Token::Id(id))) => Ok((line, id)),
+        Some((line, _)) => Err(ParseError::Syntax {
+            line,
+            expected: description,
+        }),
+        None => Err(ParseError::UnexpectedEnd {
+            expected: description,
+        }),
+    }
+}
+
+// 零个或多个紧挨着的 [key = value, ...]
+fn attributes(tokens: &mut Tokens) -> Result<HashMap<String, String>, ParseError> {
+    let mut attributes = HashMap::new();
+    while tokens
+        .next_if(|(_, token)| *token == Token::OpenAttributes)
+        .is_some()
+    {
+        loop {
+            match tokens.next() {
+                Some((_, Token::CloseAttributes)) => break,
+                Some((_, Token::Separator)) => continue,
+                Some((_, Token::Id(key))) => {
+                    expect(tokens, Token::Equals, "=")?;
+                    let (_, value) = expect_id(tokens, "属性值")?;
+                    attributes.insert(key, value);
+                }
+                Some((line, _)) => {
+                    return Err(ParseError::Syntax {
+                        line,
+                        expected: "key = value",
+                    })
+                }
+                None => return Err(ParseError::UnexpectedEnd { expected: "]" }),
+            }
+        }
+    }
+    Ok(attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+
+    #[test]
+    fn reads_common_dot_files() {
+        let text = r#"
+            // 路网
+            strict digraph "roads" {
+                rankdir = LR;
+                node [shape=circle]
+                A -> B [weight=7]; A -> C [label="9"]
+                C -> F -> E [weight = 2, color=blue]
+                /* 孤立的
+                   节点 */
+                "Z \"zone\"";
+            }
+        "#;
+        let graph: Graph<String, u32> = read_dot(text.as_bytes()).unwrap();
+        assert_eq!((graph.node_count(), graph.edge_count()), (6, 4));
+        assert!(graph.index_of(&"Z \"zone\"".to_string()).is_some());
+        let path = dijkstra(&graph, &"A".to_string(), &"E".to_string()).unwrap();
+        assert_eq!(path.cost, 13);
+
+        let undirected: Graph<String, i32> =
+            read_dot("graph { a -- b [weight=-1] }".as_bytes()).unwrap();
+        assert_eq!(undirected.edge_count(), 2);
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        let read = |text: &str| read_dot::<u32>(text.as_bytes());
+        assert!(matches!(
+            read("digraph {\n a -- b [weight=1]\n}"),
+            Err(ParseError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            read("digraph {\n\n a -> b\n}"),
+            Err(ParseError::Syntax { line: 3, .. })
+        ));
+        assert!(matches!(
+            read("digraph { a -> b [weight=x] }"),
+            Err(ParseError::BadNumber { line: 1, .. })
+        ));
+        assert!(matches!(
+            read("digraph { a -> b [weight=1]"),
+            Err(ParseError::UnexpectedEnd { .. })
+        ));
+        assert!(matches!(read("tree { }"), Err(ParseError::Syntax { .. })));
+    }
+
+    #[test]
+    fn writes_and_highlights_paths() {
+        let graph = Graph::from_edges([
+            ("A".to_string(), "B".to_string(), 7u32),
+            ("A".to_string(), "C".to_string(), 2),
+            ("C".to_string(), "B".to_string(), 3),
+            ("C".to_string(), "B".to_string(), 4),
+            ("B".to_string(), "say \"hi\"".to_string(), 1),
+        ]);
+        let path = dijkstra(&graph, &"A".to_string(), &"say \"hi\"".to_string()).unwrap();
+        let mut written = Vec::new();
+        write_dot(&graph, Some(&path), &mut written).unwrap();
+        let text = String::from_utf8(written).unwrap();
+
+        // 路径上 4 个节点和 3 条边被高亮，平行边只高亮权重小的一条
+        assert_eq!(text.matches(HIGHLIGHT).count(), 7);
+        assert!(text.contains(r#""C" -> "B" [label="3", color=red, penwidth=2];"#));
+        assert!(text.contains(r#""C" -> "B" [label="4"];"#));
+
+        let reread: Graph<String, u32> = read_dot(text.as_bytes()).unwrap();
+        assert_eq!(reread.nodes(), graph.nodes());
+        assert_eq!(
+            reread.edges().collect::<Vec<_>>(),
+            graph.edges().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// 边表：每行一条边 "起点 终点 权重"。
+// 行中有逗号时按逗号分隔(CSV，字段两边的引号和空白会去掉)，否则按空白分隔。
+// 空行和 # 开头的行被忽略；第一行数据的三个字段都不是数值时当作表头跳过。是不是数值按 f64 判断，
+// 与权重的类型无关，所以第一行的权重不合法(例如无符号权重读到 -5)时和其他行一样报错。
+use super::{number, ParseError};
+use crate::graph::{Graph, Weight};
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+pub fn read_edge_list<W>(reader: impl BufRead) -> Result<Graph<String, W>, ParseError>
+where
+    W: Weight + FromStr,
+{
+    let mut graph = Graph::new();
+    let mut first = true;
+    for (i, line) in reader.lines().enumerate() {
+        let (line, text) = (i + 1, line?);
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = if text.contains(',') {
+            text.split(',')
+                .map(|field| field.trim().trim_matches('"'))
+                .collect()
+        } else {
+            text.split_whitespace().collect()
+        };
+        let [from, to, weight] = fields.as_slice() else {
+            return Err(ParseError::Syntax {
+                line,
+                expected: "起点 终点 权重",
+            });
+        };
+        let header = first && fields.iter().all(|field| field.parse::<f64>().is_err());
+        first = false;
+        if header {
+            continue;
+        }
+        graph.add_edge(from.to_string(), to.to_string(), number(weight, line)?);
+    }
+    Ok(graph)
+}
+
+// delimiter 一般是 ' '、'\t' 或 ','；节点按 Display 写出，本身含有分隔符时读回来会出错
+pub fn write_edge_list<N, W>(
+    graph: &Graph<N, W>,
+    delimiter: char,
+    mut out: impl Write,
+) -> io::Result<()>
+where
+    N: Display,
+    W: Weight + Display,
+{
+    for (from, to, weight) in graph.edges() {
+        writeln!(
+            out,
+            "{}{d}{}{d}{}",
+            graph.node(from),
+            graph.node(to),
+            weight,
+            d = delimiter
+        )?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+
+    #[test]
+    fn reads_csv_and_whitespace_lists() {
+        let csv = "from,to,cost\n\"home\", \"shop\", 4\nhome,park,1\npark,shop,2\n";
+        let graph: Graph<String, u32> = read_edge_list(csv.as_bytes()).unwrap();
+        assert_eq!(graph.edge_count(), 3);
+        let path = dijkstra(&graph, &"home".to_string(), &"shop".to_string()).unwrap();
+        assert_eq!(
+            (path.nodes, path.cost),
+            (vec!["home".into(), "park".into(), "shop".into()], 3)
+        );
+
+        let spaces = "# 注释\n\nhome\tshop 4\n  home   park 1\npark shop 2\n";
+        let same: Graph<String, u32> = read_edge_list(spaces.as_bytes()).unwrap();
+        assert_eq!(
+            same.edges().collect::<Vec<_>>(),
+            graph.edges().collect::<Vec<_>>()
+        );
+
+        for delimiter in [',', '\t'] {
+            let mut written = Vec::new();
+            write_edge_list(&graph, delimiter, &mut written).unwrap();
+            let reread: Graph<String, u32> = read_edge_list(written.as_slice()).unwrap();
+            assert_eq!(reread.nodes(), graph.nodes());
+            assert_eq!(
+                reread.edges().collect::<Vec<_>>(),
+                graph.edges().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let read = |text: &str| read_edge_list::<u32>(text.as_bytes());
+        assert!(matches!(
+            read("a b 1\na b\n"),
+            Err(ParseError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            read("a b 1\na b x\n"),
+            Err(ParseError::BadNumber { line: 2, .. })
+        ));
+        // 只有第一行可以是表头，而且其中不能有数值字段
+        assert_eq!(read("from to weight\nb c 2\n").unwrap().edge_count(), 1);
+        assert!(matches!(
+            read("1 2 x\nb c 2\n"),
+            Err(ParseError::BadNumber { line: 1, .. })
+        ));
+        assert!(matches!(
+            read("from to weight\nsource target cost\n"),
+            Err(ParseError::BadNumber { line: 2, .. })
+        ));
+        // 第一行的权重是数值但不是权重类型时同样报错，不当作表头
+        assert!(matches!(
+            read("a b -5\nb c 2\n"),
+            Err(ParseError::BadNumber { line: 1, .. })
+        ));
+        assert!(matches!(
+            read_edge_list::<i64>("a b 1.5\nb c 2\n".as_bytes()),
+            Err(ParseError::BadNumber { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+// 图的导入导出。
+// DIMACS 最短路格式(9th DIMACS Challenge 的 .gr 和 .co 文件)：节点是 1..=n 的编号；
+// 边表：每行 "起点 终点 权重"，用逗号或空白分隔，可以用来读 CSV；
+// Graphviz DOT：读常见的子集，写出时可以高亮一条路径，用 dot -Tsvg 就能看结果。
+// 读取都是逐行或整段从 reader 读，写出到任意 io::Write，大的路网文件不必整个放进 String。
+use std::fmt;
+use std::io;
+
+pub mod dimacs;
+pub mod dot;
+pub mod edge_list;
+
+pub use dimacs::{read_dimacs, read_dimacs_coordinates, write_dimacs};
+pub use dot::{read_dot, write_dot};
+pub use edge_list::{read_edge_list, write_edge_list};
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    // 第 line 行(从 1 开始)不符合 expected 描述的格式
+    Syntax { line: usize, expected: &'static str },
+    BadNumber { line: usize, text: String },
+    // DIMACS 文件在 "p sp" 行之前出现了边，或者没有 "p sp" 行
+    MissingProblemLine,
+    NodeOutOfRange { line: usize, node: usize },
+    EdgeCount { expected: usize, found: usize },
+    // DOT 文件在 expected 之前就结束了
+    UnexpectedEnd { expected: &'static str },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(err) => write!(f, "读取失败: {}", err),
+            ParseError::Syntax { line, expected } => {
+                write!(f, "第 {} 行格式错误，应为 {}", line, expected)
+            }
+            ParseError::BadNumber { line, text } => {
+                write!(f, "第 {} 行的 {:?} 不是合法的数值", line, text)
+            }
+            ParseError::MissingProblemLine => write!(f, "缺少 \"p sp <节点数> <边数>\" 行"),
+            ParseError::NodeOutOfRange { line, node } => {
+                write!(f, "第 {} 行的节点 {} 超出了声明的节点数", line, node)
+            }
+            ParseError::EdgeCount { expected, found } => {
+                write!(f, "声明了 {} 条边，实际有 {} 条", expected, found)
+            }
+            ParseError::UnexpectedEnd { expected } => {
+                write!(f, "文件提前结束，缺少 {}", expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        ParseError::Io(err)
+    }
+}
+
+// 解析第 line 行的一个数值字段
+fn number<T: std::str::FromStr>(text: &str, line: usize) -> Result<T, ParseError> {
+    text.parse().map_err(|_| ParseError::BadNumber {
+        line,
+        text: text.to_string(),
+    })
+}
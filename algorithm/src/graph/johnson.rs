@@ -0,0 +1,144 @@
+// Johnson算法（稀疏图的全源最短路，允许负权边）
+// 先用 Bellman-Ford 从连向所有节点的虚拟源点求出势能 h(都不大于 0)，把边权改成 w + h(u) - h(v)。
+// 改过的边权都非负，两点间各条路径的长度差不变，于是可以从每个节点各跑一次 Dijkstra，
+// 最后把距离换回原来的权重。复杂度 O(nm log n)，稀疏图上比 Floyd-Warshall 快。
+use super::bellman_ford::{cycle_path, relax};
+use super::dijkstra::search;
+use super::{AllPairs, Graph, PathError, Weight};
+use std::hash::Hash;
+
+pub fn johnson<N, W>(graph: &Graph<N, W>) -> Result<AllPairs<'_, N, W>, PathError<N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let n = graph.node_count();
+    let (potential, _) = relax(graph, vec![Some(W::zero()); n])
+        .map_err(|cycle| PathError::NegativeCycle(cycle_path(graph, &cycle)))?;
+    let h: Vec<W> = potential.into_iter().flatten().collect();
+
+    // w + h(u) 不会溢出(结果介于 h(v) 和 w 之间)，再减去 h(v) 溢出的边当作不可达
+    let reweighted = graph
+        .filter_map_weights(|from, to, weight| weight.checked_add(h[from])?.checked_sub(h[to]));
+    let (dist, pred) = (0..n)
+        .map(|source| {
+            let (dist, pred) = search(&reweighted, source, |_| false);
+            let dist = dist
+                .into_iter()
+                .zip(&h)
+                .map(|(cost, &to)| cost?.checked_add(to)?.checked_sub(h[source]))
+                .collect();
+            (dist, pred)
+        })
+        .unzip();
+    Ok(AllPairs::new(graph, dist, pred))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{bellman_ford, dijkstra_all, floyd_warshall, Path};
+    use crate::test_utils::Lcg;
+
+    // 节点 0..n 按顺序加入，下标就是节点 id
+    fn random_graph(
+        random: &mut Lcg,
+        mut weight: impl FnMut(usize, usize, i64) -> i64,
+    ) -> Graph<usize, i64> {
+        let n = 1 + random.below(12) as usize;
+        let mut graph = Graph::new();
+        for node in 0..n {
+            graph.add_node(node);
+        }
+        for _ in 0..random.below(3 * n as u64 + 1) {
+            let from = random.below(n as u64) as usize;
+            let to = random.below(n as u64) as usize;
+            let base = random.below(20) as i64;
+            graph.add_edge(from, to, weight(from, to, base));
+        }
+        graph
+    }
+
+    // 路径上每一步都是图中的边，取最小的平行边时权重之和等于 cost
+    fn check(graph: &Graph<usize, i64>, path: &Path<usize, i64>) {
+        let mut total = 0;
+        for pair in path.nodes.windows(2) {
+            total += graph
+                .neighbors(pair[0])
+                .iter()
+                .filter(|edge| edge.to == pair[1])
+                .map(|edge| edge.weight)
+                .min()
+                .unwrap();
+        }
+        assert_eq!(total, path.cost);
+    }
+
+    #[test]
+    fn agrees_with_dijkstra_on_non_negative_graphs() {
+        let mut random = Lcg::new(17);
+        for _ in 0..100 {
+            let graph = random_graph(&mut random, |_, _, base| base);
+            let floyd = floyd_warshall(&graph).unwrap();
+            let all = johnson(&graph).unwrap();
+            for source in 0..graph.node_count() {
+                let expected = dijkstra_all(&graph, &source).unwrap();
+                let bellman = bellman_ford(&graph, &source).unwrap();
+                assert_eq!(bellman.distances(), expected.distances());
+                assert_eq!(floyd.distances()[source], expected.distances());
+                assert_eq!(all.distances()[source], expected.distances());
+                for to in 0..graph.node_count() {
+                    if let Some(path) = all.path(&source, &to) {
+                        check(&graph, &path);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_dijkstra_after_adding_rebates() {
+        // w(u, v) = base + p(u) - p(v)：任何环上势能相互抵消，所以没有负环，但会出现负权边。
+        // 此时 d(u, v) = d_base(u, v) + p(u) - p(v)，可以用 Dijkstra 在原始权重上验证
+        let mut random = Lcg::new(42);
+        for _ in 0..100 {
+            let potential: Vec<i64> = (0..12).map(|_| random.below(50) as i64).collect();
+            let mut bases = Graph::new();
+            let graph = random_graph(&mut random, |from, to, base| {
+                bases.add_edge(from, to, base);
+                base + potential[from] - potential[to]
+            });
+            for node in 0..graph.node_count() {
+                bases.add_node(node);
+            }
+            let floyd = floyd_warshall(&graph).unwrap();
+            let all = johnson(&graph).unwrap();
+            for source in 0..graph.node_count() {
+                let base = dijkstra_all(&bases, &source).unwrap();
+                let expected: Vec<Option<i64>> = (0..graph.node_count())
+                    .map(|to| Some(base.cost(&to)? + potential[source] - potential[to]))
+                    .collect();
+                let bellman = bellman_ford(&graph, &source).unwrap();
+                assert_eq!(bellman.distances(), expected);
+                assert_eq!(floyd.distances()[source], expected);
+                assert_eq!(all.distances()[source], expected);
+                for to in 0..graph.node_count() {
+                    if let Some(path) = floyd.path(&source, &to) {
+                        check(&graph, &path);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_negative_cycles() {
+        let graph =
+            Graph::from_edges([("a", "b", 2), ("b", "c", -4), ("c", "a", 1), ("c", "d", 3)]);
+        let Err(PathError::NegativeCycle(cycle)) = johnson(&graph) else {
+            panic!("应当发现负环");
+        };
+        assert_eq!(cycle.cost, -1);
+        assert_eq!(cycle.nodes.len(), 4);
+    }
+}
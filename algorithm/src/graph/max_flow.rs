@@ -0,0 +1,289 @@
+// Dinic 最大流 / 最小割
+// 边权是容量(负容量当作 0)。每个阶段先在残量网络上 BFS 分层，再沿层数递增的边反复找增广路，
+// 直到源点到汇点不再连通，复杂度 O(n²m)。结束时从源点在残量网络上还能到达的节点是割的源点一侧，
+// 从这一侧指向另一侧的原始边组成最小割，容量之和等于最大流。
+use super::{Graph, Weight};
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+#[derive(Debug, Clone)]
+pub struct MaxFlow<'a, N, W> {
+    graph: &'a Graph<N, W>,
+    value: W,
+    // 与 graph.edges() 一一对应的流量
+    flows: Vec<(usize, usize, W)>,
+    source_side: Vec<bool>,
+}
+
+impl<'a, N: Clone + Eq + Hash, W: Weight> MaxFlow<'a, N, W> {
+    pub fn value(&self) -> W {
+        self.value
+    }
+
+    // 流量大于 0 的边，按 (起点, 终点, 流量)
+    pub fn flows(&self) -> Vec<(N, N, W)> {
+        self.flows
+            .iter()
+            .filter(|&&(_, _, flow)| flow > W::zero())
+            .map(|&(from, to, flow)| {
+                (
+                    self.graph.node(from).clone(),
+                    self.graph.node(to).clone(),
+                    flow,
+                )
+            })
+            .collect()
+    }
+
+    // 割的源点一侧
+    pub fn source_side(&self) -> Vec<N> {
+        (0..self.graph.node_count())
+            .filter(|&i| self.source_side[i])
+            .map(|i| self.graph.node(i).clone())
+            .collect()
+    }
+
+    // 最小割的边，按 (起点, 终点, 容量)
+    pub fn min_cut(&self) -> Vec<(N, N, W)> {
+        self.graph
+            .edges()
+            .filter(|&(from, to, capacity)| {
+                self.source_side[from] && !self.source_side[to] && capacity > W::zero()
+            })
+            .map(|(from, to, capacity)| {
+                (
+                    self.graph.node(from).clone(),
+                    self.graph.node(to).clone(),
+                    capacity,
+                )
+            })
+            .collect()
+    }
+}
+
+// 残量网络中的边，第 2i 条是原图第 i 条边，第 2i+1 条是它的反向边
+#[derive(Debug, Clone, Copy)]
+struct Residual<W> {
+    from: usize,
+    to: usize,
+    capacity: W,
+}
+
+// source 或 sink 不在图中、或两者相同时返回 None。流量之和超出 W 的范围时 panic
+pub fn dinic<'a, N, W>(graph: &'a Graph<N, W>, source: &N, sink: &N) -> Option<MaxFlow<'a, N, W>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let source = graph.index_of(source)?;
+    let sink = graph.index_of(sink)?;
+    if source == sink {
+        return None;
+    }
+    let n = graph.node_count();
+    let mut residual = Vec::with_capacity(2 * graph.edge_count());
+    let mut outgoing = vec![Vec::new(); n];
+    for (from, to, capacity) in graph.edges() {
+        outgoing[from].push(residual.len());
+        residual.push(Residual {
+            from,
+            to,
+            capacity: capacity.max(W::zero()),
+        });
+        outgoing[to].push(residual.len());
+        residual.push(Residual {
+            from: to,
+            to: from,
+            capacity: W::zero(),
+        });
+    }
+
+    let mut value = W::zero();
+    loop {
+        let level = levels(&residual, &outgoing, source);
+        let Some(sink_level) = level[sink] else {
+            break;
+        };
+        // 比汇点更深的节点不可能在最短增广路上
+        let mut level: Vec<Option<usize>> = level
+            .into_iter()
+            .map(|l| l.filter(|&l| l <= sink_level))
+            .collect();
+        // next[v] 是 v 的出边中下一条要尝试的，之前的边已经证明走不通
+        let mut next = vec![0; n];
+        let mut path: Vec<usize> = Vec::new();
+        let mut v = source;
+        loop {
+            if v == sink {
+                let bottleneck = path
+                    .iter()
+                    .map(|&e| residual[e].capacity)
+                    .min()
+                    .expect("路径至少有一条边");
+                for &e in &path {
+                    residual[e].capacity = residual[e]
+                        .capacity
+                        .checked_sub(bottleneck)
+                        .expect("瓶颈不超过路径上的容量");
+                    residual[e ^ 1].capacity = residual[e ^ 1]
+                        .capacity
+                        .checked_add(bottleneck)
+                        .expect("流量之和溢出");
+                }
+                value = value.checked_add(bottleneck).expect("流量之和溢出");
+                // 退回到第一条饱和的边的起点，从那里继续找
+                let saturated = path
+                    .iter()
+                    .position(|&e| residual[e].capacity == W::zero())
+                    .expect("瓶颈边已经饱和");
+                v = residual[path[saturated]].from;
+                path.truncate(saturated);
+                continue;
+            }
+            let advance = outgoing[v][next[v]..].iter().position(|&e| {
+                let edge = residual[e];
+                edge.capacity > W::zero() && level[v].is_some_and(|l| level[edge.to] == Some(l + 1))
+            });
+            match advance {
+                Some(offset) => {
+                    next[v] += offset;
+                    let e = outgoing[v][next[v]];
+                    path.push(e);
+                    v = residual[e].to;
+                }
+                None => {
+                    if v == source {
+                        break;
+                    }
+                    // v 走不到汇点，本阶段不再经过它
+                    level[v] = None;
+                    let e = path.pop().expect("不在源点时路径非空");
+                    v = residual[e].from;
+                    next[v] += 1;
+                }
+            }
+        }
+    }
+
+    let source_side = levels(&residual, &outgoing, source)
+        .into_iter()
+        .map(|l| l.is_some())
+        .collect();
+    let flows = graph
+        .edges()
+        .enumerate()
+        .map(|(i, (from, to, _))| (from, to, residual[2 * i + 1].capacity))
+        .collect();
+    Some(MaxFlow {
+        graph,
+        value,
+        flows,
+        source_side,
+    })
+}
+
+// 残量网络上从 source 出发的 BFS 层数，走不到的节点为 None
+fn levels<W: Weight>(
+    residual: &[Residual<W>],
+    outgoing: &[Vec<usize>],
+    source: usize,
+) -> Vec<Option<usize>> {
+    let mut level = vec![None; outgoing.len()];
+    level[source] = Some(0);
+    let mut queue = VecDeque::from([source]);
+    while let Some(v) = queue.pop_front() {
+        for &e in &outgoing[v] {
+            let edge = residual[e];
+            if edge.capacity > W::zero() && level[edge.to].is_none() {
+                level[edge.to] = level[v].map(|l| l + 1);
+                queue.push_back(edge.to);
+            }
+        }
+    }
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Lcg;
+
+    #[test]
+    fn finds_max_flow_and_min_cut() {
+        let graph = Graph::from_edges([
+            ("s", "v1", 16),
+            ("s", "v2", 13),
+            ("v1", "v3", 12),
+            ("v2", "v1", 4),
+            ("v2", "v4", 14),
+            ("v3", "v2", 9),
+            ("v3", "t", 20),
+            ("v4", "v3", 7),
+            ("v4", "t", 4),
+        ]);
+        let flow = dinic(&graph, &"s", &"t").unwrap();
+        assert_eq!(flow.value(), 23);
+
+        let mut cut = flow.min_cut();
+        cut.sort();
+        assert_eq!(cut, [("v1", "v3", 12), ("v4", "t", 4), ("v4", "v3", 7)]);
+        let mut side = flow.source_side();
+        side.sort();
+        assert_eq!(side, ["s", "v1", "v2", "v4"]);
+
+        // 流量守恒，且不超过容量
+        let flows = flow.flows();
+        for node in ["v1", "v2", "v3", "v4"] {
+            let inflow: i32 = flows.iter().filter(|f| f.1 == node).map(|f| f.2).sum();
+            let outflow: i32 = flows.iter().filter(|f| f.0 == node).map(|f| f.2).sum();
+            assert_eq!(inflow, outflow);
+        }
+        for (from, to, amount) in flows {
+            let capacity = graph
+                .edges()
+                .find(|&(a, b, _)| graph.node(a) == &from && graph.node(b) == &to)
+                .unwrap()
+                .2;
+            assert!(amount <= capacity);
+        }
+
+        assert!(dinic(&graph, &"s", &"s").is_none());
+        assert!(dinic(&graph, &"s", &"x").is_none());
+        assert_eq!(dinic(&graph, &"t", &"s").unwrap().value(), 0);
+    }
+
+    #[test]
+    fn matches_brute_force_min_cut() {
+        let mut random = Lcg::new(2024);
+        for _ in 0..100 {
+            let n = 2 + random.below(7) as usize;
+            let mut graph = Graph::new();
+            for node in 0..n {
+                graph.add_node(node);
+            }
+            for _ in 0..random.below(3 * n as u64) {
+                let (from, to) = (
+                    random.below(n as u64) as usize,
+                    random.below(n as u64) as usize,
+                );
+                graph.add_edge(from, to, random.below(10) as u32);
+            }
+            // 枚举所有包含 0、不包含 n-1 的节点集合，最小的割容量就是最大流
+            let best = (0..1u32 << n)
+                .filter(|set| set & 1 == 1 && set >> (n - 1) & 1 == 0)
+                .map(|set| {
+                    graph
+                        .edges()
+                        .filter(|&(from, to, _)| set >> from & 1 == 1 && set >> to & 1 == 0)
+                        .map(|(_, _, capacity)| capacity)
+                        .sum::<u32>()
+                })
+                .min()
+                .unwrap();
+            let flow = dinic(&graph, &0, &(n - 1)).unwrap();
+            assert_eq!(flow.value(), best);
+            let cut: u32 = flow.min_cut().iter().map(|edge| edge.2).sum();
+            assert_eq!(cut, best);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// 最小生成树（Kruskal 和 Prim）
+// 边的方向被忽略，add_undirected_edge 存的两条边只会用到一条。图不连通时得到最小生成森林，
+// 每个连通分量一棵树。Kruskal 按权重排序所有边、用并查集跳过成环的边，适合稀疏图；
+// Prim 从每个分量的第一个节点出发，用堆不断取离当前树最近的边。两者总权重相同。
+use super::{Graph, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningTree<N, W> {
+    // (一端, 另一端, 权重)
+    pub edges: Vec<(N, N, W)>,
+}
+
+impl<N, W: Weight> SpanningTree<N, W> {
+    // 总权重，溢出时返回 None
+    pub fn cost(&self) -> Option<W> {
+        self.edges
+            .iter()
+            .try_fold(W::zero(), |total, &(_, _, weight)| {
+                total.checked_add(weight)
+            })
+    }
+}
+
+pub fn kruskal<N, W>(graph: &Graph<N, W>) -> SpanningTree<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let mut edges: Vec<(usize, usize, W)> = graph.edges().collect();
+    edges.sort_by_key(|&(_, _, weight)| weight);
+    let mut sets = DisjointSet::new(graph.node_count());
+    let tree = edges
+        .into_iter()
+        .filter(|&(a, b, _)| sets.union(a, b))
+        .map(|(a, b, weight)| (graph.node(a).clone(), graph.node(b).clone(), weight))
+        .collect();
+    SpanningTree { edges: tree }
+}
+
+pub fn prim<N, W>(graph: &Graph<N, W>) -> SpanningTree<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let n = graph.node_count();
+    // 不论方向，两端都能看到这条边
+    let mut adjacency = vec![Vec::new(); n];
+    for (from, to, weight) in graph.edges() {
+        adjacency[from].push((to, weight));
+        adjacency[to].push((from, weight));
+    }
+
+    let mut in_tree = vec![false; n];
+    let mut tree = Vec::with_capacity(n.saturating_sub(1));
+    for root in 0..n {
+        if in_tree[root] {
+            continue;
+        }
+        in_tree[root] = true;
+        let mut heap: BinaryHeap<_> = adjacency[root]
+            .iter()
+            .map(|&(to, weight)| Reverse((weight, root, to)))
+            .collect();
+        while let Some(Reverse((weight, from, to))) = heap.pop() {
+            if in_tree[to] {
+                continue;
+            }
+            in_tree[to] = true;
+            tree.push((graph.node(from).clone(), graph.node(to).clone(), weight));
+            heap.extend(
+                adjacency[to]
+                    .iter()
+                    .filter(|&&(next, _)| !in_tree[next])
+                    .map(|&(next, weight)| Reverse((weight, to, next))),
+            );
+        }
+    }
+    SpanningTree { edges: tree }
+}
+
+// 并查集，按大小合并并压缩路径
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+        root
+    }
+
+    // 两个元素原本不在同一集合时合并并返回 true
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Lcg;
+
+    #[test]
+    fn kruskal_and_prim_find_the_same_weight() {
+        let mut graph = Graph::new();
+        for (a, b, weight) in [
+            ('A', 'B', 7),
+            ('A', 'D', 5),
+            ('B', 'C', 8),
+            ('B', 'D', 9),
+            ('B', 'E', 7),
+            ('C', 'E', 5),
+            ('D', 'E', 15),
+            ('D', 'F', 6),
+            ('E', 'F', 8),
+            ('E', 'G', 9),
+            ('F', 'G', 11),
+        ] {
+            graph.add_undirected_edge(a, b, weight);
+        }
+        let tree = kruskal(&graph);
+        assert_eq!(tree.cost(), Some(39));
+        assert_eq!(tree.edges.len(), 6);
+        assert_eq!(prim(&graph).cost(), Some(39));
+
+        // 两个分量得到两棵树，孤立节点没有边
+        graph.add_edge('X', 'Y', 1);
+        graph.add_node('Z');
+        let forest = prim(&graph);
+        assert_eq!((forest.edges.len(), forest.cost()), (7, Some(40)));
+        assert_eq!(kruskal(&graph).cost(), Some(40));
+    }
+
+    #[test]
+    fn agree_on_random_graphs() {
+        let mut random = Lcg::new(7);
+        for _ in 0..200 {
+            let n = 1 + random.below(15);
+            let mut graph = Graph::new();
+            for node in 0..n {
+                graph.add_node(node);
+            }
+            for _ in 0..random.below(3 * n) {
+                graph.add_edge(
+                    random.below(n),
+                    random.below(n),
+                    random.below(10) as i32 - 3,
+                );
+            }
+            let (a, b) = (kruskal(&graph), prim(&graph));
+            assert_eq!(a.cost(), b.cost());
+            assert_eq!(a.edges.len(), b.edges.len());
+        }
+    }
+}
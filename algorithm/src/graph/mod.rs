@@ -0,0 +1,331 @@
+// 带权有向图。节点 id 可以是任何可哈希的类型(路口编号、坐标、字符串)，权重是任意整数类型。
+// 节点按加入顺序编号，算法内部用下标访问邻接表，结果再换回节点 id。
+// 无向图的每条边按两个方向各存一条。
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+pub mod a_star;
+pub mod bellman_ford;
+pub mod dijkstra;
+pub mod floyd_warshall;
+pub mod grid;
+pub mod io;
+pub mod johnson;
+pub mod max_flow;
+pub mod minimum_spanning_tree;
+pub mod strongly_connected;
+pub mod topological_sort;
+
+pub use a_star::{a_star, a_star_implicit, a_star_until};
+pub use bellman_ford::{bellman_ford, find_negative_cycle};
+pub use dijkstra::{dijkstra, dijkstra_all, dijkstra_to_any, dijkstra_until};
+pub use floyd_warshall::floyd_warshall;
+pub use johnson::johnson;
+pub use max_flow::{dinic, MaxFlow};
+pub use minimum_spanning_tree::{kruskal, prim, SpanningTree};
+pub use strongly_connected::{kosaraju, tarjan};
+pub use topological_sort::{topological_sort, CycleError};
+
+// 边权。加减溢出时返回 None，算法把这样的路径当作不可达。
+// 有符号类型可以有负权边，只有 bellman_ford、floyd_warshall、johnson 能处理负权
+pub trait Weight: Copy + Ord + Debug {
+    fn zero() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_weight {
+    ($($t:ty),*) => {
+        $(impl Weight for $t {
+            fn zero() -> Self {
+                0
+            }
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+        })*
+    };
+}
+
+impl_weight!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge<W> {
+    // 终点的下标
+    pub to: usize,
+    pub weight: W,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<N, W> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    // adjacency[i] 是从第 i 个节点出发的边
+    adjacency: Vec<Vec<Edge<W>>>,
+}
+
+impl<N, W> Default for Graph<N, W> {
+    fn default() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            adjacency: Vec::new(),
+        }
+    }
+}
+
+// 按下标访问，对节点类型没有要求
+impl<N, W: Weight> Graph<N, W> {
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).sum()
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn node(&self, i: usize) -> &N {
+        &self.nodes[i]
+    }
+
+    // 第 i 个节点的出边
+    pub fn neighbors(&self, i: usize) -> &[Edge<W>] {
+        &self.adjacency[i]
+    }
+
+    // 所有边，按 (起点下标, 终点下标, 权重)
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, W)> + '_ {
+        self.adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(from, edges)| edges.iter().map(move |edge| (from, edge.to, edge.weight)))
+    }
+}
+
+impl<N: Clone + Eq + Hash, W: Weight> Graph<N, W> {
+    pub fn new() -> Self {
+        Graph::default()
+    }
+
+    // 从 (起点, 终点, 权重) 列表建图
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N, W)>) -> Self {
+        let mut graph = Graph::new();
+        for (from, to, weight) in edges {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    // 兼容原来 dijkstra/a_star 使用的邻接表
+    pub fn from_adjacency(adjacency: &HashMap<N, Vec<(N, W)>>) -> Self {
+        let mut graph = Graph::new();
+        for (from, neighbors) in adjacency {
+            graph.add_node(from.clone());
+            for (to, weight) in neighbors {
+                graph.add_edge(from.clone(), to.clone(), *weight);
+            }
+        }
+        graph
+    }
+
+    // 返回节点的下标，节点已存在时不重复加入
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(&i) = self.index.get(&node) {
+            return i;
+        }
+        let i = self.nodes.len();
+        self.index.insert(node.clone(), i);
+        self.nodes.push(node);
+        self.adjacency.push(Vec::new());
+        i
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: W) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.adjacency[from].push(Edge { to, weight });
+    }
+
+    pub fn add_undirected_edge(&mut self, a: N, b: N, weight: W) {
+        self.add_edge(a.clone(), b.clone(), weight);
+        self.add_edge(b, a, weight);
+    }
+
+    pub fn index_of(&self, node: &N) -> Option<usize> {
+        self.index.get(node).copied()
+    }
+
+    // 节点不变，按 f(起点下标, 终点下标, 权重) 重新计算每条边的权重，f 返回 None 的边被去掉
+    pub(crate) fn filter_map_weights(
+        &self,
+        mut f: impl FnMut(usize, usize, W) -> Option<W>,
+    ) -> Self {
+        let adjacency = self
+            .adjacency
+            .iter()
+            .enumerate()
+            .map(|(from, edges)| {
+                edges
+                    .iter()
+                    .filter_map(|edge| {
+                        Some(Edge {
+                            to: edge.to,
+                            weight: f(from, edge.to, edge.weight)?,
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        Graph {
+            nodes: self.nodes.clone(),
+            index: self.index.clone(),
+            adjacency,
+        }
+    }
+
+    // 由前驱数组还原从起点到 goal 的节点序列
+    pub(crate) fn trace(&self, pred: &[Option<usize>], goal: usize) -> Vec<N> {
+        trace(pred, goal)
+            .into_iter()
+            .map(|i| self.nodes[i].clone())
+            .collect()
+    }
+}
+
+// 沿前驱数组从 goal 走回起点，返回从起点到 goal 的下标序列
+pub(crate) fn trace(pred: &[Option<usize>], goal: usize) -> Vec<usize> {
+    let mut path = vec![goal];
+    let mut current = goal;
+    while let Some(previous) = pred[current] {
+        path.push(previous);
+        current = previous;
+    }
+    path.reverse();
+    path
+}
+
+// 一条路径：起点和终点都包含在 nodes 中
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, W> {
+    pub nodes: Vec<N>,
+    pub cost: W,
+}
+
+// 单源最短路的结果：到每个节点的距离和前驱，不可达的节点两者都是 None
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<'a, N, W> {
+    graph: &'a Graph<N, W>,
+    source: usize,
+    dist: Vec<Option<W>>,
+    pred: Vec<Option<usize>>,
+}
+
+impl<'a, N: Clone + Eq + Hash, W: Weight> ShortestPaths<'a, N, W> {
+    pub(crate) fn new(
+        graph: &'a Graph<N, W>,
+        source: usize,
+        dist: Vec<Option<W>>,
+        pred: Vec<Option<usize>>,
+    ) -> Self {
+        ShortestPaths {
+            graph,
+            source,
+            dist,
+            pred,
+        }
+    }
+
+    pub fn source(&self) -> &N {
+        self.graph.node(self.source)
+    }
+
+    pub fn cost(&self, node: &N) -> Option<W> {
+        self.dist[self.graph.index_of(node)?]
+    }
+
+    pub fn path_to(&self, node: &N) -> Option<Path<N, W>> {
+        let goal = self.graph.index_of(node)?;
+        Some(Path {
+            cost: self.dist[goal]?,
+            nodes: self.graph.trace(&self.pred, goal),
+        })
+    }
+
+    // 按节点下标排列的距离和前驱
+    pub fn distances(&self) -> &[Option<W>] {
+        &self.dist
+    }
+
+    pub fn predecessors(&self) -> &[Option<usize>] {
+        &self.pred
+    }
+}
+
+// 全源最短路的结果：dist[i][j] 和 pred[i][j] 是从第 i 个节点到第 j 个节点的距离和 j 的前驱
+#[derive(Debug, Clone)]
+pub struct AllPairs<'a, N, W> {
+    graph: &'a Graph<N, W>,
+    dist: Vec<Vec<Option<W>>>,
+    pred: Vec<Vec<Option<usize>>>,
+}
+
+impl<'a, N: Clone + Eq + Hash, W: Weight> AllPairs<'a, N, W> {
+    pub(crate) fn new(
+        graph: &'a Graph<N, W>,
+        dist: Vec<Vec<Option<W>>>,
+        pred: Vec<Vec<Option<usize>>>,
+    ) -> Self {
+        AllPairs { graph, dist, pred }
+    }
+
+    pub fn cost(&self, from: &N, to: &N) -> Option<W> {
+        self.dist[self.graph.index_of(from)?][self.graph.index_of(to)?]
+    }
+
+    pub fn path(&self, from: &N, to: &N) -> Option<Path<N, W>> {
+        let from = self.graph.index_of(from)?;
+        let to = self.graph.index_of(to)?;
+        Some(Path {
+            cost: self.dist[from][to]?,
+            nodes: self.graph.trace(&self.pred[from], to),
+        })
+    }
+
+    // 按节点下标排列的距离矩阵和前驱矩阵
+    pub fn distances(&self) -> &[Vec<Option<W>>] {
+        &self.dist
+    }
+
+    pub fn predecessors(&self) -> &[Vec<Option<usize>>] {
+        &self.pred
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError<N, W> {
+    UnknownNode,
+    // 找到的一个负环，nodes 首尾是同一个节点，cost 是环上权重之和(小于 0)。
+    // 有负环时经过它的路径可以无限变短，最短路没有意义
+    NegativeCycle(Path<N, W>),
+}
+
+impl<N: Debug, W: Debug> fmt::Display for PathError<N, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::UnknownNode => write!(f, "节点不在图中"),
+            PathError::NegativeCycle(cycle) => {
+                write!(f, "存在负环 {:?}，总权重 {:?}", cycle.nodes, cycle.cost)
+            }
+        }
+    }
+}
+
+impl<N: Debug, W: Debug> std::error::Error for PathError<N, W> {}
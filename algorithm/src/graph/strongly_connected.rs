@@ -0,0 +1,200 @@
+// 强连通分量（Tarjan 和 Kosaraju）
+// 分量内任意两个节点互相可达。两种算法都用显式栈代替递归，长链状的大图也不会栈溢出。
+// Tarjan 一次深度优先搜索，low[v] 回到栈中最早的节点时弹出一个分量，分量按逆拓扑序产生
+// (被依赖的在前)；Kosaraju 先按完成时间排序，再在反图上按完成时间从晚到早搜索，
+// 分量按拓扑序产生。分量内的节点顺序没有特别含义。
+use super::{Graph, Weight};
+use std::hash::Hash;
+
+pub fn tarjan<N, W>(graph: &Graph<N, W>) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let n = graph.node_count();
+    let mut order: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut counter = 0;
+
+    for root in 0..n {
+        if order[root].is_some() {
+            continue;
+        }
+        // (节点, 下一条要看的出边)
+        let mut calls = vec![(root, 0)];
+        order[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (v, ref mut next_edge)) = calls.last_mut() {
+            if let Some(edge) = graph.neighbors(v).get(*next_edge) {
+                *next_edge += 1;
+                let w = edge.to;
+                match order[w] {
+                    None => {
+                        order[w] = Some(counter);
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        calls.push((w, 0));
+                    }
+                    Some(seen) if on_stack[w] => low[v] = low[v].min(seen),
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if Some(low[v]) == order[v] {
+                let mut component = Vec::new();
+                loop {
+                    let w = stack.pop().expect("v 还在栈中");
+                    on_stack[w] = false;
+                    component.push(graph.node(w).clone());
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+pub fn kosaraju<N, W>(graph: &Graph<N, W>) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let n = graph.node_count();
+    let mut visited = vec![false; n];
+    let mut finished = Vec::with_capacity(n);
+    for root in 0..n {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut calls = vec![(root, 0)];
+        while let Some(&mut (v, ref mut next_edge)) = calls.last_mut() {
+            if let Some(edge) = graph.neighbors(v).get(*next_edge) {
+                *next_edge += 1;
+                if !visited[edge.to] {
+                    visited[edge.to] = true;
+                    calls.push((edge.to, 0));
+                }
+            } else {
+                finished.push(v);
+                calls.pop();
+            }
+        }
+    }
+
+    let mut reverse = vec![Vec::new(); n];
+    for (from, to, _) in graph.edges() {
+        reverse[to].push(from);
+    }
+    let mut assigned = vec![false; n];
+    let mut components = Vec::new();
+    for &root in finished.iter().rev() {
+        if assigned[root] {
+            continue;
+        }
+        assigned[root] = true;
+        let mut component = Vec::new();
+        let mut pending = vec![root];
+        while let Some(v) = pending.pop() {
+            component.push(graph.node(v).clone());
+            for &u in &reverse[v] {
+                if !assigned[u] {
+                    assigned[u] = true;
+                    pending.push(u);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra_all;
+    use crate::test_utils::Lcg;
+
+    fn sorted(mut components: Vec<Vec<u64>>) -> Vec<Vec<u64>> {
+        for component in &mut components {
+            component.sort();
+        }
+        components.sort();
+        components
+    }
+
+    #[test]
+    fn finds_components_in_dependency_order() {
+        // {1,2,3} -> {4,5} -> {6}，7 单独一个
+        let graph = Graph::from_edges([
+            (1, 2, 1u32),
+            (2, 3, 1),
+            (3, 1, 1),
+            (3, 4, 1),
+            (4, 5, 1),
+            (5, 4, 1),
+            (5, 6, 1),
+            (6, 6, 1),
+            (7, 5, 1),
+        ]);
+        let expected = vec![vec![1, 2, 3], vec![4, 5], vec![6], vec![7]];
+        assert_eq!(sorted(tarjan(&graph)), expected);
+        assert_eq!(sorted(kosaraju(&graph)), expected);
+
+        // Tarjan 先给出没有出边的分量，Kosaraju 相反
+        assert_eq!(tarjan(&graph)[0], [6]);
+        assert_eq!(kosaraju(&graph).last().unwrap(), &[6]);
+    }
+
+    #[test]
+    fn agree_with_mutual_reachability() {
+        let mut random = Lcg::new(99);
+        for _ in 0..100 {
+            let n = 1 + random.below(12);
+            let mut graph = Graph::new();
+            for node in 0..n {
+                graph.add_node(node);
+            }
+            for _ in 0..random.below(2 * n) {
+                graph.add_edge(random.below(n), random.below(n), 1u32);
+            }
+            let reach: Vec<Vec<bool>> = (0..n)
+                .map(|from| {
+                    let paths = dijkstra_all(&graph, &from).unwrap();
+                    (0..n).map(|to| paths.cost(&to).is_some()).collect()
+                })
+                .collect();
+            let mut expected: Vec<Vec<u64>> = Vec::new();
+            for v in 0..n {
+                if !expected.iter().flatten().any(|&u| u == v) {
+                    expected.push(
+                        (0..n)
+                            .filter(|&u| {
+                                reach[v as usize][u as usize] && reach[u as usize][v as usize]
+                            })
+                            .collect(),
+                    );
+                }
+            }
+            assert_eq!(sorted(tarjan(&graph)), sorted(expected.clone()));
+            assert_eq!(sorted(kosaraju(&graph)), sorted(expected));
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// 拓扑排序（Kahn 算法）
+// 每次取出入度为 0 的节点，所有边都从排在前面的节点指向排在后面的节点。
+// 有环时环上的节点入度永远不会变成 0；这些剩下的节点每个都有来自剩余节点的入边，
+// 沿入边往回走必然走进环，据此报告一个具体的环。入度同为 0 的节点按加入图的顺序输出。
+use super::{Graph, Weight};
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError<N> {
+    // 环上的节点，首尾是同一个节点
+    pub nodes: Vec<N>,
+}
+
+impl<N: Debug> fmt::Display for CycleError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "图中存在环 {:?}，无法拓扑排序", self.nodes)
+    }
+}
+
+impl<N: Debug> std::error::Error for CycleError<N> {}
+
+pub fn topological_sort<N, W>(graph: &Graph<N, W>) -> Result<Vec<N>, CycleError<N>>
+where
+    N: Clone + Eq + Hash,
+    W: Weight,
+{
+    let n = graph.node_count();
+    let mut in_degree = vec![0; n];
+    for (_, to, _) in graph.edges() {
+        in_degree[to] += 1;
+    }
+    let mut ready: VecDeque<usize> = (0..n).filter(|&i| in_degree[i] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(i) = ready.pop_front() {
+        order.push(graph.node(i).clone());
+        for edge in graph.neighbors(i) {
+            in_degree[edge.to] -= 1;
+            if in_degree[edge.to] == 0 {
+                ready.push_back(edge.to);
+            }
+        }
+    }
+    if order.len() == n {
+        return Ok(order);
+    }
+
+    // 每个剩下的节点记一个同样剩下的前驱
+    let mut pred = vec![None; n];
+    for (from, to, _) in graph.edges() {
+        if in_degree[from] > 0 && in_degree[to] > 0 {
+            pred[to] = Some(from);
+        }
+    }
+    let mut visited = vec![false; n];
+    let mut current = (0..n).find(|&i| in_degree[i] > 0).expect("有剩下的节点");
+    while !visited[current] {
+        visited[current] = true;
+        current = pred[current].expect("剩下的节点都有剩下的前驱");
+    }
+    // current 在环上，再沿前驱绕一圈
+    let mut cycle = vec![current];
+    let mut node = current;
+    loop {
+        node = pred[node].expect("环上的节点都有前驱");
+        cycle.push(node);
+        if node == current {
+            break;
+        }
+    }
+    cycle.reverse();
+    Err(CycleError {
+        nodes: cycle.into_iter().map(|i| graph.node(i).clone()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_steps() -> Graph<&'static str, u32> {
+        Graph::from_edges([
+            ("fetch", "compile", 1),
+            ("configure", "compile", 1),
+            ("compile", "test", 1),
+            ("compile", "package", 1),
+            ("test", "release", 1),
+            ("package", "release", 1),
+        ])
+    }
+
+    #[test]
+    fn orders_dependencies_first() {
+        let graph = build_steps();
+        let order = topological_sort(&graph).unwrap();
+        assert_eq!(
+            order,
+            [
+                "fetch",
+                "configure",
+                "compile",
+                "test",
+                "package",
+                "release"
+            ]
+        );
+        let position = |node| order.iter().position(|&n| n == node).unwrap();
+        for (from, to, _) in graph.edges() {
+            assert!(position(*graph.node(from)) < position(*graph.node(to)));
+        }
+    }
+
+    #[test]
+    fn reports_a_cycle() {
+        let mut graph = build_steps();
+        graph.add_edge("release", "fetch", 1);
+        graph.add_edge("lint", "lint", 1);
+        let cycle = topological_sort(&graph).unwrap_err().nodes;
+        assert_eq!(cycle.first(), cycle.last());
+        for pair in cycle.windows(2) {
+            let from = graph.index_of(&pair[0]).unwrap();
+            let to = graph.index_of(&pair[1]).unwrap();
+            assert!(graph.neighbors(from).iter().any(|edge| edge.to == to));
+        }
+        assert!(cycle.contains(&"release"));
+
+        let selfish = Graph::from_edges([("a", "a", 1u8)]);
+        assert_eq!(topological_sort(&selfish).unwrap_err().nodes, ["a", "a"]);
+    }
+}
@@ -0,0 +1,7 @@
+// src/lib.rs
+pub mod graph;
+pub mod prime_factors;
+pub mod sort;
+
+#[cfg(test)]
+mod test_utils;
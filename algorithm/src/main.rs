@@ -1,5 +1,605 @@
-mod insert_sort;
+// src/main.rs
+// 命令行入口：按名字运行库里的算法。图从 --graph 指定的文件读取(不指定时读标准输入)，
+// 格式按扩展名判断(.gr/.dimacs 为 DIMACS，.dot/.gv 为 Graphviz，其余为边表)，也可以用 --input-format 指定；
+// 节点一律按名字处理，DIMACS 的节点名就是编号。sort 和 factor 同样读文件或标准输入。
+// --format json 时向标准输出写一个 JSON 对象(失败时为 {"error": ...})，默认输出便于阅读的文本，错误写到标准错误。
+use algorithm::graph::io::{read_dimacs, read_dot, read_edge_list, ParseError};
+use algorithm::graph::{
+    bellman_ford, dijkstra, dinic, kruskal, prim, tarjan, topological_sort, Graph, Path, PathError,
+    SpanningTree,
+};
+use algorithm::prime_factors::{is_probable_prime_big, prime_factors_big, primes};
+use algorithm::sort::{
+    heap_sort_by, merge_sort_by_key, par_merge_sort_by_key, quick_sort_by_key, radix_sort_by_key,
+    ExternalSorter,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path as FilePath, PathBuf};
+use std::process;
+
+// 退出码: 0 成功，2 参数错误(大多由 clap 输出用法)
+const EXIT_USAGE: i32 = 2;
+// 没有结果：终点不可达、图中有环或负环
+const EXIT_NO_RESULT: i32 = 1;
+// 读取或解析输入失败
+const EXIT_INPUT: i32 = 3;
+// 指定的节点不在图中
+const EXIT_NOT_FOUND: i32 = 4;
+
+#[derive(Parser)]
+#[command(name = "algorithm", version, about = "按名字运行图、排序和数论算法")]
+struct Cli {
+    /// 输出格式
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    Dimacs,
+    EdgeList,
+    Dot,
+}
+
+#[derive(clap::Args)]
+struct GraphArgs {
+    /// 图文件，不指定时读标准输入
+    #[arg(long)]
+    graph: Option<PathBuf>,
+    /// 图文件格式，默认按扩展名判断，标准输入默认为边表
+    #[arg(long, value_enum)]
+    input_format: Option<GraphFormat>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SpanningAlgorithm {
+    Kruskal,
+    Prim,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortAlgorithm {
+    Merge,
+    Quick,
+    Heap,
+    Parallel,
+    Radix,
+    External,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 两点间的最短路，有负权边时用 Bellman-Ford，否则用 Dijkstra
+    ShortestPath {
+        #[command(flatten)]
+        input: GraphArgs,
+        #[arg(long)]
+        from: String,
+        #[arg(long)]
+        to: String,
+    },
+    /// 最小生成树(图不连通时为生成森林)，忽略边的方向
+    SpanningTree {
+        #[command(flatten)]
+        input: GraphArgs,
+        #[arg(long, value_enum, default_value_t = SpanningAlgorithm::Kruskal)]
+        algorithm: SpanningAlgorithm,
+    },
+    /// 拓扑排序，有环时退出码为 1
+    TopologicalSort {
+        #[command(flatten)]
+        input: GraphArgs,
+    },
+    /// 强连通分量(Tarjan)
+    Components {
+        #[command(flatten)]
+        input: GraphArgs,
+    },
+    /// 最大流和最小割(Dinic)，边权是容量
+    MaxFlow {
+        #[command(flatten)]
+        input: GraphArgs,
+        #[arg(long)]
+        source: String,
+        #[arg(long)]
+        sink: String,
+    },
+    /// 按行排序文件，不指定文件时读标准输入
+    Sort {
+        input: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = SortAlgorithm::Merge)]
+        algorithm: SortAlgorithm,
+        /// 每行是一个整数，按数值排序(radix 要求这一项)
+        #[arg(long)]
+        numeric: bool,
+        /// external 每块使用的内存字节数
+        #[arg(long, default_value_t = 256 * 1024 * 1024)]
+        memory_limit: usize,
+    },
+    /// 分解任意大小的正整数，不给出 NUMBER 时从标准输入读取(空白分隔)
+    Factor { numbers: Vec<String> },
+    /// 列出 [START, END) 中的素数(分段筛)
+    Primes {
+        start: u64,
+        end: u64,
+        /// 只输出个数
+        #[arg(long)]
+        count: bool,
+    },
+}
 
 fn main() {
-    println!("Hello, world!");
+    let cli = Cli::parse();
+    let out = Output { format: cli.format };
+    match cli.command {
+        Command::ShortestPath { input, from, to } => run_shortest_path(&out, &input, &from, &to),
+        Command::SpanningTree { input, algorithm } => run_spanning_tree(&out, &input, algorithm),
+        Command::TopologicalSort { input } => run_topological_sort(&out, &input),
+        Command::Components { input } => run_components(&out, &input),
+        Command::MaxFlow {
+            input,
+            source,
+            sink,
+        } => run_max_flow(&out, &input, &source, &sink),
+        Command::Sort {
+            input,
+            algorithm,
+            numeric,
+            memory_limit,
+        } => run_sort(&out, input.as_deref(), algorithm, numeric, memory_limit),
+        Command::Factor { numbers } => run_factor(&out, numbers),
+        Command::Primes { start, end, count } => run_primes(&out, start, end, count),
+    }
+}
+
+struct Output {
+    format: Format,
+}
+
+impl Output {
+    // text 由调用者生成，只在文本格式下才会用到
+    fn emit(&self, text: impl FnOnce() -> String, value: Value) {
+        match self.format {
+            Format::Text => println!("{}", text()),
+            Format::Json => println!("{}", value),
+        }
+    }
+
+    fn fail(&self, code: i32, message: impl ToString) -> ! {
+        match self.format {
+            Format::Text => eprintln!("错误: {}", message.to_string()),
+            Format::Json => println!("{}", json!({ "error": message.to_string() })),
+        }
+        process::exit(code);
+    }
+}
+
+fn open_input(out: &Output, path: Option<&FilePath>) -> Box<dyn BufRead> {
+    match path {
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => out.fail(EXIT_INPUT, format!("{}: {}", path.display(), e)),
+        },
+        None => Box::new(BufReader::new(io::stdin())),
+    }
+}
+
+// 所有格式都读成以节点名为 id 的图
+fn load_graph(out: &Output, args: &GraphArgs) -> Graph<String, i64> {
+    let format = args.input_format.unwrap_or_else(|| {
+        let extension = args
+            .graph
+            .as_deref()
+            .and_then(|path| path.extension())
+            .and_then(|extension| extension.to_str());
+        match extension {
+            Some("gr" | "dimacs") => GraphFormat::Dimacs,
+            Some("dot" | "gv") => GraphFormat::Dot,
+            _ => GraphFormat::EdgeList,
+        }
+    });
+    let reader = open_input(out, args.graph.as_deref());
+    let result: Result<_, ParseError> = match format {
+        GraphFormat::Dimacs => read_dimacs::<i64>(reader).map(|numbered| {
+            let mut graph = Graph::new();
+            for node in numbered.nodes() {
+                graph.add_node(node.to_string());
+            }
+            for (from, to, weight) in numbered.edges() {
+                graph.add_edge(
+                    numbered.node(from).to_string(),
+                    numbered.node(to).to_string(),
+                    weight,
+                );
+            }
+            graph
+        }),
+        GraphFormat::EdgeList => read_edge_list(reader),
+        GraphFormat::Dot => read_dot(reader),
+    };
+    result.unwrap_or_else(|e| out.fail(EXIT_INPUT, e))
+}
+
+fn require_node(out: &Output, graph: &Graph<String, i64>, node: &str) {
+    if graph.index_of(&node.to_string()).is_none() {
+        out.fail(EXIT_NOT_FOUND, format!("节点 {} 不在图中", node));
+    }
+}
+
+fn edge_lines(edges: &[(String, String, i64)]) -> String {
+    edges
+        .iter()
+        .map(|(from, to, weight)| format!("{} {} {}", from, to, weight))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn run_shortest_path(out: &Output, input: &GraphArgs, from: &str, to: &str) {
+    let graph = load_graph(out, input);
+    require_node(out, &graph, from);
+    require_node(out, &graph, to);
+    let (from, to) = (from.to_string(), to.to_string());
+    let negative = graph.edges().any(|(_, _, weight)| weight < 0);
+    let (algorithm, path): (_, Option<Path<String, i64>>) = if negative {
+        match bellman_ford(&graph, &from) {
+            Ok(paths) => ("bellman-ford", paths.path_to(&to)),
+            Err(PathError::NegativeCycle(cycle)) => out.fail(
+                EXIT_NO_RESULT,
+                format!(
+                    "存在负环 {}，总权重 {}",
+                    cycle.nodes.join(" -> "),
+                    cycle.cost
+                ),
+            ),
+            Err(e) => out.fail(EXIT_NOT_FOUND, e),
+        }
+    } else {
+        ("dijkstra", dijkstra(&graph, &from, &to))
+    };
+    let Some(path) = path else {
+        out.fail(EXIT_NO_RESULT, format!("从 {} 到不了 {}", from, to));
+    };
+    out.emit(
+        || format!("{}\n总权重 {}", path.nodes.join(" -> "), path.cost),
+        json!({
+            "algorithm": algorithm,
+            "from": from,
+            "to": to,
+            "cost": path.cost,
+            "path": path.nodes,
+        }),
+    );
+}
+
+fn run_spanning_tree(out: &Output, input: &GraphArgs, algorithm: SpanningAlgorithm) {
+    let graph = load_graph(out, input);
+    let (name, tree): (_, SpanningTree<String, i64>) = match algorithm {
+        SpanningAlgorithm::Kruskal => ("kruskal", kruskal(&graph)),
+        SpanningAlgorithm::Prim => ("prim", prim(&graph)),
+    };
+    let cost = tree.cost();
+    out.emit(
+        || match cost {
+            Some(cost) => format!("{}\n总权重 {}", edge_lines(&tree.edges), cost),
+            None => format!("{}\n总权重溢出", edge_lines(&tree.edges)),
+        },
+        json!({
+            "algorithm": name,
+            "cost": cost,
+            "edges": tree.edges,
+        }),
+    );
+}
+
+fn run_topological_sort(out: &Output, input: &GraphArgs) {
+    let graph = load_graph(out, input);
+    match topological_sort(&graph) {
+        Ok(order) => out.emit(|| order.join("\n"), json!({ "order": order })),
+        Err(cycle) => out.fail(
+            EXIT_NO_RESULT,
+            format!("图中存在环 {}，无法拓扑排序", cycle.nodes.join(" -> ")),
+        ),
+    }
+}
+
+fn run_components(out: &Output, input: &GraphArgs) {
+    let graph = load_graph(out, input);
+    let components = tarjan(&graph);
+    out.emit(
+        || {
+            components
+                .iter()
+                .map(|component| component.join(" "))
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
+        json!({
+            "count": components.len(),
+            "components": components,
+        }),
+    );
+}
+
+fn run_max_flow(out: &Output, input: &GraphArgs, source: &str, sink: &str) {
+    let graph = load_graph(out, input);
+    require_node(out, &graph, source);
+    require_node(out, &graph, sink);
+    let flow = dinic(&graph, &source.to_string(), &sink.to_string())
+        .unwrap_or_else(|| out.fail(EXIT_NOT_FOUND, "源点和汇点不能相同"));
+    let (flows, cut) = (flow.flows(), flow.min_cut());
+    out.emit(
+        || {
+            format!(
+                "最大流 {}\n{}\n最小割:\n{}",
+                flow.value(),
+                edge_lines(&flows),
+                edge_lines(&cut)
+            )
+        },
+        json!({
+            "value": flow.value(),
+            "flows": flows,
+            "min_cut": cut,
+        }),
+    );
+}
+
+fn run_sort(
+    out: &Output,
+    input: Option<&FilePath>,
+    algorithm: SortAlgorithm,
+    numeric: bool,
+    memory_limit: usize,
+) {
+    if algorithm == SortAlgorithm::Radix && !numeric {
+        out.fail(EXIT_USAGE, "radix 只能用于 --numeric");
+    }
+    let mut reader = open_input(out, input);
+    if algorithm == SortAlgorithm::External {
+        let sorter = ExternalSorter::new().memory_limit(memory_limit);
+        // 输入在切块时逐行检查，全部读完之后才开始输出，出错时和其他算法一样不输出结果
+        let key = |line: &str| line.trim().parse::<i64>().ok();
+        let compare = |a: &str, b: &str| {
+            if numeric {
+                key(a).cmp(&key(b))
+            } else {
+                a.cmp(b)
+            }
+        };
+        if numeric {
+            reader = Box::new(BufReader::new(IntegerLines::new(reader)));
+        }
+        // 两种格式都直接写到标准输出，不经过内存
+        let stdout = io::stdout().lock();
+        let result = match out.format {
+            Format::Text => sorter.sort_lines_by(reader, stdout, compare).map(|_| ()),
+            Format::Json => {
+                let mut lines = JsonLines::new(stdout, "external");
+                sorter
+                    .sort_lines_by(reader, &mut lines, compare)
+                    .and_then(|_| lines.finish())
+            }
+        };
+        if let Err(e) = result {
+            out.fail(EXIT_INPUT, e);
+        }
+        return;
+    }
+
+    let mut text = String::new();
+    if let Err(e) = reader.read_to_string(&mut text) {
+        out.fail(EXIT_INPUT, e);
+    }
+    let mut lines: Vec<&str> = text.lines().collect();
+    if numeric {
+        let mut keyed: Vec<(i64, &str)> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| match line.trim().parse() {
+                Ok(key) => (key, *line),
+                Err(_) => out.fail(EXIT_INPUT, format!("第 {} 行 {:?} 不是整数", i + 1, line)),
+            })
+            .collect();
+        match algorithm {
+            SortAlgorithm::Quick => quick_sort_by_key(&mut keyed, |&(key, _)| key),
+            SortAlgorithm::Heap => heap_sort_by(&mut keyed, |a, b| a.0.cmp(&b.0)),
+            SortAlgorithm::Parallel => par_merge_sort_by_key(&mut keyed, |&(key, _)| key),
+            SortAlgorithm::Radix => radix_sort_by_key(&mut keyed, |&(key, _)| key),
+            _ => merge_sort_by_key(&mut keyed, |&(key, _)| key),
+        }
+        lines = keyed.into_iter().map(|(_, line)| line).collect();
+    } else {
+        match algorithm {
+            SortAlgorithm::Quick => quick_sort_by_key(&mut lines, |&line| line),
+            SortAlgorithm::Heap => heap_sort_by(&mut lines, |a, b| a.cmp(b)),
+            SortAlgorithm::Parallel => par_merge_sort_by_key(&mut lines, |&line| line),
+            _ => merge_sort_by_key(&mut lines, |&line| line),
+        }
+    }
+    let name = match algorithm {
+        SortAlgorithm::Quick => "quick",
+        SortAlgorithm::Heap => "heap",
+        SortAlgorithm::Parallel => "parallel",
+        SortAlgorithm::Radix => "radix",
+        _ => "merge",
+    };
+    out.emit(
+        || lines.join("\n"),
+        json!({ "algorithm": name, "lines": lines }),
+    );
+}
+
+// 逐行转发输入，遇到不是整数的行时返回 InvalidData，报错的格式与内存中排序时相同
+struct IntegerLines<R> {
+    inner: R,
+    line: Vec<u8>,
+    position: usize,
+    number: usize,
+}
+
+impl<R: BufRead> IntegerLines<R> {
+    fn new(inner: R) -> Self {
+        IntegerLines {
+            inner,
+            line: Vec::new(),
+            position: 0,
+            number: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Read for IntegerLines<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.line.len() {
+            self.line.clear();
+            self.position = 0;
+            if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(0);
+            }
+            self.number += 1;
+            let text = String::from_utf8_lossy(&self.line);
+            let line = text.strip_suffix('\n').unwrap_or(&text);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.trim().parse::<i64>().is_err() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("第 {} 行 {:?} 不是整数", self.number, line),
+                ));
+            }
+        }
+        let count = (&self.line[self.position..]).read(buf)?;
+        self.position += count;
+        Ok(count)
+    }
+}
+
+// 把以 '\n' 结尾的各行写成 {"algorithm": ..., "lines": [...]}，边收到边输出
+struct JsonLines<W: Write> {
+    out: W,
+    algorithm: &'static str,
+    pending: Vec<u8>,
+    lines: usize,
+}
+
+impl<W: Write> JsonLines<W> {
+    fn new(out: W, algorithm: &'static str) -> Self {
+        JsonLines {
+            out,
+            algorithm,
+            pending: Vec::new(),
+            lines: 0,
+        }
+    }
+
+    // 第一行之前写对象的开头，之后的每行前写逗号
+    fn separator(&mut self) -> io::Result<()> {
+        if self.lines == 0 {
+            write!(
+                self.out,
+                "{{\"algorithm\":{},\"lines\":[",
+                json!(self.algorithm)
+            )
+        } else {
+            self.out.write_all(b",")
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.lines == 0 {
+            self.separator()?;
+        }
+        writeln!(self.out, "]}}")?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Write for JsonLines<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            self.separator()?;
+            let line = String::from_utf8_lossy(&self.pending[..end]);
+            write!(self.out, "{}", json!(line))?;
+            self.pending.drain(..=end);
+            self.lines += 1;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn run_factor(out: &Output, numbers: Vec<String>) {
+    let numbers = if numbers.is_empty() {
+        let mut text = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut text) {
+            out.fail(EXIT_INPUT, e);
+        }
+        text.split_whitespace().map(String::from).collect()
+    } else {
+        numbers
+    };
+    let mut lines = Vec::new();
+    let mut results = Vec::new();
+    for number in numbers {
+        let n: BigUint = number
+            .parse()
+            .unwrap_or_else(|_| out.fail(EXIT_INPUT, format!("{:?} 不是非负整数", number)));
+        let factors = prime_factors_big(&n);
+        let terms: Vec<String> = factors
+            .iter()
+            .map(|(p, exponent)| match exponent {
+                1 => p.to_string(),
+                _ => format!("{}^{}", p, exponent),
+            })
+            .collect();
+        lines.push(format!("{}: {}", n, terms.join(" ")));
+        // 大整数超出 JSON 数字的范围，一律写成字符串
+        results.push(json!({
+            "n": n.to_string(),
+            "prime": is_probable_prime_big(&n),
+            "factors": factors
+                .iter()
+                .map(|(p, exponent)| json!([p.to_string(), exponent]))
+                .collect::<Vec<_>>(),
+        }));
+    }
+    out.emit(|| lines.join("\n"), json!({ "results": results }));
+}
+
+fn run_primes(out: &Output, start: u64, end: u64, count: bool) {
+    if count {
+        let count = primes(start..end).count();
+        out.emit(|| count.to_string(), json!({ "count": count }));
+        return;
+    }
+    match out.format {
+        Format::Text => {
+            let mut stdout = io::BufWriter::new(io::stdout().lock());
+            for p in primes(start..end) {
+                // 标准输出被关闭(比如接到 head)时停止
+                if writeln!(stdout, "{}", p).is_err() {
+                    return;
+                }
+            }
+        }
+        Format::Json => {
+            let primes: Vec<u64> = primes(start..end).collect();
+            println!("{}", json!({ "count": primes.len(), "primes": primes }));
+        }
+    }
 }
@@ -0,0 +1,260 @@
+// 任意精度整数的素性测试和分解(num-bigint)
+// 能放进 u128 的部分交给 prime_factors_u128 和 is_prime_u128；更大的数先试除小素数，
+// 再用 Baillie-PSW 判断素数、Pollard-Brent 找因子，做法与 u128 版本中超过确定性范围的部分相同：
+// 以 2 为底的强伪素数测试加上强 Lucas 测试，没有已知的反例，也不像固定底数的 Miller-Rabin 那样能被特意构造的合数骗过。
+// 和 u128 一样，只有除最大的素因子外其余素因子都不太大(约 20 位十进制以内)时才分解得动。
+use super::{group, primality::is_prime_u128, prime_factors_u128, primes_up_to};
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+
+// 先试除这些小素数，排除大部分合数
+const SMALL_PRIMES: [u32; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+const BATCH: usize = 128;
+
+pub fn is_probable_prime_big(n: &BigUint) -> bool {
+    if let Some(small) = n.to_u128() {
+        return is_prime_u128(small);
+    }
+    if SMALL_PRIMES.iter().any(|&p| (n % p).is_zero()) {
+        return false;
+    }
+    strong_probable_prime(n, 2) && strong_lucas_probable_prime(n)
+}
+
+// 与 primality::strong_probable_prime 相同，n 是大于 base 的奇数
+fn strong_probable_prime(n: &BigUint, base: u32) -> bool {
+    let minus_one = n - 1u32;
+    let s = minus_one.trailing_zeros().unwrap_or(0);
+    let mut x = BigUint::from(base).modpow(&(&minus_one >> s), n);
+    if x.is_one() || x == minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = &x * &x % n;
+        if x == minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+// 与 primality::strong_lucas_probable_prime 相同，n 是奇数
+fn strong_lucas_probable_prime(n: &BigUint) -> bool {
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return false;
+    }
+    let mut d: i64 = 5;
+    loop {
+        match jacobi(&residue(d, n), n) {
+            -1 => break,
+            0 if BigUint::from(d.unsigned_abs()) != *n => return false,
+            _ => d = if d > 0 { -(d + 2) } else { 2 - d },
+        }
+    }
+    let (dm, q) = (residue(d, n), residue((1 - d) / 4, n));
+    let plus_one = n + 1u32;
+    let s = plus_one.trailing_zeros().unwrap_or(0);
+    let k = &plus_one >> s;
+    let half = |x: BigUint| if x.is_even() { x >> 1 } else { (x + n) >> 1 };
+    // V² - 2Q^m，qk 小于 n 所以 n - qk 不会下溢
+    let double = |v: &BigUint, qk: &BigUint| (v * v + (n - qk) * 2u32) % n;
+
+    let (mut u, mut v, mut qk) = (BigUint::zero(), BigUint::from(2u32) % n, BigUint::one());
+    for bit in (0..k.bits()).rev() {
+        u = &u * &v % n;
+        v = double(&v, &qk);
+        qk = &qk * &qk % n;
+        if k.bit(bit) {
+            (u, v) = (half((&u + &v) % n), half((&dm * &u + &v) % n));
+            qk = qk * &q % n;
+        }
+    }
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = double(&v, &qk);
+        qk = &qk * &qk % n;
+        if v.is_zero() {
+            return true;
+        }
+    }
+    false
+}
+
+// Jacobi 符号 (a/n)，n 是正奇数
+fn jacobi(a: &BigUint, n: &BigUint) -> i32 {
+    let (mut a, mut n) = (a % n, n.clone());
+    let mut result = 1;
+    while !a.is_zero() {
+        let twos = a.trailing_zeros().unwrap_or(0);
+        a >>= twos;
+        let n_mod_8 = (&n % 8u32).to_u32().unwrap_or(0);
+        if twos % 2 == 1 && matches!(n_mod_8, 3 | 5) {
+            result = -result;
+        }
+        if (&a % 4u32).to_u32() == Some(3) && n_mod_8 % 4 == 3 {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= &n;
+    }
+    if n.is_one() {
+        result
+    } else {
+        0
+    }
+}
+
+fn residue(a: i64, n: &BigUint) -> BigUint {
+    let r = BigUint::from(a.unsigned_abs()) % n;
+    if a < 0 && !r.is_zero() {
+        n - r
+    } else {
+        r
+    }
+}
+
+pub fn prime_factors_big(n: &BigUint) -> Vec<(BigUint, u32)> {
+    if let Some(small) = n.to_u128() {
+        return prime_factors_u128(small)
+            .into_iter()
+            .map(|(p, exponent)| (BigUint::from(p), exponent))
+            .collect();
+    }
+    let mut n = n.clone();
+    let mut primes = Vec::new();
+    for p in primes_up_to(1000) {
+        while (&n % p).to_u64() == Some(0) {
+            primes.push(BigUint::from(p));
+            n /= p;
+        }
+    }
+    split(n, &mut primes);
+    group(primes)
+}
+
+fn split(n: BigUint, primes: &mut Vec<BigUint>) {
+    if let Some(small) = n.to_u128() {
+        for (p, exponent) in prime_factors_u128(small) {
+            primes.extend(std::iter::repeat_n(BigUint::from(p), exponent as usize));
+        }
+        return;
+    }
+    if is_probable_prime_big(&n) {
+        primes.push(n);
+        return;
+    }
+    // n 超过 u128 且没有 1000 以下的因子，一定能找到非平凡的因子
+    let divisor = (1u32..)
+        .find_map(|c| brent(&n, c))
+        .expect("合数总能找到因子");
+    let rest = &n / &divisor;
+    split(divisor, primes);
+    split(rest, primes);
+}
+
+// 与 rho::brent 相同
+fn brent(n: &BigUint, c: u32) -> Option<BigUint> {
+    let f = |x: &BigUint| (x * x + c) % n;
+    let distance = |a: &BigUint, b: &BigUint| if a > b { a - b } else { b - a };
+    let (mut x, mut y, mut saved) = (
+        BigUint::from(2u32),
+        BigUint::from(2u32),
+        BigUint::from(2u32),
+    );
+    let (mut product, mut divisor, mut length) = (BigUint::one(), BigUint::one(), 1usize);
+    while divisor.is_one() {
+        x = y.clone();
+        for _ in 0..length {
+            y = f(&y);
+        }
+        let mut done = 0;
+        while done < length && divisor.is_one() {
+            saved = y.clone();
+            for _ in 0..BATCH.min(length - done) {
+                y = f(&y);
+                product = product * distance(&x, &y) % n;
+            }
+            divisor = product.gcd(n);
+            done += BATCH;
+        }
+        length *= 2;
+    }
+    if divisor == *n {
+        divisor = BigUint::one();
+        while divisor.is_one() {
+            saved = f(&saved);
+            divisor = distance(&x, &saved).gcd(n);
+        }
+    }
+    (divisor != *n).then_some(divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prime_factors::primality;
+
+    fn mersenne(exponent: u32) -> BigUint {
+        (BigUint::one() << exponent) - 1u32
+    }
+
+    #[test]
+    fn probable_primes_beyond_u128() {
+        for exponent in [127, 521, 607] {
+            assert!(
+                is_probable_prime_big(&mersenne(exponent)),
+                "2^{exponent} - 1"
+            );
+        }
+        assert!(!is_probable_prime_big(&(mersenne(521) * mersenne(127))));
+        assert!(!is_probable_prime_big(&mersenne(523)));
+        assert!(!is_probable_prime_big(&BigUint::from(561u32)));
+        // Chernick 形式的 Carmichael 数 (6k+1)(12k+1)(18k+1)，三个因子都是素数，乘积超过 u128
+        let k = BigUint::from(1_000_000_001_121u64);
+        let carmichael = (&k * 6u32 + 1u32) * (&k * 12u32 + 1u32) * (&k * 18u32 + 1u32);
+        assert!(!is_probable_prime_big(&carmichael));
+    }
+
+    #[test]
+    fn lucas_test_matches_the_u128_version() {
+        for n in (3..60_000u128).step_by(2) {
+            let root = n.isqrt();
+            if root * root == n {
+                continue;
+            }
+            assert_eq!(
+                strong_lucas_probable_prime(&BigUint::from(n)),
+                primality::strong_lucas_probable_prime(n),
+                "{n}"
+            );
+        }
+    }
+
+    #[test]
+    fn factors_beyond_u128() {
+        let m127 = mersenne(127);
+        let n = &m127 * BigUint::from(1_000_003u64).pow(2) * 2147483647u64 * 243u32;
+        let expected = vec![
+            (BigUint::from(3u32), 5),
+            (BigUint::from(1_000_003u64), 2),
+            (BigUint::from(2147483647u64), 1),
+            (m127, 1),
+        ];
+        assert_eq!(prime_factors_big(&n), expected);
+        assert_eq!(
+            prime_factors_big(&BigUint::from(360u32)),
+            [
+                (BigUint::from(2u32), 3),
+                (BigUint::from(3u32), 2),
+                (BigUint::from(5u32), 1)
+            ]
+        );
+        assert_eq!(prime_factors_big(&BigUint::one()), []);
+    }
+}
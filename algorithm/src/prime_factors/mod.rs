@@ -0,0 +1,137 @@
+// 混合素数分解：用于将一个数分解为素数因子的算法，可以利用Rust的内存安全和性能优势高效地运行。
+// 先用 TRIAL_LIMIT 以下的数试除去掉小因子；剩下的部分是素数(见 primality)就直接记下，
+// 否则用 Pollard-Brent 找一个因子，把两部分分别递归分解。
+// 结果是按素数从小到大排列的 (素数, 指数) 列表；0 和 1 没有素因子，返回空列表。
+pub mod big;
+pub mod primality;
+pub mod rho;
+pub mod sieve;
+
+pub use big::{is_probable_prime_big, prime_factors_big};
+pub use primality::{is_prime, is_prime_u128};
+pub use rho::pollard_brent;
+pub use sieve::{primes, primes_up_to, Primes};
+
+const TRIAL_LIMIT: u128 = 1000;
+
+pub fn prime_factors(n: u64) -> Vec<(u64, u32)> {
+    prime_factors_u128(n as u128)
+        .into_iter()
+        .map(|(p, exponent)| (p as u64, exponent))
+        .collect()
+}
+
+pub fn prime_factors_u128(mut n: u128) -> Vec<(u128, u32)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut primes = Vec::new();
+    let mut divisor = 2;
+    // 用 divisor <= n / divisor 代替 divisor² <= n，不会溢出
+    while divisor < TRIAL_LIMIT && divisor <= n / divisor {
+        while n.is_multiple_of(divisor) {
+            primes.push(divisor);
+            n /= divisor;
+        }
+        divisor += if divisor == 2 { 1 } else { 2 };
+    }
+    split(n, &mut primes);
+    group(primes)
+}
+
+fn split(n: u128, primes: &mut Vec<u128>) {
+    if n == 1 {
+        return;
+    }
+    match pollard_brent(n) {
+        Some(divisor) => {
+            split(divisor, primes);
+            split(n / divisor, primes);
+        }
+        None => primes.push(n),
+    }
+}
+
+// 把(可能重复、无序的)素因子合并成 (素数, 指数)
+pub(crate) fn group<T: Ord>(mut primes: Vec<T>) -> Vec<(T, u32)> {
+    primes.sort_unstable();
+    let mut factors: Vec<(T, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(factors: &[(u128, u32)]) -> u128 {
+        factors.iter().map(|&(p, e)| p.pow(e)).product()
+    }
+
+    #[test]
+    fn factors_small_and_edge_values() {
+        assert_eq!(prime_factors(0), []);
+        assert_eq!(prime_factors(1), []);
+        assert_eq!(prime_factors(2), [(2, 1)]);
+        assert_eq!(prime_factors(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(prime_factors(1 << 63), [(2, 63)]);
+        assert_eq!(prime_factors(u64::MAX - 58), [(u64::MAX - 58, 1)]);
+        assert_eq!(
+            prime_factors(u64::MAX),
+            [
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6700417, 1)
+            ]
+        );
+        for n in 1..3000u64 {
+            let factors = prime_factors(n);
+            assert!(factors.iter().all(|&(p, _)| is_prime(p)));
+            assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+            assert_eq!(factors.iter().map(|&(p, e)| p.pow(e)).product::<u64>(), n);
+        }
+    }
+
+    #[test]
+    fn factors_large_semiprimes_and_powers() {
+        let (p, q) = (2147483647u64, 2147483659u64);
+        assert_eq!(prime_factors(p * q), [(p, 1), (q, 1)]);
+        assert_eq!(prime_factors(p * p), [(p, 2)]);
+        assert_eq!(prime_factors(1_000_003u64.pow(3)), [(1_000_003, 3)]);
+    }
+
+    #[test]
+    fn factors_u128() {
+        let expected = [
+            (3, 1),
+            (5, 1),
+            (17, 1),
+            (257, 1),
+            (641, 1),
+            (65537, 1),
+            (274177, 1),
+            (6700417, 1),
+            (67280421310721, 1),
+        ];
+        assert_eq!(prime_factors_u128(u128::MAX), expected);
+        assert_eq!(product(&expected), u128::MAX);
+
+        let m61 = (1u128 << 61) - 1;
+        let mixed = m61 * 2147483647 * 1009u128.pow(2) * 12;
+        assert_eq!(
+            prime_factors_u128(mixed),
+            [(2, 2), (3, 1), (1009, 2), (2147483647, 1), (m61, 1)]
+        );
+        let m127 = (1u128 << 127) - 1;
+        assert_eq!(prime_factors_u128(m127), [(m127, 1)]);
+    }
+}
@@ -0,0 +1,245 @@
+// 素性测试(Miller-Rabin)
+// 把 n - 1 写成 d·2^s，素数 n 对任意底数 a 都满足 a^d ≡ 1 或某个 a^(d·2^r) ≡ -1 (mod n)，合数至多对 1/4 的底数满足。
+// 取前 12 个素数作底数对 n < 3.18·10^23 (覆盖整个 u64)不会误判，前 13 个素数覆盖 n < 3.3·10^24。
+// 更大的 u128 没有证明过足够的固定底数组，改用 Baillie-PSW：以 2 为底的强伪素数测试加上强 Lucas 测试，
+// 没有已知的反例。乘法在 u128 中进行；模数超过 64 位时乘积会溢出，改用倍加法，慢得多但结果正确。
+const BASES: [u128; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+const DETERMINISTIC_LIMIT: u128 = 3_317_044_064_679_887_385_961_981;
+
+pub fn is_prime(n: u64) -> bool {
+    is_prime_u128(n as u128)
+}
+
+// n 不小于 3.3·10^24 时结果来自 Baillie-PSW：没有已知的反例，但也没有证明不存在
+pub fn is_prime_u128(n: u128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < DETERMINISTIC_LIMIT {
+        BASES.iter().all(|&base| strong_probable_prime(n, base))
+    } else {
+        strong_probable_prime(n, 2) && strong_lucas_probable_prime(n)
+    }
+}
+
+// n 是大于 base 的奇数
+fn strong_probable_prime(n: u128, base: u128) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let mut x = pow_mod(base, (n - 1) >> s, n);
+    if x == 1 || x == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = mul_mod(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+// 强 Lucas 测试，参数按 Selfridge 的方法选取：D 依次取 5, -7, 9, -11, ... 中第一个使 Jacobi(D/n) = -1 的，
+// P = 1，Q = (1 - D)/4。n 是奇数且不能被 3 整除，所以 n + 1 不会溢出
+pub(crate) fn strong_lucas_probable_prime(n: u128) -> bool {
+    // 完全平方数找不到这样的 D
+    let root = n.isqrt();
+    if root * root == n {
+        return false;
+    }
+    let mut d: i128 = 5;
+    loop {
+        match jacobi(d, n) {
+            -1 => break,
+            0 if d.unsigned_abs() != n => return false,
+            _ => d = if d > 0 { -(d + 2) } else { 2 - d },
+        }
+    }
+    let (dm, q) = (residue(d, n), residue((1 - d) / 4, n));
+    let s = (n + 1).trailing_zeros();
+    let k = (n + 1) >> s;
+
+    // 从高位到低位求 U_k、V_k 和 Q^k
+    let (mut u, mut v, mut qk) = (0, 2 % n, 1 % n);
+    for bit in (0..u128::BITS - k.leading_zeros()).rev() {
+        // 下标翻倍：U_2m = U_m·V_m，V_2m = V_m² - 2Q^m
+        u = mul_mod(u, v, n);
+        v = sub_mod(mul_mod(v, v, n), add_mod(qk, qk, n), n);
+        qk = mul_mod(qk, qk, n);
+        if (k >> bit) & 1 == 1 {
+            // 下标加一：U_m+1 = (U_m + V_m)/2，V_m+1 = (D·U_m + V_m)/2
+            (u, v) = (
+                half(add_mod(u, v, n), n),
+                half(add_mod(mul_mod(dm, u, n), v, n), n),
+            );
+            qk = mul_mod(qk, q, n);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = sub_mod(mul_mod(v, v, n), add_mod(qk, qk, n), n);
+        qk = mul_mod(qk, qk, n);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+// Jacobi 符号 (a/n)，n 是正奇数
+fn jacobi(a: i128, n: u128) -> i32 {
+    let (mut a, mut n) = (residue(a, n), n);
+    let mut result = 1;
+    while a != 0 {
+        let twos = a.trailing_zeros();
+        a >>= twos;
+        if twos % 2 == 1 && matches!(n % 8, 3 | 5) {
+            result = -result;
+        }
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= n;
+    }
+    if n == 1 {
+        result
+    } else {
+        0
+    }
+}
+
+fn residue(a: i128, n: u128) -> u128 {
+    let r = a.unsigned_abs() % n;
+    if a < 0 && r != 0 {
+        n - r
+    } else {
+        r
+    }
+}
+
+// x/2 (mod n)，n 是奇数
+fn half(x: u128, n: u128) -> u128 {
+    if x.is_multiple_of(2) {
+        x / 2
+    } else {
+        x / 2 + n / 2 + 1
+    }
+}
+
+// 以下运算都要求参数已经小于 m
+pub(crate) fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= m {
+        sum.wrapping_sub(m)
+    } else {
+        sum
+    }
+}
+
+fn sub_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+pub(crate) fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if m >> 64 == 0 {
+        return a * b % m;
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    let (mut a, mut b, mut result) = (a, b, 0);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+pub(crate) fn pow_mod(base: u128, mut exp: u128, m: u128) -> u128 {
+    let (mut base, mut result) = (base % m, 1 % m);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trial_division(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn agrees_with_trial_division() {
+        for n in 0..20_000 {
+            assert_eq!(is_prime(n), trial_division(n), "{n}");
+        }
+        let start = 1_000_000_000_000u64;
+        for n in start..start + 2000 {
+            assert_eq!(is_prime(n), trial_division(n), "{n}");
+        }
+    }
+
+    #[test]
+    fn rejects_pseudoprimes() {
+        // Carmichael 数、以 2 为底的强伪素数，以及能骗过前几个素数底数的强伪素数
+        for n in [561u64, 1105, 41041, 2047, 3215031751, 3825123056546413051] {
+            assert!(!is_prime(n), "{n}");
+        }
+        assert!(!is_prime_u128(318665857834031151167461));
+        assert!(!is_prime_u128(3317044064679887385961981));
+        assert!(is_prime(u64::MAX - 58));
+        assert!(!is_prime(u64::MAX));
+    }
+
+    #[test]
+    fn baillie_psw_above_the_deterministic_limit() {
+        for exponent in [89, 107, 127] {
+            assert!(is_prime_u128((1 << exponent) - 1), "2^{exponent} - 1");
+        }
+        let m61 = (1u128 << 61) - 1;
+        assert!(!is_prime_u128(m61 * m61));
+        assert!(!is_prime_u128(m61 * ((1 << 31) - 1) * ((1 << 19) - 1)));
+        assert!(!is_prime_u128((1 << 101) - 1));
+        assert!(!is_prime_u128(u128::MAX));
+
+        // 强 Lucas 伪素数(OEIS A217255)之外，小奇数上与素性一致
+        let pseudoprimes = [
+            5459, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519,
+        ];
+        for n in (3..60_000u64).step_by(2) {
+            let root = n.isqrt();
+            if root * root == n {
+                continue;
+            }
+            assert_eq!(
+                strong_lucas_probable_prime(n as u128),
+                trial_division(n) || pseudoprimes.contains(&n),
+                "{n}"
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Pollard rho 找因子，用 Brent 的方法找环
+// 迭代 x ← x² + c (mod n)，对 n 的某个素因子 p 取模后序列大约 √p 步进入循环，这时 gcd(|x - y|, n) 是 p 的倍数。
+// Brent 的方法让 x 停在 2 的幂次步的位置、y 往前走，比 Floyd 的快慢指针少算不少乘法；
+// 每 BATCH 步把 |x - y| 连乘起来只求一次 gcd，乘积包含了 n 的全部因子时退回这一批逐步求 gcd。
+// 所需步数大约是 n 最小素因子的平方根：62 位的半素数只要几万步，两个 64 位素数的乘积就不现实了。
+use super::primality::{add_mod, is_prime_u128, mul_mod};
+
+const BATCH: u64 = 128;
+
+// 返回合数 n 的一个非平凡因子(不一定是素数)，n 是素数或小于 4 时返回 None
+pub fn pollard_brent(n: u128) -> Option<u128> {
+    if n < 4 || is_prime_u128(n) {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    // 找到的是 n 本身时换一个 c 重来
+    (1..n).find_map(|c| brent(n, c))
+}
+
+fn brent(n: u128, c: u128) -> Option<u128> {
+    let f = |x: u128| add_mod(mul_mod(x, x, n), c, n);
+    let (mut x, mut y, mut saved) = (2 % n, 2 % n, 2 % n);
+    let (mut product, mut divisor, mut length) = (1, 1, 1u64);
+    while divisor == 1 {
+        x = y;
+        for _ in 0..length {
+            y = f(y);
+        }
+        let mut done = 0;
+        while done < length && divisor == 1 {
+            saved = y;
+            for _ in 0..BATCH.min(length - done) {
+                y = f(y);
+                product = mul_mod(product, x.abs_diff(y), n);
+            }
+            divisor = gcd(product, n);
+            done += BATCH;
+        }
+        length *= 2;
+    }
+    if divisor == n {
+        divisor = 1;
+        while divisor == 1 {
+            saved = f(saved);
+            divisor = gcd(x.abs_diff(saved), n);
+        }
+    }
+    (divisor != n).then_some(divisor)
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nontrivial_divisors() {
+        for n in 4..5000u128 {
+            match pollard_brent(n) {
+                Some(d) => assert!(d > 1 && d < n && n.is_multiple_of(d), "{n}: {d}"),
+                None => assert!(is_prime_u128(n), "{n}"),
+            }
+        }
+        // 62 位的半素数
+        let (p, q) = (2147483647u128, 2147483659u128);
+        let d = pollard_brent(p * q).unwrap();
+        assert!(d == p || d == q);
+    }
+}
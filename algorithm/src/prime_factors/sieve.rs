@@ -0,0 +1,106 @@
+// 分段筛：按顺序列出区间 [start, end) 中的素数
+// 先求出 √end 以内的素数(同样用分段筛)，再把区间切成 SEGMENT 个数一段，每段用这些素数的倍数划掉合数。
+// 每段只占 SEGMENT 字节，放得进缓存；区间本身可以远大于内存，只有 √end 以内的素数需要一直保存
+// (end = 10^16 时约 576 万个)。
+use std::ops::Range;
+
+const SEGMENT: u64 = 1 << 15;
+
+#[derive(Debug, Clone)]
+pub struct Primes {
+    base: Vec<u64>,
+    next: u64,
+    end: u64,
+    found: std::vec::IntoIter<u64>,
+}
+
+pub fn primes(range: Range<u64>) -> Primes {
+    // 合数 m < end 一定有不超过 √m 的素因子
+    let limit = range.end.saturating_sub(1).isqrt();
+    let base = if limit < 2 {
+        Vec::new()
+    } else {
+        primes(2..limit + 1).collect()
+    };
+    Primes {
+        base,
+        next: range.start.max(2),
+        end: range.end,
+        found: Vec::new().into_iter(),
+    }
+}
+
+pub fn primes_up_to(limit: u64) -> Vec<u64> {
+    primes(2..limit.saturating_add(1)).collect()
+}
+
+impl Primes {
+    fn sieve_segment(&mut self) {
+        let low = self.next;
+        let high = low.saturating_add(SEGMENT).min(self.end);
+        let mut composite = vec![false; (high - low) as usize];
+        for &p in &self.base {
+            // p ≤ √end < 2^32，p² 不会溢出
+            let square = p * p;
+            if square >= high {
+                break;
+            }
+            let first = if square >= low {
+                square
+            } else {
+                match low.div_ceil(p).checked_mul(p) {
+                    Some(first) => first,
+                    None => continue,
+                }
+            };
+            for multiple in (first..high).step_by(p as usize) {
+                composite[(multiple - low) as usize] = true;
+            }
+        }
+        self.found = (low..high)
+            .filter(|&m| !composite[(m - low) as usize])
+            .collect::<Vec<_>>()
+            .into_iter();
+        self.next = high;
+    }
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            if let Some(p) = self.found.next() {
+                return Some(p);
+            }
+            if self.next >= self.end {
+                return None;
+            }
+            self.sieve_segment();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::is_prime;
+    use super::*;
+
+    #[test]
+    fn matches_primality_test() {
+        assert_eq!(primes_up_to(30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes_up_to(1).len(), 0);
+        assert_eq!(primes(0..1_000_000).count(), 78498);
+        assert_eq!(primes(24..29).collect::<Vec<_>>(), Vec::<u64>::new());
+
+        // 跨越多个段，起点不是段的边界
+        for range in [
+            0..100_000,
+            99_991..200_003,
+            1_000_000_000_000..1_000_000_100_000,
+        ] {
+            let expected: Vec<u64> = range.clone().filter(|&n| is_prime(n)).collect();
+            assert_eq!(primes(range).collect::<Vec<_>>(), expected);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+// 外部归并排序：给放不进内存的文本文件按行排序。
+// 先按 memory_limit 把输入切成若干块，每块在内存中用 sort_by 原地稳定排序(不复制行)后写成一个临时的"顺串"文件；
+// 再每次把最多 fan_in 个相邻的顺串多路归并成一个，直到剩下的顺串不超过 fan_in 个，最后一轮直接写到输出。
+// 归并时比较相等的行取较早的顺串，所以整体是稳定的。输入能放进一块时不产生临时文件。
+// 输出的每一行都以 '\n' 结尾，输入行尾的 "\r\n" 和 "\n" 都会去掉。临时文件在结束或出错时删除。
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+// 区分同一进程中同时进行的多次排序
+static NEXT_SORT: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+pub struct ExternalSorter {
+    memory_limit: usize,
+    fan_in: usize,
+    temp_dir: PathBuf,
+}
+
+impl Default for ExternalSorter {
+    fn default() -> Self {
+        ExternalSorter {
+            memory_limit: 256 * 1024 * 1024,
+            fan_in: 64,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+impl ExternalSorter {
+    pub fn new() -> Self {
+        ExternalSorter::default()
+    }
+
+    // 每块在内存中占用的大约字节数(行内容加上 String 本身)
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes.max(1);
+        self
+    }
+
+    // 每轮同时归并的顺串数，也是同时打开的临时文件数
+    pub fn fan_in(mut self, runs: usize) -> Self {
+        self.fan_in = runs.max(2);
+        self
+    }
+
+    pub fn temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = dir.into();
+        self
+    }
+
+    // 返回排序的行数
+    pub fn sort_file(&self, input: &Path, output: &Path) -> io::Result<usize> {
+        let input = BufReader::new(File::open(input)?);
+        self.sort_lines(input, BufWriter::new(File::create(output)?))
+    }
+
+    pub fn sort_lines(&self, input: impl BufRead, output: impl Write) -> io::Result<usize> {
+        self.sort_lines_by(input, output, |a, b| a.cmp(b))
+    }
+
+    pub fn sort_lines_by(
+        &self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+        mut compare: impl FnMut(&str, &str) -> Ordering,
+    ) -> io::Result<usize> {
+        let mut runs = Runs::new(&self.temp_dir);
+        let mut total = 0;
+        loop {
+            let (mut chunk, done) = read_chunk(&mut input, self.memory_limit)?;
+            total += chunk.len();
+            chunk.sort_by(|a, b| compare(a, b));
+            if done && runs.paths.is_empty() {
+                // 整个输入只有一块
+                write_lines(&chunk, &mut output)?;
+                output.flush()?;
+                return Ok(total);
+            }
+            let mut run = BufWriter::new(runs.create()?);
+            write_lines(&chunk, &mut run)?;
+            run.flush()?;
+            if done {
+                break;
+            }
+        }
+
+        while runs.paths.len() > self.fan_in {
+            let pending = runs.paths.clone();
+            for group in pending.chunks(self.fan_in) {
+                let mut run = BufWriter::new(runs.create()?);
+                merge_runs(group, &mut run, &mut compare)?;
+                run.flush()?;
+                runs.remove(group)?;
+            }
+        }
+        merge_runs(&runs.paths, &mut output, &mut compare)?;
+        output.flush()?;
+        Ok(total)
+    }
+}
+
+// 读到内存用量超过 limit 或输入结束为止，返回这些行以及输入是否已经读完
+fn read_chunk(input: &mut impl BufRead, limit: usize) -> io::Result<(Vec<String>, bool)> {
+    let mut lines = Vec::new();
+    let mut used = 0;
+    while used < limit {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok((lines, true));
+        }
+        trim_newline(&mut line);
+        used += line.len() + std::mem::size_of::<String>();
+        lines.push(line);
+    }
+    Ok((lines, false))
+}
+
+fn trim_newline(line: &mut String) {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+}
+
+fn write_lines(lines: &[String], out: &mut impl Write) -> io::Result<()> {
+    for line in lines {
+        out.write_all(line.as_bytes())?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// 多路归并：heap 是按当前行排序的顺串编号组成的小顶堆，比较器不是 Ord，所以手写堆
+fn merge_runs(
+    paths: &[PathBuf],
+    out: &mut impl Write,
+    compare: &mut impl FnMut(&str, &str) -> Ordering,
+) -> io::Result<()> {
+    let mut readers = Vec::with_capacity(paths.len());
+    let mut heads = Vec::with_capacity(paths.len());
+    for path in paths {
+        let mut reader = BufReader::new(File::open(path)?);
+        heads.push(next_line(&mut reader)?);
+        readers.push(reader);
+    }
+    let mut heap: Vec<usize> = (0..paths.len()).filter(|&i| heads[i].is_some()).collect();
+    // (顺串编号) 的先后：当前行小的在前，相等时编号小的在前
+    let mut less = |a: usize, b: usize, heads: &[Option<String>]| {
+        let (x, y) = (
+            heads[a].as_deref().unwrap_or_default(),
+            heads[b].as_deref().unwrap_or_default(),
+        );
+        compare(x, y).then(a.cmp(&b)) == Ordering::Less
+    };
+    for start in (0..heap.len() / 2).rev() {
+        sift_down(&mut heap, start, &heads, &mut less);
+    }
+    while let Some(&run) = heap.first() {
+        let line = heads[run].take().expect("堆中的顺串都有当前行");
+        out.write_all(line.as_bytes())?;
+        out.write_all(b"\n")?;
+        heads[run] = next_line(&mut readers[run])?;
+        if heads[run].is_none() {
+            heap.swap_remove(0);
+        }
+        sift_down(&mut heap, 0, &heads, &mut less);
+    }
+    Ok(())
+}
+
+fn sift_down(
+    heap: &mut [usize],
+    mut node: usize,
+    heads: &[Option<String>],
+    less: &mut impl FnMut(usize, usize, &[Option<String>]) -> bool,
+) {
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= heap.len() {
+            return;
+        }
+        if child + 1 < heap.len() && less(heap[child + 1], heap[child], heads) {
+            child += 1;
+        }
+        if !less(heap[child], heap[node], heads) {
+            return;
+        }
+        heap.swap(node, child);
+        node = child;
+    }
+}
+
+fn next_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    trim_newline(&mut line);
+    Ok(Some(line))
+}
+
+// 本次排序创建的临时文件，drop 时删除还没删掉的
+struct Runs {
+    dir: PathBuf,
+    prefix: String,
+    created: usize,
+    paths: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn new(dir: &Path) -> Self {
+        Runs {
+            dir: dir.to_path_buf(),
+            prefix: format!(
+                "external-sort-{}-{}",
+                std::process::id(),
+                NEXT_SORT.fetch_add(1, AtomicOrdering::Relaxed)
+            ),
+            created: 0,
+            paths: Vec::new(),
+        }
+    }
+
+    fn create(&mut self) -> io::Result<File> {
+        let path = self
+            .dir
+            .join(format!("{}-{}.run", self.prefix, self.created));
+        self.created += 1;
+        let file = File::options().write(true).create_new(true).open(&path)?;
+        self.paths.push(path);
+        Ok(file)
+    }
+
+    fn remove(&mut self, paths: &[PathBuf]) -> io::Result<()> {
+        for path in paths {
+            fs::remove_file(path)?;
+        }
+        self.paths.retain(|path| !paths.contains(path));
+        Ok(())
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Lcg;
+
+    fn lines(count: usize) -> Vec<String> {
+        let mut random = Lcg::new(11);
+        (0..count)
+            .map(|i| format!("{:03},{}", random.below(300), i))
+            .collect()
+    }
+
+    fn leftovers(dir: &Path) -> usize {
+        fs::read_dir(dir).unwrap().count()
+    }
+
+    #[test]
+    fn sorts_through_temporary_runs() {
+        let dir = std::env::temp_dir().join(format!("external-sort-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = lines(5000);
+        let text = input.join("\n");
+
+        // 按第一列排序，检查多轮归并后仍然稳定
+        let key = |line: &str| line.split(',').next().unwrap().to_string();
+        let mut expected = input.clone();
+        expected.sort_by_key(|line| key(line));
+
+        let sorter = ExternalSorter::new()
+            .memory_limit(4096)
+            .fan_in(3)
+            .temp_dir(&dir);
+        let mut output = Vec::new();
+        let count = sorter
+            .sort_lines_by(text.as_bytes(), &mut output, |a, b| key(a).cmp(&key(b)))
+            .unwrap();
+        assert_eq!(count, 5000);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            expected.join("\n") + "\n"
+        );
+        assert_eq!(leftovers(&dir), 0);
+
+        let (source, target) = (dir.join("input.csv"), dir.join("sorted.csv"));
+        fs::write(&source, "b\r\na\nc").unwrap();
+        assert_eq!(
+            ExternalSorter::new().sort_file(&source, &target).unwrap(),
+            3
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "a\nb\nc\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+/* 插入排序 - 通过插入到适当位置进行排序
+- 从第二个元素开始，与前面的元素比较以找到正确位置并插入。
+*/
+// 稳定，原地排序。O(n²)，但对很短或几乎有序的输入最快，归并排序和快速排序都用它处理短区间。
+use std::cmp::Ordering;
+
+pub fn insertion_sort<T: Ord>(slice: &mut [T]) {
+    insertion_sort_by(slice, T::cmp)
+}
+
+pub fn insertion_sort_by<T>(slice: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    for i in 1..slice.len() {
+        let mut j = i;
+        // 只有严格大于时才后移，相等的元素保持原来的先后顺序
+        while j > 0 && compare(&slice[j - 1], &slice[j]) == Ordering::Greater {
+            slice.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_ascending_and_keeps_ties_in_order() {
+        let mut numbers = vec![5, 2, 9, 1, 5, 6];
+        insertion_sort(&mut numbers);
+        assert_eq!(numbers, [1, 2, 5, 5, 6, 9]);
+
+        let mut pairs = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+        insertion_sort_by(&mut pairs, |a, b| a.0.cmp(&b.0));
+        assert_eq!(pairs, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+}
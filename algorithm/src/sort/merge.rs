@@ -0,0 +1,108 @@
+// 归并排序是一种复杂度为O(n log n)的排序算法。在Rust中，可以利用其数据结构高效地实现该算法。
+// 稳定排序。只在开始时分配一个和输入一样长的缓冲区，之后在输入和缓冲区之间来回归并，
+// 每层递归交换两者的角色，不再为每一层复制数组。归并时交换元素而不是克隆，元素只在建缓冲区时克隆一次。
+// 不超过 INSERTION_THRESHOLD 个元素的区间用插入排序。
+use super::insertion::insertion_sort_by;
+use std::cmp::Ordering;
+
+pub(crate) const INSERTION_THRESHOLD: usize = 24;
+
+pub fn merge_sort<T: Ord + Clone>(slice: &mut [T]) {
+    merge_sort_by(slice, T::cmp)
+}
+
+pub fn merge_sort_by<T: Clone>(slice: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    if slice.len() <= INSERTION_THRESHOLD {
+        insertion_sort_by(slice, compare);
+        return;
+    }
+    let mut buffer = slice.to_vec();
+    sort_in_place(slice, &mut buffer, &mut compare);
+}
+
+// key 在每次比较时计算，代价高时先算好再排序
+pub fn merge_sort_by_key<T: Clone, K: Ord>(slice: &mut [T], mut key: impl FnMut(&T) -> K) {
+    merge_sort_by(slice, |a, b| key(a).cmp(&key(b)))
+}
+
+// 排好 slice，scratch 是同样长的临时空间，内容会被打乱
+pub(crate) fn sort_in_place<T, F>(slice: &mut [T], scratch: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.len() <= INSERTION_THRESHOLD {
+        insertion_sort_by(slice, compare);
+        return;
+    }
+    let mid = slice.len() / 2;
+    let (scratch_left, scratch_right) = scratch.split_at_mut(mid);
+    let (left, right) = slice.split_at_mut(mid);
+    sort_into(left, scratch_left, compare);
+    sort_into(right, scratch_right, compare);
+    merge(scratch_left, scratch_right, slice, compare);
+}
+
+// 把 src 的元素排好序放进 dst，src 中留下 dst 原来的元素
+pub(crate) fn sort_into<T, F>(src: &mut [T], dst: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if src.len() <= INSERTION_THRESHOLD {
+        src.swap_with_slice(dst);
+        insertion_sort_by(dst, compare);
+        return;
+    }
+    let mid = src.len() / 2;
+    let (left, right) = src.split_at_mut(mid);
+    let (dst_left, dst_right) = dst.split_at_mut(mid);
+    sort_in_place(left, dst_left, compare);
+    sort_in_place(right, dst_right, compare);
+    merge(left, right, dst, compare);
+}
+
+// 把有序的 left 和 right 依次交换进 dst，相等时 left 的元素在前
+pub(crate) fn merge<T, F>(left: &mut [T], right: &mut [T], dst: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (mut i, mut j) = (0, 0);
+    for slot in dst.iter_mut() {
+        let take_left =
+            j == right.len() || (i < left.len() && compare(&right[j], &left[i]) != Ordering::Less);
+        if take_left {
+            std::mem::swap(slot, &mut left[i]);
+            i += 1;
+        } else {
+            std::mem::swap(slot, &mut right[j]);
+            j += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_generic_values_stably() {
+        let mut words: Vec<String> = "the quick brown fox jumps over the lazy dog again and again"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        let mut expected = words.clone();
+        expected.sort();
+        merge_sort(&mut words);
+        assert_eq!(words, expected);
+
+        // 按 key 排序，key 相同的保持原来的顺序
+        let mut numbered: Vec<(usize, usize)> = (0..200).map(|i| (i * 7919 % 13, i)).collect();
+        merge_sort_by_key(&mut numbered, |&(key, _)| key);
+        assert!(numbered
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+
+        let mut descending: Vec<i32> = (0..100).collect();
+        merge_sort_by(&mut descending, |a, b| b.cmp(a));
+        assert_eq!(descending, (0..100).rev().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,67 @@
+// 排序。所有排序都有三种形式：按 Ord、按比较函数(_by)、按提取的键(_by_key)。
+// 稳定(相等元素保持原来的先后顺序)：insertion_sort、merge_sort、par_merge_sort、radix_sort；
+// 不稳定但原地、不分配内存：quick_sort(内省排序)和 heap_sort_by。
+// radix_sort 只用于整数键；放不进内存的文件用 external::ExternalSorter 按行排序。
+pub mod external;
+pub mod insertion;
+pub mod merge;
+pub mod parallel;
+pub mod quick;
+pub mod radix;
+
+pub use external::ExternalSorter;
+pub use insertion::{insertion_sort, insertion_sort_by};
+pub use merge::{merge_sort, merge_sort_by, merge_sort_by_key};
+pub use parallel::{par_merge_sort, par_merge_sort_by, par_merge_sort_by_key};
+pub use quick::{heap_sort_by, quick_sort, quick_sort_by, quick_sort_by_key};
+pub use radix::{radix_sort, radix_sort_by_key, RadixKey};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Lcg;
+
+    type Sort = fn(&mut [(i32, usize)]);
+
+    // 各种排序在随机输入上都与标准库的结果一致，稳定的排序连相等元素的顺序也一致
+    #[test]
+    fn all_sorts_agree_with_std() {
+        let mut random = Lcg::new(5);
+        for len in [0, 1, 2, 23, 24, 25, 100, 1000, 10_000] {
+            let range = 1 + random.below(2 * len as u64 + 1);
+            let input: Vec<(i32, usize)> = (0..len)
+                .map(|i| (random.below(range) as i32 - range as i32 / 2, i))
+                .collect();
+            let mut expected = input.clone();
+            expected.sort_by_key(|&(key, _)| key);
+
+            let stable: [Sort; 4] = [
+                |v| insertion_sort_by(v, |a, b| a.0.cmp(&b.0)),
+                |v| merge_sort_by_key(v, |&(key, _)| key),
+                |v| par_merge_sort_by_key(v, |&(key, _)| key),
+                |v| radix_sort_by_key(v, |&(key, _)| key),
+            ];
+            for sort in stable {
+                let mut values = input.clone();
+                sort(&mut values);
+                assert_eq!(values, expected);
+            }
+
+            let unstable: [Sort; 2] = [
+                |v| quick_sort_by_key(v, |&(key, _)| key),
+                |v| heap_sort_by(v, |a, b| a.0.cmp(&b.0)),
+            ];
+            for sort in unstable {
+                let mut values = input.clone();
+                sort(&mut values);
+                let keys: Vec<i32> = values.iter().map(|&(key, _)| key).collect();
+                let expected_keys: Vec<i32> = expected.iter().map(|&(key, _)| key).collect();
+                assert_eq!(keys, expected_keys);
+                values.sort();
+                let mut all = input.clone();
+                all.sort();
+                assert_eq!(values, all);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// 并行归并排序（rayon）
+// 两半用 rayon::join 并行排序；归并时取较长一半的中点，在另一半里二分查找分界，
+// 拆成两个互不重叠的归并并行进行，所以最后一层归并也能用满所有线程。
+// 区间不超过 PARALLEL_THRESHOLD 时改用串行的 merge_sort，避免调度开销超过排序本身。
+// 结果和 merge_sort 完全相同(稳定)。
+use super::merge;
+use std::cmp::Ordering;
+
+const PARALLEL_THRESHOLD: usize = 4096;
+
+pub fn par_merge_sort<T: Ord + Clone + Send>(slice: &mut [T]) {
+    par_merge_sort_by(slice, T::cmp)
+}
+
+pub fn par_merge_sort_by<T, F>(slice: &mut [T], compare: F)
+where
+    T: Clone + Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if slice.len() <= PARALLEL_THRESHOLD {
+        merge::merge_sort_by(slice, compare);
+        return;
+    }
+    let mut buffer = slice.to_vec();
+    sort_in_place(slice, &mut buffer, &compare);
+}
+
+pub fn par_merge_sort_by_key<T, K, F>(slice: &mut [T], key: F)
+where
+    T: Clone + Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    par_merge_sort_by(slice, |a, b| key(a).cmp(&key(b)))
+}
+
+fn sort_in_place<T: Send, F>(slice: &mut [T], scratch: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if slice.len() <= PARALLEL_THRESHOLD {
+        merge::sort_in_place(slice, scratch, &mut |a, b| compare(a, b));
+        return;
+    }
+    let mid = slice.len() / 2;
+    let (scratch_left, scratch_right) = scratch.split_at_mut(mid);
+    let (left, right) = slice.split_at_mut(mid);
+    rayon::join(
+        || sort_into(left, scratch_left, compare),
+        || sort_into(right, scratch_right, compare),
+    );
+    par_merge(scratch_left, scratch_right, slice, compare);
+}
+
+fn sort_into<T: Send, F>(src: &mut [T], dst: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if src.len() <= PARALLEL_THRESHOLD {
+        merge::sort_into(src, dst, &mut |a, b| compare(a, b));
+        return;
+    }
+    let mid = src.len() / 2;
+    let (left, right) = src.split_at_mut(mid);
+    let (dst_left, dst_right) = dst.split_at_mut(mid);
+    rayon::join(
+        || sort_in_place(left, dst_left, compare),
+        || sort_in_place(right, dst_right, compare),
+    );
+    par_merge(left, right, dst, compare);
+}
+
+// 与 merge::merge 相同，相等时 left 的元素在前
+fn par_merge<T: Send, F>(left: &mut [T], right: &mut [T], dst: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if dst.len() <= PARALLEL_THRESHOLD {
+        merge::merge(left, right, dst, &mut |a, b| compare(a, b));
+        return;
+    }
+    // 前一部分的元素都不大于后一部分，和基准相等的元素按稳定性的要求分到对应的一边
+    let (i, j) = if left.len() >= right.len() {
+        let i = left.len() / 2;
+        (
+            i,
+            right.partition_point(|x| compare(x, &left[i]) == Ordering::Less),
+        )
+    } else {
+        let j = right.len() / 2;
+        (
+            left.partition_point(|x| compare(x, &right[j]) != Ordering::Greater),
+            j,
+        )
+    };
+    let (left_low, left_high) = left.split_at_mut(i);
+    let (right_low, right_high) = right.split_at_mut(j);
+    let (dst_low, dst_high) = dst.split_at_mut(i + j);
+    rayon::join(
+        || par_merge(left_low, right_low, dst_low, compare),
+        || par_merge(left_high, right_high, dst_high, compare),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Lcg;
+
+    #[test]
+    fn matches_the_sequential_sort() {
+        let mut random = Lcg::new(3);
+        let mut values: Vec<(u16, usize)> = (0..100_000)
+            .map(|i| (random.below(1 << 12) as u16, i))
+            .collect();
+        let mut expected = values.clone();
+        // 只按第一个分量比较，第二个分量检查稳定性
+        expected.sort_by_key(|&(key, _)| key);
+        par_merge_sort_by_key(&mut values, |&(key, _)| key);
+        assert_eq!(values, expected);
+
+        let mut numbers: Vec<i64> = (0..50_000).rev().collect();
+        par_merge_sort(&mut numbers);
+        assert!(numbers.is_sorted());
+    }
+}
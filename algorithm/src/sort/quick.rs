@@ -0,0 +1,149 @@
+// 快速排序（内省排序）
+// 不稳定，原地排序，不分配内存。三数取中选基准，Hoare 划分让相等的元素均匀分到两边；
+// 先递归较短的一边、较长的一边继续循环，栈深度不超过 log₂n。递归次数超过 2·log₂n 说明基准总是选得很差，
+// 剩下的区间改用堆排序，最坏情况也是 O(n log n)。
+use super::insertion::insertion_sort_by;
+use super::merge::INSERTION_THRESHOLD;
+use std::cmp::Ordering;
+
+pub fn quick_sort<T: Ord>(slice: &mut [T]) {
+    quick_sort_by(slice, T::cmp)
+}
+
+pub fn quick_sort_by<T>(slice: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    let depth = 2 * (usize::BITS - slice.len().leading_zeros()) as usize;
+    introsort(slice, depth, &mut compare);
+}
+
+pub fn quick_sort_by_key<T, K: Ord>(slice: &mut [T], mut key: impl FnMut(&T) -> K) {
+    quick_sort_by(slice, |a, b| key(a).cmp(&key(b)))
+}
+
+pub fn heap_sort_by<T>(slice: &mut [T], mut compare: impl FnMut(&T, &T) -> Ordering) {
+    for start in (0..slice.len() / 2).rev() {
+        sift_down(slice, start, &mut compare);
+    }
+    for end in (1..slice.len()).rev() {
+        slice.swap(0, end);
+        sift_down(&mut slice[..end], 0, &mut compare);
+    }
+}
+
+fn introsort<T, F>(mut slice: &mut [T], mut depth: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if slice.len() <= INSERTION_THRESHOLD {
+            insertion_sort_by(slice, compare);
+            return;
+        }
+        if depth == 0 {
+            heap_sort_by(slice, compare);
+            return;
+        }
+        depth -= 1;
+        let pivot = partition(slice, compare);
+        let (left, right) = std::mem::take(&mut slice).split_at_mut(pivot);
+        let right = &mut right[1..];
+        if left.len() < right.len() {
+            introsort(left, depth, compare);
+            slice = right;
+        } else {
+            introsort(right, depth, compare);
+            slice = left;
+        }
+    }
+}
+
+// 返回基准最终的位置：左边的元素都不大于它，右边的都不小于它
+fn partition<T, F>(slice: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (last, mid) = (slice.len() - 1, slice.len() / 2);
+    // 三数取中，中位数换到开头作为基准
+    if compare(&slice[mid], &slice[0]) == Ordering::Less {
+        slice.swap(mid, 0);
+    }
+    if compare(&slice[last], &slice[mid]) == Ordering::Less {
+        slice.swap(last, mid);
+        if compare(&slice[mid], &slice[0]) == Ordering::Less {
+            slice.swap(mid, 0);
+        }
+    }
+    slice.swap(0, mid);
+
+    let (mut i, mut j) = (1, last);
+    loop {
+        while i <= j && compare(&slice[i], &slice[0]) == Ordering::Less {
+            i += 1;
+        }
+        while i <= j && compare(&slice[j], &slice[0]) == Ordering::Greater {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        slice.swap(i, j);
+        i += 1;
+        j -= 1;
+    }
+    slice.swap(0, j);
+    j
+}
+
+fn sift_down<T, F>(heap: &mut [T], mut node: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= heap.len() {
+            return;
+        }
+        if child + 1 < heap.len() && compare(&heap[child], &heap[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+        if compare(&heap[node], &heap[child]) != Ordering::Less {
+            return;
+        }
+        heap.swap(node, child);
+        node = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_adversarial_inputs() {
+        let inputs: Vec<Vec<i32>> = vec![
+            vec![],
+            vec![1],
+            (0..1000).collect(),
+            (0..1000).rev().collect(),
+            vec![7; 1000],
+            (0..1000).map(|i| i % 3).collect(),
+            // 管风琴形状
+            (0..500).chain((0..500).rev()).collect(),
+        ];
+        for mut input in inputs {
+            let mut expected = input.clone();
+            expected.sort();
+            let mut heap = input.clone();
+            quick_sort(&mut input);
+            assert_eq!(input, expected);
+            heap_sort_by(&mut heap, i32::cmp);
+            assert_eq!(heap, expected);
+        }
+
+        let mut words = vec!["pear", "fig", "banana", "kiwi"];
+        quick_sort_by_key(&mut words, |word| word.len());
+        assert_eq!(
+            words.iter().map(|w| w.len()).collect::<Vec<_>>(),
+            [3, 4, 4, 6]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// 基数排序（LSD，每趟按一个字节）
+// 只用于整数键：从最低字节到最高字节各做一趟稳定的计数排序，复杂度 O(n·字节数)，不做比较。
+// 有符号整数把符号位取反后按无符号处理，负数就排在正数前面。
+// 某个字节在所有元素上都相同时(比如键都很小时的高位字节)跳过这一趟。
+pub trait RadixKey: Copy {
+    const BYTES: usize;
+    // 第 i 个字节，0 是最低字节
+    fn byte(self, i: usize) -> u8;
+}
+
+macro_rules! impl_radix_key {
+    ($($t:ty => $unsigned:ty),*) => {
+        $(impl RadixKey for $t {
+            const BYTES: usize = std::mem::size_of::<$t>();
+            fn byte(self, i: usize) -> u8 {
+                // 无符号类型的 flip 为 0
+                let flip = (<$t>::MIN as $unsigned) & (1 << (<$unsigned>::BITS - 1));
+                ((self as $unsigned ^ flip) >> (8 * i)) as u8
+            }
+        })*
+    };
+}
+
+impl_radix_key!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize
+);
+
+pub fn radix_sort<T: RadixKey>(slice: &mut [T]) {
+    radix_sort_by_key(slice, |&x| x)
+}
+
+// 稳定，每个元素的键只计算一次
+pub fn radix_sort_by_key<T: Clone, K: RadixKey>(slice: &mut [T], key: impl Fn(&T) -> K) {
+    let mut items: Vec<(K, T)> = slice.iter().map(|x| (key(x), x.clone())).collect();
+    let mut buffer = items.clone();
+    for byte in 0..K::BYTES {
+        let mut counts = [0usize; 256];
+        for (key, _) in &items {
+            counts[key.byte(byte) as usize] += 1;
+        }
+        if counts.contains(&items.len()) {
+            continue;
+        }
+        let mut offsets = [0usize; 256];
+        for b in 1..256 {
+            offsets[b] = offsets[b - 1] + counts[b - 1];
+        }
+        for item in &mut items {
+            let b = item.0.byte(byte) as usize;
+            std::mem::swap(&mut buffer[offsets[b]], item);
+            offsets[b] += 1;
+        }
+        std::mem::swap(&mut items, &mut buffer);
+    }
+    for (slot, (_, item)) in slice.iter_mut().zip(items) {
+        *slot = item;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_signed_and_unsigned_integers() {
+        let mut signed = vec![3i32, -1, i32::MIN, 0, i32::MAX, -1000, 42, -1];
+        radix_sort(&mut signed);
+        assert_eq!(signed, [i32::MIN, -1000, -1, -1, 0, 3, 42, i32::MAX]);
+
+        let mut big: Vec<u128> = (0..1000u128)
+            .map(|i| (i * 0x9e37_79b9_7f4a_7c15) << 40)
+            .collect();
+        let mut expected = big.clone();
+        expected.sort();
+        radix_sort(&mut big);
+        assert_eq!(big, expected);
+
+        let mut bytes = vec![200u8, 3, 3, 0, 255];
+        radix_sort(&mut bytes);
+        assert_eq!(bytes, [0, 3, 3, 200, 255]);
+    }
+
+    #[test]
+    fn sorts_records_stably_by_key() {
+        let mut records = vec![("b", -2i64), ("a", 5), ("c", -2), ("d", 0), ("e", 5)];
+        radix_sort_by_key(&mut records, |&(_, key)| key);
+        assert_eq!(
+            records,
+            [("b", -2), ("c", -2), ("d", 0), ("a", 5), ("e", 5)]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+// src/test_utils.rs
+// 测试共用的工具：固定种子的线性同余发生器，让随机测试每次生成同样的输入
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    // 31 位的随机数，取状态的高位(低位的周期很短)
+    pub fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    // [0, n) 中的数，n 远小于 2^31 时分布足够均匀
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
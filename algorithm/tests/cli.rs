@@ -0,0 +1,155 @@
+// 以子进程运行命令行，检查 JSON 输出和退出码
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> (i32, Value) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_algorithm"))
+        .args(["--format", "json"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // 参数错误时进程可能不读标准输入就退出了
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    let json = serde_json::from_slice(&output.stdout).unwrap();
+    (output.status.code().unwrap(), json)
+}
+
+#[test]
+fn graph_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let graph = dir.path().join("g.gr");
+    std::fs::write(
+        &graph,
+        "c 示例\np sp 4 5\na 1 2 7\na 1 3 2\na 3 2 3\na 2 4 1\na 3 4 9\n",
+    )
+    .unwrap();
+    let graph = graph.to_str().unwrap();
+
+    let path = |from, to| {
+        run(
+            &[
+                "shortest-path",
+                "--graph",
+                graph,
+                "--from",
+                from,
+                "--to",
+                to,
+            ],
+            "",
+        )
+    };
+    let (code, out) = path("1", "4");
+    assert_eq!(code, 0);
+    assert_eq!(out["algorithm"], "dijkstra");
+    assert_eq!(out["cost"], 6);
+    assert_eq!(out["path"], serde_json::json!(["1", "3", "2", "4"]));
+    assert_eq!(path("4", "1").0, 1);
+    let (code, out) = path("1", "9");
+    assert_eq!(code, 4);
+    assert!(out["error"].is_string());
+
+    // 标准输入的边表，负权边改用 Bellman-Ford
+    let (code, out) = run(
+        &["shortest-path", "--from", "a", "--to", "c"],
+        "a,b,4\nb,c,-2\na,c,3\n",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out["algorithm"], "bellman-ford");
+    assert_eq!(out["cost"], 2);
+    assert_eq!(
+        run(
+            &["shortest-path", "--from", "a", "--to", "b"],
+            "a b 1\nb a -2\n"
+        )
+        .0,
+        1
+    );
+
+    let (code, out) = run(
+        &["spanning-tree", "--graph", graph, "--algorithm", "prim"],
+        "",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out["cost"], 6);
+    let (code, out) = run(
+        &["max-flow", "--graph", graph, "--source", "1", "--sink", "4"],
+        "",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out["value"], 3);
+
+    let (code, out) = run(&["topological-sort"], "shirt tie 1\ntie jacket 1\n");
+    assert_eq!(code, 0);
+    assert_eq!(out["order"], serde_json::json!(["shirt", "tie", "jacket"]));
+    assert_eq!(run(&["topological-sort"], "a b 1\nb a 1\n").0, 1);
+    let (_, out) = run(&["components"], "a b 1\nb a 1\nb c 1\n");
+    assert_eq!(out["count"], 2);
+
+    let (code, out) = run(
+        &["shortest-path", "--from", "a", "--to", "b"],
+        "a b 1\nb c x\n",
+    );
+    assert_eq!(code, 3);
+    assert!(out["error"].as_str().unwrap().contains("第 2 行"));
+}
+
+#[test]
+fn sort_and_number_commands() {
+    for algorithm in ["merge", "quick", "heap", "parallel", "radix", "external"] {
+        let (code, out) = run(
+            &["sort", "--numeric", "--algorithm", algorithm],
+            "10\n-3\n7\n-3\n",
+        );
+        assert_eq!(code, 0, "{algorithm}");
+        assert_eq!(out["lines"], serde_json::json!(["-3", "-3", "7", "10"]));
+    }
+    let (_, out) = run(&["sort"], "pear\napple\nfig\n");
+    assert_eq!(out["lines"], serde_json::json!(["apple", "fig", "pear"]));
+    for algorithm in ["merge", "external"] {
+        let (code, out) = run(&["sort", "--numeric", "--algorithm", algorithm], "1\ntwo\n");
+        assert_eq!(code, 3, "{algorithm}");
+        assert!(out["error"].as_str().unwrap().contains("第 2 行"));
+    }
+    // 分成多个顺串时 JSON 同样逐行输出
+    let input: String = (0..500).rev().map(|i| format!("{i}\n")).collect();
+    let (code, out) = run(
+        &[
+            "sort",
+            "--numeric",
+            "--algorithm",
+            "external",
+            "--memory-limit",
+            "256",
+        ],
+        &input,
+    );
+    assert_eq!(code, 0);
+    let expected: Vec<String> = (0..500).map(|i| i.to_string()).collect();
+    assert_eq!(out["lines"], serde_json::json!(expected));
+    assert_eq!(out["algorithm"], "external");
+    assert_eq!(run(&["sort", "--algorithm", "radix"], "").0, 2);
+
+    let (code, out) = run(&["factor"], "360 4611686014132420609\n");
+    assert_eq!(code, 0);
+    assert_eq!(
+        out["results"][0]["factors"],
+        serde_json::json!([["2", 3], ["3", 2], ["5", 1]])
+    );
+    assert_eq!(
+        out["results"][1]["factors"],
+        serde_json::json!([["2147483647", 2]])
+    );
+    let (_, out) = run(&["factor", "170141183460469231731687303715884105727"], "");
+    assert_eq!(out["results"][0]["prime"], true);
+    assert_eq!(run(&["factor", "12x"], "").0, 3);
+
+    let (_, out) = run(&["primes", "90", "110"], "");
+    assert_eq!(out["primes"], serde_json::json!([97, 101, 103, 107, 109]));
+    let (_, out) = run(&["primes", "0", "1000000", "--count"], "");
+    assert_eq!(out["count"], 78498);
+}
@@ -0,0 +1,67 @@
+// 数论：分解不同位数的半素数(两个因子大小相近，对 Pollard rho 来说最难)、Miller-Rabin 判素和分段筛。
+// 分解和判素每次迭代处理一批数，吞吐量按个数计算；分段筛按区间长度计算。
+use algorithm::prime_factors::{is_prime, prime_factors, prime_factors_u128, primes};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion_bench::utils::{random_u64s, semiprimes};
+
+const BATCH: usize = 16;
+
+pub fn factorization_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("factorization");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    for bits in [32, 48, 62] {
+        let numbers = semiprimes(bits, BATCH);
+        group.bench_with_input(BenchmarkId::new("u64", bits), &numbers, |b, numbers| {
+            b.iter(|| {
+                for &n in numbers {
+                    black_box(prime_factors(black_box(n)));
+                }
+            })
+        });
+    }
+    // 超过 64 位后乘法改用倍加法，同样大小的因子慢得多
+    let m61 = (1u128 << 61) - 1;
+    let numbers: Vec<u128> = semiprimes(48, BATCH)
+        .into_iter()
+        .map(|n| n as u128 * m61)
+        .collect();
+    group.sample_size(10);
+    group.bench_with_input(BenchmarkId::new("u128", 109), &numbers, |b, numbers| {
+        b.iter(|| {
+            for &n in numbers {
+                black_box(prime_factors_u128(black_box(n)));
+            }
+        })
+    });
+    group.finish();
+}
+
+pub fn primality_benchmark(c: &mut Criterion) {
+    let numbers = random_u64s(1000, 5);
+    let mut group = c.benchmark_group("primality");
+    group.throughput(Throughput::Elements(numbers.len() as u64));
+    group.bench_function("is_prime_u64", |b| {
+        b.iter(|| numbers.iter().filter(|&&n| is_prime(black_box(n))).count())
+    });
+    group.finish();
+}
+
+pub fn sieve_benchmark(c: &mut Criterion) {
+    const WIDTH: u64 = 1_000_000;
+    let mut group = c.benchmark_group("segmented_sieve");
+    group.throughput(Throughput::Elements(WIDTH));
+    for start in [0, 1_000_000_000, 1_000_000_000_000] {
+        group.bench_with_input(BenchmarkId::from_parameter(start), &start, |b, &start| {
+            b.iter(|| primes(black_box(start)..start + WIDTH).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    factorization_benchmark,
+    primality_benchmark,
+    sieve_benchmark
+);
+criterion_main!(benches);
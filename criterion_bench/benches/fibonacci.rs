@@ -0,0 +1,55 @@
+// 斐波那契数的各种算法并排比较：u64 版本在 n = 20 和 90 上(递归只测 n = 20 和 30)，
+// 任意精度版本在 n = 1 千到 10 万上，n 越大 O(log n) 次乘法的优势越明显。
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion_bench::fib::{
+    fast_doubling, fast_doubling_big, iterative, iterative_big, matrix, matrix_big, memoized,
+};
+use criterion_bench::fibonacci;
+use num_bigint::BigUint;
+
+type Variant = fn(u64) -> Option<u64>;
+type BigVariant = fn(u64) -> BigUint;
+
+pub fn u64_benchmark(c: &mut Criterion) {
+    let variants: [(&str, Variant); 4] = [
+        ("iterative", iterative),
+        ("memoized", memoized),
+        ("matrix", matrix),
+        ("fast_doubling", fast_doubling),
+    ];
+    let mut group = c.benchmark_group("fibonacci");
+    for n in [20, 30] {
+        group.bench_with_input(BenchmarkId::new("recursive", n), &n, |b, &n| {
+            b.iter(|| fibonacci(black_box(n)))
+        });
+    }
+    for n in [20, 90] {
+        for (name, variant) in variants {
+            group.bench_with_input(BenchmarkId::new(name, n), &n, |b, &n| {
+                b.iter(|| variant(black_box(n)))
+            });
+        }
+    }
+    group.finish();
+}
+
+pub fn big_benchmark(c: &mut Criterion) {
+    let variants: [(&str, BigVariant); 3] = [
+        ("iterative_big", iterative_big),
+        ("matrix_big", matrix_big),
+        ("fast_doubling_big", fast_doubling_big),
+    ];
+    let mut group = c.benchmark_group("fibonacci_big");
+    group.sample_size(20);
+    for n in [1_000, 10_000, 100_000] {
+        for (name, variant) in variants {
+            group.bench_with_input(BenchmarkId::new(name, n), &n, |b, &n| {
+                b.iter(|| variant(black_box(n)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, u64_benchmark, big_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+// 最短路：随机稀疏图(每个节点 4 条出边)上从节点 0 出发的单源最短路，吞吐量按边数计算；
+// 网格寻路比较 A* 和 Jump Point Search，从左上角到右下角，吞吐量按格子数计算。
+use algorithm::graph::grid::{find_path, jump_point_search, Connectivity};
+use algorithm::graph::{bellman_ford, dijkstra, dijkstra_all};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion_bench::utils::{random_graph, random_grid};
+
+const DEGREE: usize = 4;
+
+pub fn graph_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shortest_path");
+    for nodes in [1_000, 10_000, 100_000] {
+        let graph = random_graph(nodes, DEGREE, 7);
+        group.throughput(Throughput::Elements(graph.edge_count() as u64));
+        group.bench_with_input(BenchmarkId::new("dijkstra_all", nodes), &graph, |b, g| {
+            b.iter(|| dijkstra_all(g, black_box(&0)).map(|paths| paths.cost(&(nodes - 1))))
+        });
+        group.bench_with_input(BenchmarkId::new("dijkstra", nodes), &graph, |b, g| {
+            b.iter(|| dijkstra(g, black_box(&0), &(nodes / 2)))
+        });
+        group.bench_with_input(BenchmarkId::new("bellman_ford", nodes), &graph, |b, g| {
+            b.iter(|| bellman_ford(g, black_box(&0)).map(|paths| paths.cost(&(nodes - 1))))
+        });
+    }
+    group.finish();
+}
+
+pub fn grid_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_path");
+    for size in [64, 256, 1024] {
+        let grid = random_grid(size, 20, 11);
+        let (start, goal) = ((0, 0), (size - 1, size - 1));
+        assert!(jump_point_search(&grid, start, goal).is_some());
+        group.throughput(Throughput::Elements((size * size) as u64));
+        group.bench_with_input(BenchmarkId::new("a_star", size), &grid, |b, grid| {
+            b.iter(|| find_path(grid, black_box(start), goal, Connectivity::Eight))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("jump_point_search", size),
+            &grid,
+            |b, grid| b.iter(|| jump_point_search(grid, black_box(start), goal)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, graph_benchmark, grid_benchmark);
+criterion_main!(benches);
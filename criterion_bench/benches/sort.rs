@@ -0,0 +1,35 @@
+// 排序：每种算法在 1 千到 100 万个随机 u64 上的耗时，吞吐量按元素个数计算。
+// 标准库的 sort 和 sort_unstable 作为参照。每次迭代排序输入的一份新拷贝，拷贝不计入耗时。
+use algorithm::sort::{heap_sort_by, merge_sort, par_merge_sort, quick_sort, radix_sort};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use criterion_bench::utils::random_u64s;
+
+const SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+type Sort = fn(&mut [u64]);
+
+pub fn sort_benchmark(c: &mut Criterion) {
+    let sorts: [(&str, Sort); 7] = [
+        ("merge_sort", merge_sort),
+        ("quick_sort", quick_sort),
+        ("heap_sort", |v| heap_sort_by(v, u64::cmp)),
+        ("par_merge_sort", par_merge_sort),
+        ("radix_sort", radix_sort),
+        ("std_sort", |v| v.sort()),
+        ("std_sort_unstable", |v| v.sort_unstable()),
+    ];
+    let mut group = c.benchmark_group("sort");
+    for size in SIZES {
+        let input = random_u64s(size, 42);
+        group.throughput(Throughput::Elements(size as u64));
+        for (name, sort) in sorts {
+            group.bench_with_input(BenchmarkId::new(name, size), &input, |b, input| {
+                b.iter_batched_ref(|| input.clone(), |v| sort(v), BatchSize::LargeInput)
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, sort_benchmark);
+criterion_main!(benches);
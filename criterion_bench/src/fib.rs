@@ -0,0 +1,193 @@
+// 斐波那契数的几种算法，用来对比复杂度。约定 fib(0) = 0，fib(1) = 1(与 bigint-pyo3 相同)。
+// fibonacci 是按定义的递归，指数时间；iterative 和 memoized 是 O(n) 次加法；
+// matrix 计算 [[1,1],[1,0]]^n，fast_doubling 用 F(2k) = F(k)(2F(k+1) - F(k))、F(2k+1) = F(k)² + F(k+1)²，
+// 两者都只要 O(log n) 次乘法，fast_doubling 的乘法更少。
+// u64 最多放得下 fib(93)，更大的 n 返回 None；_big 版本返回任意精度的结果。
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use std::ops::{Add, Mul, Sub};
+
+// fib(MAX_U64_INDEX) 是 u64 能表示的最大的斐波那契数
+pub const MAX_U64_INDEX: u64 = 93;
+
+// 按定义递归，n 超过 MAX_U64_INDEX 时 panic
+pub fn fibonacci(n: u64) -> u64 {
+    assert!(n <= MAX_U64_INDEX, "fib({}) 超出了 u64 的范围", n);
+    recursive(n)
+}
+
+#[inline]
+fn recursive(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        n => recursive(n - 1) + recursive(n - 2),
+    }
+}
+
+pub fn iterative(n: u64) -> Option<u64> {
+    if n == 0 {
+        return Some(0);
+    }
+    // (a, b) = (F(k - 1), F(k))，不提前算 F(n + 1)，fib(93) 不会误报溢出
+    let (mut a, mut b) = (0u64, 1u64);
+    for _ in 1..n {
+        (a, b) = (b, a.checked_add(b)?);
+    }
+    Some(b)
+}
+
+// 自顶向下的递归，每个 fib(k) 只算一次
+pub fn memoized(n: u64) -> Option<u64> {
+    fn go(n: usize, memo: &mut [Option<u64>]) -> u64 {
+        if let Some(value) = memo[n] {
+            return value;
+        }
+        let value = go(n - 1, memo) + go(n - 2, memo);
+        memo[n] = Some(value);
+        value
+    }
+    if n > MAX_U64_INDEX {
+        return None;
+    }
+    let mut memo = vec![None; n as usize + 2];
+    memo[0] = Some(0);
+    memo[1] = Some(1);
+    Some(go(n as usize, &mut memo))
+}
+
+// 过程中会算到 fib(n + 1)，在 u128 中计算再换回 u64
+pub fn matrix(n: u64) -> Option<u64> {
+    (n <= MAX_U64_INDEX).then(|| matrix_power::<u128>(n) as u64)
+}
+
+pub fn fast_doubling(n: u64) -> Option<u64> {
+    (n <= MAX_U64_INDEX).then(|| doubling::<u128>(n) as u64)
+}
+
+pub fn iterative_big(n: u64) -> BigUint {
+    let (mut a, mut b) = (BigUint::zero(), BigUint::one());
+    for _ in 0..n {
+        let next = &a + &b;
+        a = std::mem::replace(&mut b, next);
+    }
+    a
+}
+
+pub fn matrix_big(n: u64) -> BigUint {
+    matrix_power(n)
+}
+
+pub fn fast_doubling_big(n: u64) -> BigUint {
+    doubling(n)
+}
+
+// 2×2 矩阵，按行存放
+type Matrix<T> = [T; 4];
+
+fn multiply<T>(x: &Matrix<T>, y: &Matrix<T>) -> Matrix<T>
+where
+    for<'a> &'a T: Add<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    [
+        &(&x[0] * &y[0]) + &(&x[1] * &y[2]),
+        &(&x[0] * &y[1]) + &(&x[1] * &y[3]),
+        &(&x[2] * &y[0]) + &(&x[3] * &y[2]),
+        &(&x[2] * &y[1]) + &(&x[3] * &y[3]),
+    ]
+}
+
+// [[1,1],[1,0]]^n = [[F(n+1), F(n)], [F(n), F(n-1)]]
+fn matrix_power<T: Zero + One>(mut n: u64) -> T
+where
+    for<'a> &'a T: Add<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    let mut result = [T::one(), T::zero(), T::zero(), T::one()];
+    let mut base = [T::one(), T::one(), T::one(), T::zero()];
+    while n > 0 {
+        if n & 1 == 1 {
+            result = multiply(&result, &base);
+        }
+        n >>= 1;
+        // 最后一次平方用不到，跳过
+        if n > 0 {
+            base = multiply(&base, &base);
+        }
+    }
+    let [_, fib, _, _] = result;
+    fib
+}
+
+// 从最高位开始，(a, b) = (F(k), F(k+1))，每一位把 k 翻倍，该位为 1 时再加一
+fn doubling<T: Zero + One>(n: u64) -> T
+where
+    for<'a> &'a T: Add<&'a T, Output = T> + Sub<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    let (mut a, mut b) = (T::zero(), T::one());
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        let even = &a * &(&(&b + &b) - &a);
+        let odd = &(&a * &a) + &(&b * &b);
+        if (n >> bit) & 1 == 1 {
+            b = &even + &odd;
+            a = odd;
+        } else {
+            a = even;
+            b = odd;
+        }
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Variant = fn(u64) -> Option<u64>;
+
+    const VARIANTS: [(&str, Variant); 4] = [
+        ("iterative", iterative),
+        ("memoized", memoized),
+        ("matrix", matrix),
+        ("fast_doubling", fast_doubling),
+    ];
+
+    #[test]
+    fn all_variants_agree() {
+        assert_eq!(fibonacci(0), 0);
+        assert_eq!(fibonacci(1), 1);
+        assert_eq!(fibonacci(20), 6765);
+        for n in 0..=25 {
+            assert_eq!(iterative(n), Some(fibonacci(n)), "fib({n})");
+        }
+        for n in 0..=MAX_U64_INDEX {
+            let expected = iterative(n);
+            for (name, variant) in VARIANTS {
+                assert_eq!(variant(n), expected, "{name}({n})");
+            }
+            assert_eq!(fast_doubling_big(n), BigUint::from(expected.unwrap()));
+        }
+        assert_eq!(iterative(MAX_U64_INDEX), Some(12200160415121876738));
+        for (name, variant) in VARIANTS {
+            assert_eq!(variant(MAX_U64_INDEX + 1), None, "{name}");
+            assert_eq!(variant(u64::MAX), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn big_variants_agree() {
+        for n in (0..300).chain([1000, 4321]) {
+            let expected = iterative_big(n);
+            assert_eq!(matrix_big(n), expected, "matrix_big({n})");
+            assert_eq!(fast_doubling_big(n), expected, "fast_doubling_big({n})");
+        }
+        assert_eq!(fast_doubling_big(100).to_string(), "354224848179261915075");
+        // fib(1000) 有 209 位十进制数字
+        assert_eq!(fast_doubling_big(1000).to_string().len(), 209);
+    }
+
+    #[test]
+    #[should_panic]
+    fn recursive_rejects_overflowing_input() {
+        fibonacci(MAX_U64_INDEX + 1);
+    }
+}
@@ -1,11 +1,6 @@
 // https://bheisler.github.io/criterion.rs/book/getting_started.html
-mod utils;
+pub mod fib;
+pub mod regression;
+pub mod utils;
 
-#[inline]
-pub fn fibonacci(n: u64) -> u64 {
-    match n {
-        0 => 1,
-        1 => 1,
-        n => fibonacci(n - 1) + fibonacci(n - 2),
-    }
-}
+pub use fib::fibonacci;
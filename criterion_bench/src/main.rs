@@ -1,5 +1,132 @@
 // Criterion.rs：Rust 性能优化的利器 Criterion.rs是Rust的一个基准测试库，它允许开发人员以极高的精度测量其代码特定部分的性能。它旨在实现高度精确且易于使用，让您详细了解代码的运行时行为。
+// 这个程序是性能退化的检查：比较两个用 --save-baseline 保存的基线，逐个用例打印耗时和变化的置信区间，
+// 有用例变化区间的下界超过 --threshold 时以非零状态退出，可以直接放在 CI 里。
+use clap::{Parser, ValueEnum};
+use criterion_bench::regression::{compare, format_time, Statistic, Verdict};
+use std::path::PathBuf;
+use std::process;
+
+// 退出码: 0 没有退化，2 参数错误(由 clap 输出用法)
+// 至少一个用例退化
+const EXIT_REGRESSED: i32 = 1;
+// 读取估计值失败，或两个基线没有共同的用例
+const EXIT_INPUT: i32 = 3;
+
+#[derive(Parser)]
+#[command(
+    name = "criterion_bench",
+    version,
+    about = "比较两个 criterion 基线，有用例退化时以非零状态退出"
+)]
+struct Cli {
+    /// 作为参照的基线
+    #[arg(value_parser = baseline_name)]
+    base: String,
+    /// 要检查的基线
+    #[arg(value_parser = baseline_name)]
+    new: String,
+    /// 允许的变慢幅度(百分比)
+    #[arg(long, default_value_t = 5.0)]
+    threshold: f64,
+    /// 比较哪个统计量
+    #[arg(long, value_enum, default_value_t = StatisticArg::Mean)]
+    statistic: StatisticArg,
+    /// criterion 的输出目录
+    #[arg(long, default_value = "target/criterion")]
+    criterion_dir: PathBuf,
+}
+
+// criterion 每次运行都把结果写进 new，再复制到 --save-baseline 指定的目录，base 和 change 也是它自己用的目录。
+// 用这些名字保存的基线会被下一次运行覆盖(保存为 new 时复制到自身，文件直接被清空)
+const RESERVED_BASELINES: [&str; 3] = ["new", "base", "change"];
+
+fn baseline_name(name: &str) -> Result<String, String> {
+    if RESERVED_BASELINES.contains(&name) {
+        Err(format!(
+            "{} 是 criterion 内部使用的目录，请用 --save-baseline 另起一个名字",
+            name
+        ))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StatisticArg {
+    Mean,
+    Median,
+    Slope,
+}
 
 fn main() {
-    println!("Hello, world!");
+    let cli = Cli::parse();
+    let statistic = match cli.statistic {
+        StatisticArg::Mean => Statistic::Mean,
+        StatisticArg::Median => Statistic::Median,
+        StatisticArg::Slope => Statistic::Slope,
+    };
+    let report = compare(&cli.criterion_dir, &cli.base, &cli.new, statistic).unwrap_or_else(|e| {
+        eprintln!("错误: 读取 {} 失败: {}", cli.criterion_dir.display(), e);
+        process::exit(EXIT_INPUT);
+    });
+    if report.comparisons.is_empty() {
+        eprintln!(
+            "错误: {} 中没有同时保存了基线 {} 和 {} 的用例",
+            cli.criterion_dir.display(),
+            cli.base,
+            cli.new
+        );
+        process::exit(EXIT_INPUT);
+    }
+
+    let threshold = cli.threshold / 100.0;
+    let width = report
+        .comparisons
+        .iter()
+        .map(|c| c.id.chars().count())
+        .max()
+        .unwrap_or(0);
+    // 表头的汉字占两列，按显示宽度对齐
+    println!(
+        "{:<label$}  {:>12}  {:>12}  {:>6}  {:>15}  结论",
+        "用例",
+        cli.base,
+        cli.new,
+        "变化",
+        "置信区间",
+        label = width.saturating_sub(2)
+    );
+    for comparison in &report.comparisons {
+        let change = comparison.change();
+        let verdict = match comparison.verdict(threshold) {
+            Verdict::Regressed => "退化",
+            Verdict::Improved => "改进",
+            Verdict::Unchanged => "无明显变化",
+        };
+        println!(
+            "{:<width$}  {:>12}  {:>12}  {:>+7.2}%  [{:>+7.2}%, {:>+7.2}%]  {}",
+            comparison.id,
+            format_time(comparison.base.point_estimate),
+            format_time(comparison.new.point_estimate),
+            change.point * 100.0,
+            change.lower * 100.0,
+            change.upper * 100.0,
+            verdict
+        );
+    }
+    for id in &report.only_base {
+        println!("{}: 只在 {} 中", id, cli.base);
+    }
+    for id in &report.only_new {
+        println!("{}: 只在 {} 中", id, cli.new);
+    }
+
+    let regressed = report.regressions(threshold).count();
+    if regressed > 0 {
+        eprintln!(
+            "{} 个用例比 {} 慢了超过 {}%",
+            regressed, cli.base, cli.threshold
+        );
+        process::exit(EXIT_REGRESSED);
+    }
 }
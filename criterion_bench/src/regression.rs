@@ -0,0 +1,276 @@
+// 比较两个基线：读取 criterion 在 target/criterion/<用例>/<基线>/estimates.json 保存的估计值，
+// 对两边都有的用例计算耗时的相对变化。两次测量相互独立，用 criterion 给出的标准误差按 delta 方法
+// 估计 ln(新/旧) 的标准误差 √((σ新/新)² + (σ旧/旧)²)，在对数上取 95% 置信区间再换回比值。
+// 只有整个区间都超过阈值时才判为退化(或改进)，噪声不会让结论来回变化。
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 标准正态分布的 97.5% 分位数，对应双侧 95% 置信区间
+const Z_95: f64 = 1.959_963_984_540_054;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statistic {
+    Mean,
+    Median,
+    // 只有线性采样的用例才有，其余用例退回到 Mean
+    Slope,
+}
+
+// 单位是纳秒
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Estimate {
+    pub point_estimate: f64,
+    pub standard_error: f64,
+    pub confidence_interval: ConfidenceInterval,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ConfidenceInterval {
+    pub confidence_level: f64,
+    pub lower_bound: f64,
+    pub upper_bound: f64,
+}
+
+#[derive(Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    slope: Option<Estimate>,
+}
+
+#[derive(Deserialize)]
+struct BenchmarkInfo {
+    full_id: String,
+}
+
+// 相对变化，0.05 表示慢了 5%
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change {
+    pub point: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub id: String,
+    pub base: Estimate,
+    pub new: Estimate,
+}
+
+impl Comparison {
+    pub fn change(&self) -> Change {
+        let (base, new) = (&self.base, &self.new);
+        let ratio = new.point_estimate / base.point_estimate;
+        let relative = |estimate: &Estimate| estimate.standard_error / estimate.point_estimate;
+        let margin = Z_95 * relative(new).hypot(relative(base));
+        Change {
+            point: ratio - 1.0,
+            lower: ratio * (-margin).exp() - 1.0,
+            upper: ratio * margin.exp() - 1.0,
+        }
+    }
+
+    // threshold 是相对变化，0.05 表示 5%
+    pub fn verdict(&self, threshold: f64) -> Verdict {
+        let change = self.change();
+        if change.lower > threshold {
+            Verdict::Regressed
+        } else if change.upper < -threshold {
+            Verdict::Improved
+        } else {
+            Verdict::Unchanged
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub comparisons: Vec<Comparison>,
+    // 只在一个基线中出现的用例(新增或删除的)
+    pub only_base: Vec<String>,
+    pub only_new: Vec<String>,
+}
+
+impl Report {
+    pub fn regressions(&self, threshold: f64) -> impl Iterator<Item = &Comparison> {
+        self.comparisons
+            .iter()
+            .filter(move |comparison| comparison.verdict(threshold) == Verdict::Regressed)
+    }
+}
+
+// 按用例 id 排序
+pub fn compare(dir: &Path, base: &str, new: &str, statistic: Statistic) -> io::Result<Report> {
+    let mut found = BTreeMap::new();
+    walk(dir, base, new, statistic, &mut found)?;
+    let mut report = Report::default();
+    for (id, sides) in found {
+        match sides {
+            (Some(base), Some(new)) => report.comparisons.push(Comparison { id, base, new }),
+            (Some(_), None) => report.only_base.push(id),
+            (None, Some(_)) => report.only_new.push(id),
+            (None, None) => {}
+        }
+    }
+    Ok(report)
+}
+
+type Sides = (Option<Estimate>, Option<Estimate>);
+
+fn walk(
+    dir: &Path,
+    base: &str,
+    new: &str,
+    statistic: Statistic,
+    found: &mut BTreeMap<String, Sides>,
+) -> io::Result<()> {
+    let sides = (
+        read_estimate(&dir.join(base), statistic)?,
+        read_estimate(&dir.join(new), statistic)?,
+    );
+    if sides.0.is_some() || sides.1.is_some() {
+        let side = if sides.0.is_some() { base } else { new };
+        let info: BenchmarkInfo = read_json(&dir.join(side).join("benchmark.json"))?;
+        found.insert(info.full_id, sides);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // HTML 报告的目录里没有估计值
+        if entry.file_type()?.is_dir() && entry.file_name() != "report" {
+            walk(&entry.path(), base, new, statistic, found)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_estimate(dir: &Path, statistic: Statistic) -> io::Result<Option<Estimate>> {
+    let path = dir.join("estimates.json");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let estimates: Estimates = read_json(&path)?;
+    Ok(Some(match statistic {
+        Statistic::Mean => estimates.mean,
+        Statistic::Median => estimates.median,
+        Statistic::Slope => estimates.slope.unwrap_or(estimates.mean),
+    }))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let text = fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+// 纳秒数按大小换成合适的单位
+pub fn format_time(nanoseconds: f64) -> String {
+    let (value, unit) = if nanoseconds < 1e3 {
+        (nanoseconds, "ns")
+    } else if nanoseconds < 1e6 {
+        (nanoseconds / 1e3, "µs")
+    } else if nanoseconds < 1e9 {
+        (nanoseconds / 1e6, "ms")
+    } else {
+        (nanoseconds / 1e9, "s")
+    };
+    format!("{:.2} {}", value, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // spread 是 95% 置信区间的半宽
+    fn estimate(point: f64, spread: f64) -> String {
+        format!(
+            r#"{{"point_estimate": {point}, "standard_error": {},
+                "confidence_interval": {{"confidence_level": 0.95,
+                    "lower_bound": {}, "upper_bound": {}}}}}"#,
+            spread / Z_95,
+            point - spread,
+            point + spread
+        )
+    }
+
+    fn save(dir: &Path, id: &str, baseline: &str, point: f64, spread: f64) {
+        let path = dir.join(id).join(baseline);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(
+            path.join("estimates.json"),
+            format!(
+                r#"{{"mean": {}, "median": {}, "slope": null}}"#,
+                estimate(point, spread),
+                estimate(point, spread)
+            ),
+        )
+        .unwrap();
+        fs::write(
+            path.join("benchmark.json"),
+            format!(r#"{{"full_id": "{}", "title": "{}"}}"#, id, id),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn classifies_changes_by_confidence_interval() {
+        let dir = std::env::temp_dir().join(format!("regression-test-{}", std::process::id()));
+        save(&dir, "sort/merge/1000", "main", 100.0, 2.0);
+        save(&dir, "sort/merge/1000", "branch", 120.0, 2.0);
+        save(&dir, "sort/quick/1000", "main", 100.0, 2.0);
+        save(&dir, "sort/quick/1000", "branch", 80.0, 2.0);
+        // 点估计慢了 8%，但区间太宽，不算退化
+        save(&dir, "sort/heap/1000", "main", 100.0, 10.0);
+        save(&dir, "sort/heap/1000", "branch", 108.0, 10.0);
+        // 慢了 10%，两边都是 ±3%：区间端点最不利的组合 107/103 只慢 3.9%，但比值的 95% 区间整个在 5% 以上
+        save(&dir, "sort/radix/1000", "main", 100.0, 3.0);
+        save(&dir, "sort/radix/1000", "branch", 110.0, 3.0);
+        save(&dir, "fib/old", "main", 5.0, 0.1);
+        save(&dir, "fib/new", "branch", 5.0, 0.1);
+        fs::create_dir_all(dir.join("report")).unwrap();
+
+        let report = compare(&dir, "main", "branch", Statistic::Mean).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let verdicts: Vec<(&str, Verdict)> = report
+            .comparisons
+            .iter()
+            .map(|c| (c.id.as_str(), c.verdict(0.05)))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("sort/heap/1000", Verdict::Unchanged),
+                ("sort/merge/1000", Verdict::Regressed),
+                ("sort/quick/1000", Verdict::Improved),
+                ("sort/radix/1000", Verdict::Regressed),
+            ]
+        );
+        assert_eq!(report.only_base, ["fib/old"]);
+        assert_eq!(report.only_new, ["fib/new"]);
+
+        let merge = &report.comparisons[1];
+        let change = merge.change();
+        assert!((change.point - 0.2).abs() < 1e-9);
+        assert!(change.lower < change.point && change.point < change.upper);
+        // 阈值比变化的下界还高时不算退化
+        assert_eq!(merge.verdict(0.2), Verdict::Unchanged);
+        assert_eq!(report.regressions(0.05).count(), 2);
+
+        assert_eq!(format_time(950.0), "950.00 ns");
+        assert_eq!(format_time(16_729_178.5), "16.73 ms");
+    }
+}
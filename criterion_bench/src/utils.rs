@@ -0,0 +1,102 @@
+// 基准测试的输入数据。都由固定种子的伪随机数生成，每次运行、每个分支测的都是同样的输入，
+// 不同基线之间的结果才能直接比较。
+use algorithm::graph::grid::Grid;
+use algorithm::graph::Graph;
+use algorithm::prime_factors::primes;
+
+// 线性同余生成器，每次取高 32 位
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 32) as u32
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    // [0, n) 中的数，n 远小于 2^32 时分布足够均匀
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+pub fn random_u64s(len: usize, seed: u64) -> Vec<u64> {
+    let mut rng = Lcg::new(seed);
+    (0..len).map(|_| rng.next_u64()).collect()
+}
+
+// nodes 个节点的有向图，每个节点连向下一个节点(保证从 0 出发都可达)，
+// 再连 degree - 1 条随机的边，权重在 1..=100 之间
+pub fn random_graph(nodes: usize, degree: usize, seed: u64) -> Graph<usize, u32> {
+    let mut rng = Lcg::new(seed);
+    let mut graph = Graph::new();
+    for node in 0..nodes {
+        graph.add_node(node);
+    }
+    for node in 0..nodes {
+        graph.add_edge(node, (node + 1) % nodes, 1 + rng.below(100) as u32);
+        for _ in 1..degree {
+            graph.add_edge(node, rng.below(nodes), 1 + rng.below(100) as u32);
+        }
+    }
+    graph
+}
+
+// size × size 的网格，大约 blocked_percent% 的格子是障碍，左上角和右下角总是空地
+pub fn random_grid(size: usize, blocked_percent: usize, seed: u64) -> Grid {
+    let mut rng = Lcg::new(seed);
+    let mut grid = Grid::new(size, size);
+    for y in 0..size {
+        for x in 0..size {
+            grid.set_blocked((x, y), rng.below(100) < blocked_percent);
+        }
+    }
+    grid.set_blocked((0, 0), false);
+    grid.set_blocked((size - 1, size - 1), false);
+    grid
+}
+
+// count 个恰好 bits 位的半素数，两个因子都在 [√2^(bits-1), √2^bits) 中，大小相近。
+// 这个区间里的素数不够 2·count 个时返回的数会少一些
+pub fn semiprimes(bits: u32, count: usize) -> Vec<u64> {
+    assert!((4..=64).contains(&bits), "bits 必须在 4 到 64 之间");
+    // 因子的平方不小于 2^(bits-1)、不超过 2^bits - 1，两个因子的乘积就恰好是 bits 位
+    let start = (1u128 << (bits - 1)).isqrt();
+    let start = if start * start < 1 << (bits - 1) {
+        start + 1
+    } else {
+        start
+    };
+    let end = ((1u128 << bits) - 1).isqrt() + 1;
+    let factors: Vec<u64> = primes(start as u64..end as u64).take(2 * count).collect();
+    factors
+        .chunks_exact(2)
+        .map(|pair| pair[0] * pair[1])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semiprimes_have_exactly_the_requested_bits() {
+        for bits in [9, 32, 48, 62, 63, 64] {
+            let numbers = semiprimes(bits, 8);
+            assert!(!numbers.is_empty(), "{bits}");
+            for n in numbers {
+                assert_eq!(u64::BITS - n.leading_zeros(), bits, "{n}");
+            }
+        }
+    }
+}
@@ -0,0 +1,570 @@
+// src/api.rs
+// HTTP 接口和区块浏览器，沿用 ai/2122-axumapp_stepbystep 的 axum 写法。
+// 所有接口共享一个 Node，通过接口挖出的区块和提交的交易同样会广播给对等节点。
+use crate::block::Block;
+use crate::blockchain::Blockchain;
+use crate::node::{Message, Node};
+use crate::state::Call;
+use crate::transaction::Transaction;
+use crate::vm::Storage;
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse,
+    },
+    routing::{get, post},
+    Router,
+};
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::io;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tower_http::trace::TraceLayer;
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+
+type ApiError = (StatusCode, Json<ErrorBody>);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+fn error(status: StatusCode, message: impl ToString) -> ApiError {
+    (
+        status,
+        Json(ErrorBody {
+            error: message.to_string(),
+        }),
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Status {
+    pub consensus: String,
+    pub height: u64,
+    pub tip: String,
+    // u128 超出 JavaScript 数字能精确表示的范围，和命令行一样写成字符串
+    #[serde(with = "u128_string")]
+    pub work: u128,
+    pub next_difficulty: usize,
+    pub pending_transactions: usize,
+    pub pending_calls: usize,
+}
+
+// 与 validate 命令相同：完整重放整条链，无效时给出第一个出错的区块和原因
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Validity {
+    pub valid: bool,
+    pub error: Option<ValidityError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidityError {
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+mod u128_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+// 分页参数，区块按高度从高到低排列，offset 从链尾开始计
+#[derive(Debug, Deserialize, Default)]
+pub struct Pagination {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewBlock {
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Submitted {
+    pub txid: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmittedCall {
+    pub id: String,
+}
+
+// 链尾的世界状态
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorldStateView {
+    pub root: String,
+    pub balances: BTreeMap<String, u64>,
+    pub storage: BTreeMap<String, Storage>,
+    pub owners: BTreeMap<String, String>,
+}
+
+pub fn router(node: Node) -> Router {
+    Router::new()
+        .route("/", get(explorer))
+        .route("/api/status", get(status))
+        .route("/api/valid", get(validity))
+        .route("/api/blocks", get(blocks).post(mine_block))
+        .route("/api/blocks/:id", get(block))
+        .route("/api/transactions", post(submit_transaction))
+        .route("/api/transactions/:txid", get(transaction))
+        .route("/api/mempool", get(mempool))
+        .route("/api/calls", get(pending_calls).post(submit_call))
+        .route("/api/state", get(world_state))
+        .route("/api/events", get(events))
+        .layer(TraceLayer::new_for_http())
+        .fallback(handler_404)
+        .with_state(node)
+}
+
+pub async fn serve(listener: TcpListener, node: Node) -> io::Result<()> {
+    axum::serve(listener, router(node)).await
+}
+
+// 服务模式入口：在 http 上提供接口；给出 p2p 地址时同时作为 P2P 节点监听并连接 peers
+pub async fn run(
+    http: &str,
+    blockchain: Blockchain,
+    p2p: Option<String>,
+    peers: Vec<String>,
+) -> io::Result<()> {
+    let node = Node::new(blockchain);
+    if let Some(p2p) = p2p {
+        let listener = TcpListener::bind(&p2p).await?;
+        println!("节点监听 {}", listener.local_addr()?);
+        tokio::spawn(node.clone().serve(listener));
+        for peer in peers {
+            node.connect(peer);
+        }
+    }
+
+    let listener = TcpListener::bind(http).await?;
+    println!("区块浏览器: http://{}/", listener.local_addr()?);
+    serve(listener, node).await
+}
+
+async fn explorer() -> Html<&'static str> {
+    Html(EXPLORER_HTML)
+}
+
+async fn status(State(node): State<Node>) -> Json<Status> {
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    Json(Status {
+        consensus: chain.consensus.name().to_string(),
+        height: chain.tip().header.index,
+        tip: chain.tip().hash.clone(),
+        work: chain.cumulative_work(),
+        next_difficulty: chain.next_difficulty(),
+        pending_transactions: chain.mempool.len(),
+        pending_calls: chain.pending_calls.len(),
+    })
+}
+
+// 在链的副本上重放，校验期间不持有链锁
+async fn validity(State(node): State<Node>) -> Result<Json<Validity>, ApiError> {
+    let snapshot = node.chain().lock().unwrap().snapshot();
+    let result = tokio::task::spawn_blocking(move || snapshot.validate())
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(Json(Validity {
+        valid: result.is_ok(),
+        error: result.err().map(|e| ValidityError {
+            index: e.index(),
+            message: e.to_string(),
+        }),
+    }))
+}
+
+async fn blocks(
+    pagination: Option<Query<Pagination>>,
+    State(node): State<Node>,
+) -> Json<Vec<Block>> {
+    let Query(pagination) = pagination.unwrap_or_default();
+    let offset = pagination.offset.unwrap_or(0);
+    let limit = pagination
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .min(MAX_PAGE_SIZE);
+
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    Json(
+        chain
+            .chain
+            .iter()
+            .rev()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect(),
+    )
+}
+
+// id 是十进制数字时按高度查找，否则按区块哈希查找
+async fn block(Path(id): Path<String>, State(node): State<Node>) -> Result<Json<Block>, ApiError> {
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    chain
+        .find_block(&id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("区块 {} 不存在", id)))
+}
+
+async fn mine_block(
+    State(node): State<Node>,
+    Json(input): Json<NewBlock>,
+) -> Result<(StatusCode, Json<Block>), ApiError> {
+    match node.mine(input.data).await {
+        Ok(Some(block)) => Ok((StatusCode::CREATED, Json(block))),
+        Ok(None) => Err(error(StatusCode::CONFLICT, "挖矿期间链已更新，区块作废")),
+        Err(e) => Err(error(StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
+
+async fn submit_transaction(
+    State(node): State<Node>,
+    Json(tx): Json<Transaction>,
+) -> Result<(StatusCode, Json<Submitted>), ApiError> {
+    let txid = tx.id.clone();
+    node.submit_transaction(tx)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e))?;
+    Ok((StatusCode::ACCEPTED, Json(Submitted { txid })))
+}
+
+// 先在链上找，再在交易池里找
+async fn transaction(
+    Path(txid): Path<String>,
+    State(node): State<Node>,
+) -> Result<Json<Transaction>, ApiError> {
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    chain
+        .find_transaction(&txid)
+        .or_else(|| chain.mempool.get(&txid))
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("交易 {} 不存在", txid)))
+}
+
+async fn mempool(State(node): State<Node>) -> Json<Vec<Transaction>> {
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    Json(chain.mempool.transactions().cloned().collect())
+}
+
+async fn submit_call(
+    State(node): State<Node>,
+    Json(call): Json<Call>,
+) -> Result<(StatusCode, Json<SubmittedCall>), ApiError> {
+    let id = call.id();
+    node.submit_call(call)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e))?;
+    Ok((StatusCode::ACCEPTED, Json(SubmittedCall { id })))
+}
+
+async fn pending_calls(State(node): State<Node>) -> Json<Vec<Call>> {
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    Json(chain.pending_calls.clone())
+}
+
+async fn world_state(State(node): State<Node>) -> Result<Json<WorldStateView>, ApiError> {
+    let chain = node.chain();
+    let chain = chain.lock().unwrap();
+    let state = chain
+        .state()
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(Json(WorldStateView {
+        root: state.root(),
+        balances: state.balances,
+        storage: state.storage,
+        owners: state.owners,
+    }))
+}
+
+// 以 Server-Sent Events 推送新区块(block 事件)和链切换后的新链头(head 事件)
+async fn events(State(node): State<Node>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = stream::unfold(node.subscribe(), |mut events| async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(Message::NewBlock(block)) => Event::default().event("block").json_data(block),
+                Ok(head @ Message::Head { .. }) => Event::default().event("head").json_data(head),
+                Ok(_) => continue,
+                // 落后太多时跳过积压的消息，客户端可以重新拉取区块列表
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            if let Ok(event) = event {
+                return Some((Ok(event), events));
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn handler_404() -> impl IntoResponse {
+    error(StatusCode::NOT_FOUND, "nothing to see here")
+}
+
+const EXPLORER_HTML: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>区块浏览器</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; width: 100%; }
+td, th { border: 1px solid #ccc; padding: 4px 8px; text-align: left; font-family: monospace; }
+#status span { margin-right: 2em; }
+</style>
+</head>
+<body>
+<h1>区块浏览器</h1>
+<div id="status"></div>
+<form id="mine">
+    <input name="data" placeholder="区块数据" size="40">
+    <button type="submit">挖出新区块</button>
+</form>
+<table>
+    <thead><tr><th>高度</th><th>时间</th><th>难度</th><th>交易数</th><th>数据</th><th>哈希</th></tr></thead>
+    <tbody id="blocks"></tbody>
+</table>
+<script>
+function escape(text) {
+    const div = document.createElement("div");
+    div.textContent = text;
+    return div.innerHTML;
+}
+async function refresh() {
+    const status = await (await fetch("/api/status")).json();
+    const valid = await (await fetch("/api/valid")).json();
+    document.getElementById("status").innerHTML =
+        `<span>高度 ${status.height}</span><span>下一个难度 ${status.next_difficulty}</span>` +
+        `<span>待打包交易 ${status.pending_transactions}</span><span>待执行调用 ${status.pending_calls}</span><span>${valid.valid ? "链有效" : "链无效: " + escape(valid.error.message)}</span>`;
+    const blocks = await (await fetch("/api/blocks?limit=50")).json();
+    document.getElementById("blocks").innerHTML = blocks.map(b =>
+        `<tr><td>${b.header.index}</td><td>${escape(b.header.timestamp)}</td><td>${b.header.difficulty}</td>` +
+        `<td>${b.transactions.length}</td><td>${escape(b.data)}</td>` +
+        `<td><a href="/api/blocks/${b.hash}">${b.hash.slice(0, 16)}…</a></td></tr>`).join("");
+}
+document.getElementById("mine").addEventListener("submit", async event => {
+    event.preventDefault();
+    const data = event.target.data.value;
+    await fetch("/api/blocks", {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ data }),
+    });
+    event.target.data.value = "";
+});
+const events = new EventSource("/api/events");
+events.addEventListener("block", refresh);
+events.addEventListener("head", refresh);
+refresh();
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::BLOCK_REWARD;
+    use crate::transaction::{OutPoint, TxInput, TxOutput};
+    use crate::wallet::Wallet;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use serde::de::DeserializeOwned;
+    use tower::ServiceExt;
+
+    async fn call<T: DeserializeOwned>(
+        node: &Node,
+        method: &str,
+        uri: &str,
+        body: Option<String>,
+    ) -> (StatusCode, T) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, Body::from))
+            .unwrap();
+        let response = router(node.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn mine_and_fetch_blocks() {
+        let node = Node::new(Blockchain::new(1));
+
+        let (code, mined): (_, Block) = call(
+            &node,
+            "POST",
+            "/api/blocks",
+            Some(r#"{"data":"hello"}"#.to_string()),
+        )
+        .await;
+        assert_eq!(code, StatusCode::CREATED);
+        assert_eq!(mined.data, "hello");
+
+        let (_, status): (_, Status) = call(&node, "GET", "/api/status", None).await;
+        assert_eq!(status.height, 1);
+        assert_eq!(status.tip, mined.hash);
+
+        let (_, by_index): (_, Block) = call(&node, "GET", "/api/blocks/1", None).await;
+        let (_, by_hash): (_, Block) =
+            call(&node, "GET", &format!("/api/blocks/{}", mined.hash), None).await;
+        assert_eq!(by_index.hash, mined.hash);
+        assert_eq!(by_hash.hash, mined.hash);
+
+        let (code, _): (_, ErrorBody) = call(&node, "GET", "/api/blocks/7", None).await;
+        assert_eq!(code, StatusCode::NOT_FOUND);
+
+        let (_, list): (_, Vec<Block>) = call(&node, "GET", "/api/blocks?limit=1", None).await;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].hash, mined.hash);
+
+        let (_, raw): (_, serde_json::Value) = call(&node, "GET", "/api/status", None).await;
+        assert_eq!(raw["work"], status.work.to_string());
+
+        let (_, validity): (_, Validity) = call(&node, "GET", "/api/valid", None).await;
+        assert!(validity.valid);
+        // 与 validate 命令一样完整重放，而不是只看缓存的链尾状态
+        node.chain().lock().unwrap().chain[1].data = "tampered".to_string();
+        let (_, validity): (_, Validity) = call(&node, "GET", "/api/valid", None).await;
+        assert!(!validity.valid);
+        assert_eq!(validity.error.unwrap().index, Some(1));
+    }
+
+    #[tokio::test]
+    async fn submit_and_look_up_transaction() {
+        let (alice, bob) = (Wallet::generate(), Wallet::generate());
+        let node = Node::new(Blockchain::new(1));
+
+        // 不能通过接口铸币
+        let mint = Transaction::coinbase(1, alice.address(), 50);
+        let unsigned = Transaction::new(Vec::new(), mint.outputs.clone(), 0);
+        for forged in [mint, unsigned] {
+            let body = serde_json::to_string(&forged).unwrap();
+            let (code, _): (_, ErrorBody) =
+                call(&node, "POST", "/api/transactions", Some(body)).await;
+            assert_eq!(code, StatusCode::BAD_REQUEST);
+        }
+
+        // 出块奖励发给 alice，再由她转账给 bob
+        node.chain().lock().unwrap().reward_address = Some(alice.address());
+        let (_, mined): (_, Block) = call(
+            &node,
+            "POST",
+            "/api/blocks",
+            Some(r#"{"data":"reward"}"#.to_string()),
+        )
+        .await;
+        let mut tx = Transaction::new(
+            vec![TxInput::new(mined.transactions[0].id.clone(), 0)],
+            vec![TxOutput {
+                address: bob.address(),
+                amount: BLOCK_REWARD,
+            }],
+            0,
+        );
+        alice.sign(&mut tx);
+
+        let body = serde_json::to_string(&tx).unwrap();
+        let (code, submitted): (_, Submitted) =
+            call(&node, "POST", "/api/transactions", Some(body.clone())).await;
+        assert_eq!(code, StatusCode::ACCEPTED);
+        assert_eq!(submitted.txid, tx.id);
+
+        // 同一笔交易重复提交被拒绝
+        let (code, _): (_, ErrorBody) = call(&node, "POST", "/api/transactions", Some(body)).await;
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+
+        let uri = format!("/api/transactions/{}", tx.id);
+        let (code, found): (_, Transaction) = call(&node, "GET", &uri, None).await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(found, tx);
+    }
+
+    #[tokio::test]
+    async fn submit_call_and_read_state() {
+        let alice = Wallet::generate();
+        let node = Node::new(Blockchain::new(1));
+        node.chain().lock().unwrap().reward_address = Some(alice.address());
+        let (_, reward): (_, Block) = call(
+            &node,
+            "POST",
+            "/api/blocks",
+            Some(r#"{"data":"reward"}"#.to_string()),
+        )
+        .await;
+        let input = OutPoint {
+            txid: reward.transactions[0].id.clone(),
+            index: 0,
+        };
+        let signed = |code: &str| {
+            let mut call = Call::new(
+                "counter".to_string(),
+                code.to_string(),
+                40,
+                1,
+                vec![input.clone()],
+            );
+            alice.sign_call(&mut call);
+            serde_json::to_string(&call).unwrap()
+        };
+
+        // 未签名的调用和无法解析的程序都被拒绝
+        let mut unsigned: Call = serde_json::from_str(&signed("PUSH 0 PUSH 42 SSTORE")).unwrap();
+        unsigned.signature.clear();
+        for bad in [serde_json::to_string(&unsigned).unwrap(), signed("FLY")] {
+            let (code, _): (_, ErrorBody) = call(&node, "POST", "/api/calls", Some(bad)).await;
+            assert_eq!(code, StatusCode::BAD_REQUEST);
+        }
+
+        let (code, submitted): (_, SubmittedCall) = call(
+            &node,
+            "POST",
+            "/api/calls",
+            Some(signed("PUSH 0 PUSH 42 SSTORE")),
+        )
+        .await;
+        assert_eq!(code, StatusCode::ACCEPTED);
+        let (_, pending): (_, Vec<Call>) = call(&node, "GET", "/api/calls", None).await;
+        assert_eq!(pending[0].id(), submitted.id);
+
+        let (code, mined): (_, Block) = call(
+            &node,
+            "POST",
+            "/api/blocks",
+            Some(r#"{"data":"run"}"#.to_string()),
+        )
+        .await;
+        assert_eq!(code, StatusCode::CREATED);
+        let (_, state): (_, WorldStateView) = call(&node, "GET", "/api/state", None).await;
+        assert_eq!(state.root, mined.header.state_root);
+        assert_eq!(state.storage["counter"].get(&0), Some(&42));
+        assert_eq!(state.owners["counter"], alice.address());
+    }
+}
@@ -1,46 +1,242 @@
+use crate::encoding;
+use crate::merkle::{self, Hash, MerkleProof, MerkleTree};
+use crate::miner::Miner;
+use crate::state::Call;
+use crate::transaction::Transaction;
 use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-#[derive(Debug, Clone)]
-pub struct Block {
+// 区块头只包含定长字段和条目的 Merkle 根，区块哈希只对区块头计算，
+// 哈希开销与区块内容大小无关，轻节点只需要区块头加上包含证明就能校验单个条目。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
     pub index: u64,
     pub timestamp: String,
-    pub data: String,
     pub previous_hash: String,
+    pub merkle_root: String,
+    // 应用本区块后世界状态的根，见 state 模块
+    #[serde(default)]
+    pub state_root: String,
+    // 挖出这个区块时要求的难度：哈希前 difficulty 个十六进制位为 0
+    #[serde(default)]
+    pub difficulty: usize,
+    // 权益证明中出块验证者的十六进制公钥，工作量证明中为空
+    #[serde(default)]
+    pub proposer: String,
     pub nonce: u64,
+}
+
+impl BlockHeader {
+    // 对区块头的规范二进制编码求哈希，nonce 放在最后
+    pub fn calculate_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.hash_prefix());
+        hasher.update(self.nonce.to_le_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    // 参与哈希的除 nonce 以外的字段，挖矿时只需预先计算一次
+    pub fn hash_prefix(&self) -> Vec<u8> {
+        encoding::header_prefix(self)
+    }
+
+    pub fn time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok()
+    }
+
+    // 校验某个条目(叶子哈希)确实包含在这个区块中
+    pub fn verify_inclusion(&self, leaf: Hash, proof: &MerkleProof) -> bool {
+        match hex::decode(&self.merkle_root)
+            .ok()
+            .and_then(|root| Hash::try_from(root).ok())
+        {
+            Some(root) => proof.verify(leaf, &root),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub data: String,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+    // 合约调用，在交易之后按顺序执行
+    #[serde(default)]
+    pub calls: Vec<Call>,
     pub hash: String,
+    // 提议者对区块哈希的签名，只在权益证明中使用
+    #[serde(default)]
+    pub signature: String,
 }
 
 impl Block {
-    pub fn new(index: u64, data: String, previous_hash: String) -> Self {
-        let timestamp = Utc::now().to_rfc3339();
+    pub fn new(
+        index: u64,
+        data: String,
+        transactions: Vec<Transaction>,
+        previous_hash: String,
+    ) -> Self {
         let mut block = Block {
-            index,
-            timestamp,
+            header: BlockHeader {
+                index,
+                timestamp: Utc::now().to_rfc3339(),
+                previous_hash,
+                merkle_root: String::new(),
+                state_root: String::new(),
+                difficulty: 0,
+                proposer: String::new(),
+                nonce: 0,
+            },
             data,
-            previous_hash,
-            nonce: 0,
+            transactions,
+            calls: Vec::new(),
             hash: String::new(),
+            signature: String::new(),
         };
+        block.header.merkle_root = block.calculate_merkle_root();
         block.hash = block.calculate_hash();
         block
     }
 
     pub fn calculate_hash(&self) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(format!(
-            "{}{}{}{}{}",
-            self.index, self.timestamp, self.data, self.previous_hash, self.nonce
-        ));
-        format!("{:x}", hasher.finalize())
+        self.header.calculate_hash()
+    }
+
+    // 区块条目的叶子哈希：第 0 个是 data，之后依次是每笔交易和每个合约调用
+    pub fn entry_hashes(&self) -> Vec<Hash> {
+        let mut leaves = Vec::with_capacity(1 + self.transactions.len() + self.calls.len());
+        leaves.push(data_leaf(self.data.as_bytes()));
+        leaves.extend(self.transactions.iter().map(transaction_leaf));
+        leaves.extend(self.calls.iter().map(call_leaf));
+        leaves
+    }
+
+    pub fn merkle_tree(&self) -> MerkleTree {
+        MerkleTree::new(self.entry_hashes())
+    }
+
+    pub fn calculate_merkle_root(&self) -> String {
+        hex::encode(self.merkle_tree().root())
+    }
+
+    // 第 entry 个条目的包含证明，编号规则同 entry_hashes
+    pub fn inclusion_proof(&self, entry: usize) -> Option<MerkleProof> {
+        self.merkle_tree().proof(entry)
+    }
+
+    pub fn transaction_proof(&self, txid: &str) -> Option<MerkleProof> {
+        let position = self.transactions.iter().position(|tx| tx.id == txid)?;
+        self.inclusion_proof(position + 1)
     }
 
+    // 按给定难度挖矿，难度记录在区块头中；使用所有 CPU 核，需要取消时直接用 Miner
     pub fn mine_block(&mut self, difficulty: usize) {
-        let target = "0".repeat(difficulty);
-        while &self.hash[..difficulty] != target {
-            self.nonce += 1;
-            self.hash = self.calculate_hash();
+        let stats = Miner::default()
+            .mine(self, difficulty)
+            .expect("未设置取消标记的挖矿不会被取消");
+        println!("Block mined: {} ({:.0} H/s)", self.hash, stats.hashrate());
+    }
+}
+
+pub fn meets_difficulty(hash: &str, difficulty: usize) -> bool {
+    hash.len() >= difficulty && hash.bytes().take(difficulty).all(|b| b == b'0')
+}
+
+// 难度 d 要求哈希前 d 个十六进制位为 0，期望尝试 16^d 次
+pub fn block_work(difficulty: usize) -> u128 {
+    1u128
+        .checked_shl(4 * difficulty as u32)
+        .unwrap_or(u128::MAX)
+}
+
+// 条目叶子的类型，区块 data 的字节恰好等于某笔交易的哈希时，也不能把它当作交易证明
+pub const DATA_ENTRY: u8 = 0x00;
+pub const TRANSACTION_ENTRY: u8 = 0x01;
+pub const CALL_ENTRY: u8 = 0x02;
+
+pub fn data_leaf(data: &[u8]) -> Hash {
+    merkle::leaf_hash(DATA_ENTRY, data)
+}
+
+pub fn transaction_leaf(tx: &Transaction) -> Hash {
+    merkle::leaf_hash(TRANSACTION_ENTRY, &tx.full_hash())
+}
+
+pub fn call_leaf(call: &Call) -> Hash {
+    merkle::leaf_hash(CALL_ENTRY, &call.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::OutPoint;
+
+    fn mint(amount: u64) -> Transaction {
+        Transaction::coinbase(amount, "alice".to_string(), amount)
+    }
+
+    #[test]
+    fn transaction_inclusion_proof_verifies_against_header() {
+        let txs: Vec<_> = (1..=5).map(mint).collect();
+        let block = Block::new(1, "payload".to_string(), txs.clone(), "0".to_string());
+
+        for tx in &txs {
+            let proof = block.transaction_proof(&tx.id).unwrap();
+            assert!(block.header.verify_inclusion(transaction_leaf(tx), &proof));
         }
-        println!("Block mined: {}", self.hash);
+        let proof = block.inclusion_proof(0).unwrap();
+        assert!(block.header.verify_inclusion(data_leaf(b"payload"), &proof));
+        assert!(!block.header.verify_inclusion(data_leaf(b"forged"), &proof));
+
+        // 把第 2 笔交易的证明说成在别的位置
+        let mut moved = block.transaction_proof(&txs[1].id).unwrap();
+        moved.index = 1;
+        assert!(!block
+            .header
+            .verify_inclusion(transaction_leaf(&txs[1]), &moved));
+    }
+
+    #[test]
+    fn data_cannot_pose_as_another_entry_kind() {
+        // data 的字节等于一笔交易的哈希时，得到的叶子与交易的叶子不同
+        let tx = mint(7);
+        assert_ne!(data_leaf(&tx.full_hash()), transaction_leaf(&tx));
+
+        // 合约调用的编码都是 ASCII，可以原样放进 data：data 的证明不能证明这个调用
+        let call = Call::new(
+            "counter".to_string(),
+            "PUSH 1".to_string(),
+            100,
+            1,
+            vec![OutPoint {
+                txid: tx.id.clone(),
+                index: 0,
+            }],
+        );
+        let data = String::from_utf8(call.encode()).unwrap();
+        let block = Block::new(1, data, vec![tx], "0".to_string());
+        let proof = block.inclusion_proof(0).unwrap();
+        assert!(block
+            .header
+            .verify_inclusion(data_leaf(block.data.as_bytes()), &proof));
+        assert!(!block.header.verify_inclusion(call_leaf(&call), &proof));
+        assert!(!block.header.verify_inclusion(
+            merkle::leaf_hash(TRANSACTION_ENTRY, block.data.as_bytes()),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn hash_does_not_depend_on_payload_directly() {
+        let mut block = Block::new(1, "a".repeat(1 << 16), Vec::new(), "0".to_string());
+        assert_eq!(block.hash, block.calculate_hash());
+
+        // 改了内容但没更新 Merkle 根：区块哈希不变，Merkle 根对不上
+        block.data = "b".to_string();
+        assert_eq!(block.hash, block.calculate_hash());
+        assert_ne!(block.header.merkle_root, block.calculate_merkle_root());
     }
 }
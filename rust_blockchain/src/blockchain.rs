@@ -1,46 +1,1313 @@
 // src/blockchain.rs
-use crate::block::Block;
+use crate::block::{Block, BlockHeader};
+use crate::consensus::{Consensus, ProofOfWork};
+use crate::encoding;
+use crate::light::TransactionProof;
+use crate::mempool::{Mempool, MempoolError};
+use crate::miner::CancelToken;
+use crate::state::{add_outputs, spend, ApplyError, Call, CallError, WorldState, BLOCK_GAS_LIMIT};
+use crate::storage::ChainStore;
+use crate::transaction::{OutPoint, Transaction, TxOutput};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+// 区块中交易的总字节数上限
+pub const MAX_BLOCK_SIZE: usize = 1_000_000;
+// 每个区块的铸币交易最多领取的新币，另加区块内交易的手续费
+pub const BLOCK_REWARD: u64 = 50;
+// 创世区块使用固定时间戳，保证所有节点的创世区块相同
+const GENESIS_TIMESTAMP: &str = "2024-01-01T00:00:00+00:00";
+// 默认每 10 个区块按最近出块时间调整一次难度，目标 10 秒一个区块
+pub const DEFAULT_RETARGET_INTERVAL: u64 = 10;
+pub const DEFAULT_TARGET_BLOCK_TIME: Duration = Duration::from_secs(10);
+// 难度每加 1 工作量变为 16 倍，出块时间偏离目标超过 4 倍(16 的平方根)才调整
+const RETARGET_FACTOR: u128 = 4;
+const MAX_DIFFICULTY: usize = 64;
 
 #[derive(Debug)]
 pub struct Blockchain {
     pub chain: Vec<Block>,
-    pub difficulty: usize,
+    pub retarget_interval: u64,
+    pub target_block_time: Duration,
+    pub mempool: Mempool,
+    // 等待打包的合约调用，按提交顺序执行
+    pub pending_calls: Vec<Call>,
+    pub max_block_size: usize,
+    // 出块奖励的接收地址，为 None 时新区块不含铸币交易
+    pub reward_address: Option<String>,
+    // 区块的封装、校验和分叉权重规则，默认为工作量证明
+    pub consensus: Arc<dyn Consensus>,
+    store: Option<ChainStore>,
+    // 链尾哈希和校验通过后的世界状态，新区块只需在它上面增量校验；链尾变了就作废
+    verified: Option<(String, WorldState)>,
+    // 打开日志时截断的尾部字节数(上次写入中途崩溃留下的不完整记录)
+    discarded_bytes: usize,
 }
 
 impl Blockchain {
+    // difficulty 是第 1 个区块的难度，记录在创世区块头中
     pub fn new(difficulty: usize) -> Self {
-        let mut blockchain = Blockchain {
-            chain: Vec::new(),
-            difficulty,
-        };
-        blockchain.chain.push(Blockchain::create_genesis_block());
+        let genesis = Blockchain::create_genesis_block(difficulty);
+        let mut blockchain = Blockchain::from_blocks(vec![genesis]);
+        blockchain.verify().expect("创世区块总是有效的");
         blockchain
     }
 
-    fn create_genesis_block() -> Block {
-        Block::new(0, "Genesis Block".to_string(), "0".to_string())
+    // 从磁盘日志加载区块链，文件不存在时创建并写入创世区块；
+    // difficulty 只用于新建的创世区块，已有的链按创世区块中记录的难度校验
+    pub fn open<P: AsRef<Path>>(path: P, difficulty: usize) -> io::Result<Self> {
+        Blockchain::open_with_consensus(path, difficulty, Arc::new(ProofOfWork))
+    }
+
+    // 按给定的共识规则加载并校验磁盘上的区块链
+    pub fn open_with_consensus<P: AsRef<Path>>(
+        path: P,
+        difficulty: usize,
+        consensus: Arc<dyn Consensus>,
+    ) -> io::Result<Self> {
+        let (mut store, chain, discarded) = ChainStore::open(path)?;
+        let mut blockchain = Blockchain::from_blocks(chain);
+        blockchain.consensus = consensus;
+        blockchain.discarded_bytes = discarded;
+
+        if blockchain.chain.is_empty() {
+            let genesis = Blockchain::create_genesis_block(difficulty);
+            store.append(&genesis)?;
+            blockchain.chain.push(genesis);
+        }
+        if let Err(e) = blockchain.verify() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("区块链数据校验失败: {}", e),
+            ));
+        }
+
+        blockchain.store = Some(store);
+        Ok(blockchain)
+    }
+
+    // 由已有区块构造只在内存中的区块链，不做校验
+    pub fn from_blocks(chain: Vec<Block>) -> Self {
+        Blockchain {
+            chain,
+            retarget_interval: DEFAULT_RETARGET_INTERVAL,
+            target_block_time: DEFAULT_TARGET_BLOCK_TIME,
+            mempool: Mempool::new(),
+            pending_calls: Vec::new(),
+            max_block_size: MAX_BLOCK_SIZE,
+            reward_address: None,
+            consensus: Arc::new(ProofOfWork),
+            store: None,
+            verified: None,
+            discarded_bytes: 0,
+        }
+    }
+
+    pub fn discarded_bytes(&self) -> usize {
+        self.discarded_bytes
+    }
+
+    // 把整条链导出为版本化的二进制文件，格式见 encoding 模块
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        encoding::export_chain(path, &self.chain)
     }
 
-    pub fn add_block(&mut self, data: String) {
-        let previous_block = self.chain.last().unwrap().clone();
-        let mut new_block = Block::new(previous_block.index + 1, data, previous_block.hash);
-        new_block.mine_block(self.difficulty);
-        self.chain.push(new_block);
+    // 从导出文件加载只在内存中的区块链，完整校验后才返回
+    pub fn import<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut blockchain = Blockchain::from_blocks(encoding::import_chain(path)?);
+        if let Err(e) = blockchain.verify() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("导入的区块链无效: {}", e),
+            ));
+        }
+        Ok(blockchain)
+    }
+
+    // 创世区块不需要挖矿，它的 difficulty 字段记录第 1 个区块的难度
+    pub(crate) fn create_genesis_block(difficulty: usize) -> Block {
+        let mut genesis = Block::new(0, "Genesis Block".to_string(), Vec::new(), "0".to_string());
+        genesis.header.timestamp = GENESIS_TIMESTAMP.to_string();
+        genesis.header.difficulty = difficulty;
+        genesis.header.merkle_root = genesis.calculate_merkle_root();
+        genesis.header.state_root = WorldState::default().root();
+        genesis.hash = genesis.calculate_hash();
+        genesis
+    }
+
+    // 与本链共用难度规则的另一条链，用于校验分叉
+    fn with_same_rules(&self, chain: Vec<Block>) -> Blockchain {
+        let mut other = Blockchain::from_blocks(chain);
+        other.retarget_interval = self.retarget_interval;
+        other.target_block_time = self.target_block_time;
+        other.consensus = self.consensus.clone();
+        other
+    }
+
+    // 规则相同的副本，只有区块，没有数据文件、交易池和缓存的状态
+    pub fn snapshot(&self) -> Blockchain {
+        self.with_same_rules(self.chain.clone())
+    }
+
+    pub fn tip(&self) -> &Block {
+        self.chain.last().unwrap()
+    }
+
+    // 第 index 个区块应有的难度，只依赖它之前的区块；时间戳无法解析时返回 None
+    pub fn expected_difficulty(&self, index: usize) -> Option<usize> {
+        expected_difficulty(
+            index,
+            self.chain[0].header.difficulty,
+            self.retarget_interval,
+            self.target_block_time,
+            |i| &self.chain[i].header,
+        )
+    }
+
+    pub fn next_difficulty(&self) -> usize {
+        self.expected_difficulty(self.chain.len())
+            .unwrap_or(self.tip().header.difficulty)
+    }
+
+    // 校验交易的输入并放入交易池，等待下一次出块打包
+    pub fn submit_transaction(&mut self, tx: Transaction) -> Result<(), MempoolError> {
+        tx.validate()?;
+        if tx.is_coinbase() {
+            return Err(MempoolError::Coinbase);
+        }
+        if self.find_transaction(&tx.id).is_some() {
+            return Err(MempoolError::Duplicate(tx.id));
+        }
+
+        // 输入可以引用链上未花费的输出，也可以引用池中交易的输出
+        let mut unspent = self.utxo_set();
+        for pending in self.mempool.transactions() {
+            add_outputs(&mut unspent, pending);
+        }
+        spend(&mut unspent, &tx)?;
+
+        self.mempool.add(tx)
+    }
+
+    // 检查合约调用的签名、所有者和费用并放入待打包队列，执行结果在出块时才确定
+    pub fn submit_call(&mut self, call: Call) -> Result<(), CallError> {
+        call.check()?;
+        let id = call.id();
+        let known = self
+            .chain
+            .iter()
+            .flat_map(|block| &block.calls)
+            .chain(&self.pending_calls)
+            .any(|other| other.id() == id);
+        if known {
+            return Err(CallError::Duplicate(id));
+        }
+        // 在链尾状态上试执行；待打包的调用之间的冲突(花费同一个输出、抢同一个合约)在出块时处理
+        if let Ok(mut state) = self.state() {
+            state.apply_call(&call)?;
+        }
+        self.pending_calls.push(call);
+        Ok(())
+    }
+
+    // 链尾已通过校验时的世界状态，不需要重放
+    pub fn verified_state(&self) -> Option<&WorldState> {
+        let (hash, state) = self.verified.as_ref()?;
+        (Some(hash) == self.chain.last().map(|block| &block.hash)).then_some(state)
+    }
+
+    // 当前世界状态，链尾没有校验过时按链上所有区块重放
+    pub fn state(&self) -> Result<WorldState, ApplyError> {
+        match self.verified_state() {
+            Some(state) => Ok(state.clone()),
+            None => WorldState::replay(&self.chain),
+        }
+    }
+
+    // 用 data 和交易池中手续费率最高的交易组装新区块，按共识规则封装后接在链尾。
+    // 设置了 reward_address 时第一笔交易是领取奖励和手续费的铸币交易
+    pub fn add_block(&mut self, data: String) -> io::Result<()> {
+        let mut new_block = self.prepare_block(data);
+        self.consensus
+            .seal(&mut new_block, &CancelToken::new())
+            .map_err(io::Error::other)?;
+        if !self.append_block(new_block)? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "新区块未通过校验",
+            ));
+        }
+        Ok(())
+    }
+
+    // 组装接在链尾的候选区块，状态根和共识相关的区块头字段已填好，尚未封装。
+    // 交易池中的交易可能因为别处的区块已经失效，在链尾状态上逐笔应用，失败的交易连同后代移出交易池
+    pub fn prepare_block(&mut self, data: String) -> Block {
+        let previous_block = self.tip();
+        let (index, previous_hash) = (previous_block.header.index + 1, previous_block.hash.clone());
+        let selected = self.mempool.select(self.max_block_size);
+        // 链尾状态无法重放说明链本身无效，在它上面出的块不会被接受，状态根留空
+        let mut state = self.state().ok();
+        let mut transactions = Vec::with_capacity(selected.len() + 1);
+        for tx in selected {
+            match state.as_mut().map(|state| state.apply_transaction(&tx)) {
+                Some(Err(_)) => self.mempool.evict(&tx.id),
+                _ => transactions.push(tx),
+            }
+        }
+        if let Some(address) = &self.reward_address {
+            let amount = BLOCK_REWARD.saturating_add(total_fees(&transactions));
+            let coinbase = Transaction::coinbase(index, address.clone(), amount);
+            if let Some(state) = state.as_mut() {
+                state
+                    .apply_transaction(&coinbase)
+                    .expect("铸币交易不花费任何输出");
+            }
+            transactions.insert(0, coinbase);
+        }
+        let mut block = Block::new(index, data, transactions, previous_hash);
+
+        if let Some(mut state) = state {
+            // 待打包的调用之间可能冲突，只打包在当前状态上仍然有效的
+            let mut gas = 0;
+            for call in &self.pending_calls {
+                if gas + call.gas_limit > BLOCK_GAS_LIMIT {
+                    break;
+                }
+                if state.apply_call(call).is_ok() {
+                    gas += call.gas_limit;
+                    block.calls.push(call.clone());
+                }
+            }
+            block.header.state_root = state.root();
+        }
+        block.header.merkle_root = block.calculate_merkle_root();
+
+        self.consensus.prepare(self, &mut block);
+        block
+    }
+
+    // 接收别处挖出的区块，校验通过并接在链尾时返回 true。
+    // 只在链尾的世界状态上检查这一个区块，链尾没有校验过时才先校验整条链
+    pub fn append_block(&mut self, block: Block) -> io::Result<bool> {
+        if block.header.previous_hash != self.tip().hash
+            || block.header.index != self.tip().header.index + 1
+        {
+            return Ok(false);
+        }
+        let mut state = match self.verified_state() {
+            Some(state) => state.clone(),
+            None => match self.replay() {
+                Ok(state) => state,
+                Err(_) => return Ok(false),
+            },
+        };
+
+        self.chain.push(block);
+        let result = self.check_block(self.chain.len() - 1, &mut state);
+        let block = self.chain.pop().unwrap();
+        if result.is_err() {
+            return Ok(false);
+        }
+
+        // 先落盘再加入内存，保证内存中的链不会比磁盘上的新
+        if let Some(store) = self.store.as_mut() {
+            store.append(&block)?;
+        }
+        self.mempool.remove_confirmed(&block.transactions);
+        // 已上链的调用和支付费用的输出已被花费的调用都不能再打包
+        self.pending_calls.retain(|call| {
+            call.inputs
+                .iter()
+                .all(|outpoint| state.unspent().contains_key(outpoint))
+        });
+        self.verified = Some((block.hash.clone(), state));
+        self.chain.push(block);
+        Ok(true)
+    }
+
+    // 累计工作量：各区块按共识规则的权重之和，工作量证明中是难度的期望尝试次数
+    pub fn cumulative_work(&self) -> u128 {
+        self.chain
+            .iter()
+            .map(|block| self.consensus.weight(block))
+            .fold(0u128, u128::saturating_add)
+    }
+
+    // 最长链规则：候选链与本链创世区块相同、完整有效且累计工作量更大时替换本链，
+    // 被丢弃区块中的交易放回交易池。发生替换时返回 true。
+    pub fn replace_chain(&mut self, candidate: Vec<Block>) -> io::Result<bool> {
+        let Some(mut candidate) = self.candidate(candidate) else {
+            return Ok(false);
+        };
+        if candidate.verify().is_err() {
+            return Ok(false);
+        }
+        self.adopt(candidate)
+    }
+
+    // 按本链的规则包装候选链，创世区块不同或累计工作量不更大时返回 None。
+    // 只做这些便宜的检查，完整校验(verify)可以在别处进行，之后交给 adopt
+    pub fn candidate(&self, blocks: Vec<Block>) -> Option<Blockchain> {
+        if blocks.first().map(|block| &block.hash) != Some(&self.chain[0].hash) {
+            return None;
+        }
+        let candidate = self.with_same_rules(blocks);
+        (candidate.cumulative_work() > self.cumulative_work()).then_some(candidate)
+    }
+
+    // 换成已经完整校验过的候选链。校验期间本链可能又长了，所以重新比较累计工作量
+    pub fn adopt(&mut self, candidate: Blockchain) -> io::Result<bool> {
+        if candidate.chain[0].hash != self.chain[0].hash
+            || candidate.verified_state().is_none()
+            || candidate.cumulative_work() <= self.cumulative_work()
+        {
+            return Ok(false);
+        }
+
+        if let Some(store) = self.store.as_mut() {
+            store.replace_all(&candidate.chain)?;
+        }
+        let fork_point = self
+            .chain
+            .iter()
+            .zip(&candidate.chain)
+            .take_while(|(ours, theirs)| ours.hash == theirs.hash)
+            .count();
+        let old_chain = std::mem::replace(&mut self.chain, candidate.chain);
+        self.verified = candidate.verified;
+
+        let mut pending: Vec<Transaction> = old_chain[fork_point..]
+            .iter()
+            .flat_map(|block| block.transactions.iter().cloned())
+            .collect();
+        pending.extend(std::mem::take(&mut self.mempool).transactions().cloned());
+        self.resubmit(pending);
+
+        let calls: Vec<Call> = old_chain[fork_point..]
+            .iter()
+            .flat_map(|block| block.calls.iter().cloned())
+            .chain(std::mem::take(&mut self.pending_calls))
+            .collect();
+        for call in calls {
+            let _ = self.submit_call(call);
+        }
+        Ok(true)
+    }
+
+    // 重新提交一批交易，父交易可能排在子交易后面，所以反复尝试直到没有进展
+    fn resubmit(&mut self, mut pending: Vec<Transaction>) {
+        loop {
+            let before = pending.len();
+            pending.retain(|tx| {
+                !matches!(
+                    self.submit_transaction(tx.clone()),
+                    Ok(()) | Err(MempoolError::Duplicate(_))
+                )
+            });
+            if pending.is_empty() || pending.len() == before {
+                break;
+            }
+        }
+    }
+
+    // 按高度或哈希查找区块
+    pub fn find_block(&self, id: &str) -> Option<&Block> {
+        match id.parse::<usize>() {
+            Ok(index) => self.chain.get(index),
+            Err(_) => self.chain.iter().find(|block| block.hash == id),
+        }
+    }
+
+    // 从 from 高度开始的区块头，最多 limit 个，供轻节点同步
+    pub fn headers(&self, from: u64, limit: usize) -> Vec<BlockHeader> {
+        self.chain
+            .iter()
+            .skip(usize::try_from(from).unwrap_or(usize::MAX))
+            .take(limit)
+            .map(|block| block.header.clone())
+            .collect()
+    }
+
+    // 交易所在的区块高度和它的包含证明
+    pub fn transaction_proof(&self, txid: &str) -> Option<TransactionProof> {
+        self.chain.iter().find_map(|block| {
+            let transaction = block.transactions.iter().find(|tx| tx.id == txid)?;
+            Some(TransactionProof {
+                index: block.header.index,
+                transaction: transaction.clone(),
+                proof: block.transaction_proof(txid)?,
+            })
+        })
+    }
+
+    pub fn find_transaction(&self, txid: &str) -> Option<&Transaction> {
+        self.chain
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .find(|tx| tx.id == txid)
+    }
+
+    // 链上所有未花费的输出
+    pub fn utxo_set(&self) -> HashMap<OutPoint, TxOutput> {
+        if let Some(state) = self.verified_state() {
+            return state.unspent().clone();
+        }
+        let mut unspent = HashMap::new();
+        for tx in self.chain.iter().flat_map(|block| &block.transactions) {
+            for input in &tx.inputs {
+                unspent.remove(&input.outpoint());
+            }
+            add_outputs(&mut unspent, tx);
+        }
+        unspent
+    }
+
+    pub fn balance(&self, address: &str) -> u64 {
+        if let Some(state) = self.verified_state() {
+            return state.balance(address);
+        }
+        self.utxo_set()
+            .values()
+            .filter(|output| output.address == address)
+            .map(|output| output.amount)
+            .sum()
     }
 
     pub fn is_valid(&self) -> bool {
-        for i in 1..self.chain.len() {
-            let current = &self.chain[i];
+        self.validate().is_ok()
+    }
+
+    // 按区块顺序重放整条链，检查所有规则，返回第一个不满足的区块和规则
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.replay().map(|_| ())
+    }
+
+    // 完整校验整条链，通过后记下链尾的世界状态，之后的区块只需增量校验
+    pub fn verify(&mut self) -> Result<(), ValidationError> {
+        let state = self.replay()?;
+        self.verified = Some((self.tip().hash.clone(), state));
+        Ok(())
+    }
+
+    // 完整校验并返回链尾的世界状态
+    fn replay(&self) -> Result<WorldState, ValidationError> {
+        let genesis = self.chain.first().ok_or(ValidationError::EmptyChain)?;
+        if genesis.header.index != 0
+            || genesis.header.previous_hash != "0"
+            || genesis.header.difficulty > MAX_DIFFICULTY
+        {
+            return Err(ValidationError::Genesis);
+        }
+
+        let mut state = WorldState::default();
+        for i in 0..self.chain.len() {
+            self.check_block(i, &mut state)?;
+        }
+        Ok(state)
+    }
+
+    // 检查第 i 个区块能接在前面的区块之后，state 是第 i - 1 个区块之后的世界状态，
+    // 检查通过时变为第 i 个区块之后的状态
+    fn check_block(&self, i: usize, state: &mut WorldState) -> Result<(), ValidationError> {
+        let current = &self.chain[i];
+        if current.hash != current.calculate_hash() {
+            return Err(ValidationError::HashMismatch { index: i });
+        }
+        if current.header.merkle_root != current.calculate_merkle_root() {
+            return Err(ValidationError::MerkleRootMismatch { index: i });
+        }
+        let time = current
+            .header
+            .time()
+            .ok_or(ValidationError::BadTimestamp { index: i })?;
+
+        if i > 0 {
             let previous = &self.chain[i - 1];
+            if current.header.index != i as u64 {
+                return Err(ValidationError::IndexMismatch {
+                    index: i,
+                    found: current.header.index,
+                });
+            }
+            if current.header.previous_hash != previous.hash {
+                return Err(ValidationError::PreviousHashMismatch { index: i });
+            }
+            // 上一个区块的时间戳已经检查过
+            if previous
+                .header
+                .time()
+                .is_some_and(|previous| time < previous)
+            {
+                return Err(ValidationError::TimestampNotIncreasing { index: i });
+            }
+            self.consensus.verify(self, i)?;
+        }
 
-            if current.hash != current.calculate_hash() {
-                return false;
+        // 只有第一笔交易可以是铸币交易，记录的高度正确，金额不超过奖励加上手续费
+        let reward = BLOCK_REWARD.saturating_add(total_fees(&current.transactions));
+        for (n, tx) in current.transactions.iter().enumerate() {
+            if tx.is_coinbase()
+                && (n > 0
+                    || tx.inputs[0].output_index != i as u32
+                    || tx.output_total().is_none_or(|total| total > reward))
+            {
+                return Err(ValidationError::BadCoinbase { index: i });
             }
+        }
+        // 按顺序重放交易，检查结构和签名、输入存在且属于签名者、金额足够
+        for tx in &current.transactions {
+            state
+                .apply_transaction(tx)
+                .map_err(|error| ValidationError::InvalidTransaction {
+                    index: i,
+                    txid: tx.id.clone(),
+                    error,
+                })?;
+        }
+        // 合约调用必须由所有者签名并能支付 gas 费用，执行失败不影响区块有效性，
+        // gas 上限之和不能超过区块上限
+        let gas = current
+            .calls
+            .iter()
+            .fold(0u64, |sum, call| sum.saturating_add(call.gas_limit));
+        if gas > BLOCK_GAS_LIMIT {
+            return Err(ValidationError::GasLimitExceeded { index: i });
+        }
+        for call in &current.calls {
+            state
+                .apply_call(call)
+                .map_err(|error| ValidationError::InvalidCall {
+                    index: i,
+                    id: call.id(),
+                    error,
+                })?;
+        }
+        if current.header.state_root != state.root() {
+            return Err(ValidationError::StateRootMismatch { index: i });
+        }
+        Ok(())
+    }
+}
 
-            if current.previous_hash != previous.hash {
-                return false;
+fn total_fees(transactions: &[Transaction]) -> u64 {
+    transactions
+        .iter()
+        .fold(0u64, |sum, tx| sum.saturating_add(tx.fee))
+}
+
+// 难度调整规则，header(i) 返回第 i 个区块头，只会访问 index 之前的区块。
+// 每 retarget_interval 个区块比较一次最近一个周期的实际出块时间和目标时间，
+// 出块太快难度加 1，太慢减 1，其余区块沿用上一个区块的难度。时间戳无法解析时返回 None。
+pub fn expected_difficulty<'a>(
+    index: usize,
+    initial: usize,
+    retarget_interval: u64,
+    target_block_time: Duration,
+    header: impl Fn(usize) -> &'a BlockHeader,
+) -> Option<usize> {
+    if index <= 1 {
+        return Some(initial);
+    }
+    let previous = header(index - 1);
+    let interval = retarget_interval.max(2) as usize;
+    // 第一个周期包含固定时间戳的创世区块，不参与调整
+    if !index.is_multiple_of(interval) || index <= interval {
+        return Some(previous.difficulty);
+    }
+
+    let first = header(index - interval).time()?;
+    let last = previous.time()?;
+    let actual = (last - first).num_milliseconds().max(0) as u128;
+    let expected = target_block_time.as_millis() * (interval as u128 - 1);
+
+    let current = previous.difficulty;
+    Some(if actual * RETARGET_FACTOR < expected {
+        (current + 1).min(MAX_DIFFICULTY)
+    } else if actual > expected * RETARGET_FACTOR {
+        current.saturating_sub(1).max(1)
+    } else {
+        current
+    })
+}
+
+// 区块链校验失败的原因，index 是出问题的区块高度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    EmptyChain,
+    // 第一个区块的高度不是 0、previous_hash 不是 "0" 或者记录的初始难度超出上限
+    Genesis,
+    HashMismatch {
+        index: usize,
+    },
+    MerkleRootMismatch {
+        index: usize,
+    },
+    BadTimestamp {
+        index: usize,
+    },
+    IndexMismatch {
+        index: usize,
+        found: u64,
+    },
+    PreviousHashMismatch {
+        index: usize,
+    },
+    TimestampNotIncreasing {
+        index: usize,
+    },
+    // expected 为 None 表示之前的区块时间戳无法解析，无法算出应有难度
+    DifficultyMismatch {
+        index: usize,
+        expected: Option<usize>,
+        found: usize,
+    },
+    InsufficientWork {
+        index: usize,
+    },
+    // 权益证明中区块不是由这个高度被选中的验证者提议的
+    WrongProposer {
+        index: usize,
+        expected: String,
+    },
+    BadBlockSignature {
+        index: usize,
+    },
+    // 铸币交易不是第一笔、高度不符或者金额超过奖励加手续费
+    BadCoinbase {
+        index: usize,
+    },
+    InvalidTransaction {
+        index: usize,
+        txid: String,
+        error: MempoolError,
+    },
+    InvalidCall {
+        index: usize,
+        id: String,
+        error: CallError,
+    },
+    GasLimitExceeded {
+        index: usize,
+    },
+    StateRootMismatch {
+        index: usize,
+    },
+}
+
+impl ValidationError {
+    // 出问题的区块高度，空链时为 None
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            ValidationError::EmptyChain => None,
+            ValidationError::Genesis => Some(0),
+            ValidationError::HashMismatch { index }
+            | ValidationError::MerkleRootMismatch { index }
+            | ValidationError::BadTimestamp { index }
+            | ValidationError::IndexMismatch { index, .. }
+            | ValidationError::PreviousHashMismatch { index }
+            | ValidationError::TimestampNotIncreasing { index }
+            | ValidationError::DifficultyMismatch { index, .. }
+            | ValidationError::InsufficientWork { index }
+            | ValidationError::WrongProposer { index, .. }
+            | ValidationError::BadBlockSignature { index }
+            | ValidationError::BadCoinbase { index }
+            | ValidationError::InvalidTransaction { index, .. }
+            | ValidationError::InvalidCall { index, .. }
+            | ValidationError::GasLimitExceeded { index }
+            | ValidationError::StateRootMismatch { index } => Some(*index),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(index) = self.index() {
+            write!(f, "区块 #{}: ", index)?;
+        }
+        match self {
+            ValidationError::EmptyChain => write!(f, "区块链为空"),
+            ValidationError::Genesis => write!(f, "创世区块的高度或前一个哈希不正确"),
+            ValidationError::HashMismatch { .. } => write!(f, "区块哈希与区块头不符"),
+            ValidationError::MerkleRootMismatch { .. } => write!(f, "Merkle 根与区块内容不符"),
+            ValidationError::BadTimestamp { .. } => write!(f, "时间戳无法解析"),
+            ValidationError::IndexMismatch { found, .. } => {
+                write!(f, "区块头中的高度是 {}", found)
+            }
+            ValidationError::PreviousHashMismatch { .. } => {
+                write!(f, "前一个哈希与上一个区块不符")
+            }
+            ValidationError::TimestampNotIncreasing { .. } => {
+                write!(f, "时间戳早于上一个区块")
+            }
+            ValidationError::DifficultyMismatch {
+                expected: Some(expected),
+                found,
+                ..
+            } => write!(f, "难度应为 {}，区块声明 {}", expected, found),
+            ValidationError::DifficultyMismatch {
+                expected: None,
+                found,
+                ..
+            } => write!(f, "无法确定应有难度，区块声明 {}", found),
+            ValidationError::InsufficientWork { .. } => write!(f, "哈希不满足声明的难度"),
+            ValidationError::WrongProposer { expected, .. } => {
+                write!(f, "这个高度应由验证者 {} 提议", expected)
             }
+            ValidationError::BadBlockSignature { .. } => write!(f, "提议者的区块签名无效"),
+            ValidationError::BadCoinbase { .. } => {
+                write!(f, "铸币交易无效或金额超过奖励 {} 加手续费", BLOCK_REWARD)
+            }
+            ValidationError::InvalidTransaction { txid, error, .. } => {
+                write!(f, "交易 {} 无效: {}", txid, error)
+            }
+            ValidationError::InvalidCall { id, error, .. } => {
+                write!(f, "合约调用 {} 无效: {}", id, error)
+            }
+            ValidationError::GasLimitExceeded { .. } => {
+                write!(f, "合约调用的 gas 上限之和超过 {}", BLOCK_GAS_LIMIT)
+            }
+            ValidationError::StateRootMismatch { .. } => write!(f, "状态根与执行结果不符"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::meets_difficulty;
+    use crate::transaction::{TransactionError, TxInput};
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn chain_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let mut blockchain = Blockchain::open(&path, 1).unwrap();
+        blockchain.add_block("a".to_string()).unwrap();
+        blockchain.add_block("b".to_string()).unwrap();
+        let tip = blockchain.chain.last().unwrap().hash.clone();
+        drop(blockchain);
+
+        // 已有的链按创世区块中记录的难度校验，与打开时传入的难度无关
+        let mut blockchain = Blockchain::open(&path, 4).unwrap();
+        assert_eq!(blockchain.chain.len(), 3);
+        assert_eq!(blockchain.chain.last().unwrap().hash, tip);
+        assert!(blockchain.is_valid());
+        assert_eq!(blockchain.next_difficulty(), 1);
+        blockchain.add_block("c".to_string()).unwrap();
+        assert!(Blockchain::from_blocks(blockchain.chain.clone()).is_valid());
+
+        // 初始难度写在创世区块里，不同难度的链创世区块不同
+        assert_ne!(
+            Blockchain::new(1).chain[0].hash,
+            Blockchain::new(4).chain[0].hash
+        );
+    }
+
+    #[test]
+    fn export_and_import_with_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.bin");
+        let alice = Wallet::generate();
+
+        let mut blockchain = Blockchain::new(1);
+        fund(&mut blockchain, &alice);
+        blockchain.add_block("b".to_string()).unwrap();
+        blockchain.export(&path).unwrap();
+
+        let imported = Blockchain::import(&path).unwrap();
+        assert_eq!(imported.tip().hash, blockchain.tip().hash);
+        assert_eq!(imported.balance(&alice.address()), BLOCK_REWARD);
+
+        // 导出后被篡改的链拒绝导入
+        blockchain.chain[1].data = "forged".to_string();
+        blockchain.export(&path).unwrap();
+        let error = Blockchain::import(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    // 挖出一个把出块奖励发给 to 的区块，返回其中的铸币交易
+    fn fund(blockchain: &mut Blockchain, to: &Wallet) -> Transaction {
+        blockchain.reward_address = Some(to.address());
+        blockchain.add_block("reward".to_string()).unwrap();
+        blockchain.reward_address = None;
+        blockchain.tip().transactions[0].clone()
+    }
+
+    fn pay(from: &Wallet, prev: &Transaction, to: &Wallet, amount: u64, fee: u64) -> Transaction {
+        let mut tx = Transaction::new(
+            vec![TxInput::new(prev.id.clone(), 0)],
+            vec![TxOutput {
+                address: to.address(),
+                amount,
+            }],
+            fee,
+        );
+        from.sign(&mut tx);
+        tx
+    }
+
+    #[test]
+    fn block_includes_pending_transactions() {
+        let (alice, bob, miner) = (Wallet::generate(), Wallet::generate(), Wallet::generate());
+        let mut blockchain = Blockchain::new(1);
+        let mint = fund(&mut blockchain, &alice);
+        assert_eq!(blockchain.balance(&alice.address()), BLOCK_REWARD);
+        let payment = pay(&alice, &mint, &bob, 40, 10);
+        blockchain.submit_transaction(payment.clone()).unwrap();
+        // 可以花费还在交易池中的输出
+        let refund = pay(&bob, &payment, &alice, 35, 5);
+        blockchain.submit_transaction(refund.clone()).unwrap();
+
+        blockchain.reward_address = Some(miner.address());
+        blockchain.add_block("payments".to_string()).unwrap();
+        assert!(blockchain.mempool.is_empty());
+        assert_eq!(blockchain.chain[2].transactions[1..], [payment, refund]);
+        // 出块者领取奖励和两笔交易的手续费
+        assert_eq!(blockchain.balance(&miner.address()), BLOCK_REWARD + 15);
+        assert_eq!(blockchain.balance(&bob.address()), 0);
+        assert_eq!(blockchain.balance(&alice.address()), 35);
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn rejects_overspend_and_double_spend() {
+        let (alice, bob, carol) = (Wallet::generate(), Wallet::generate(), Wallet::generate());
+        let mut blockchain = Blockchain::new(1);
+        let mint = fund(&mut blockchain, &alice);
+
+        assert!(matches!(
+            blockchain.submit_transaction(pay(&alice, &mint, &bob, BLOCK_REWARD, 1)),
+            Err(MempoolError::InsufficientFunds { .. })
+        ));
+
+        blockchain
+            .submit_transaction(pay(&alice, &mint, &bob, 30, 1))
+            .unwrap();
+        blockchain.add_block("spend".to_string()).unwrap();
+        assert!(matches!(
+            blockchain.submit_transaction(pay(&alice, &mint, &carol, 10, 1)),
+            Err(MempoolError::MissingInput(_))
+        ));
+
+        // 篡改链上交易金额后整条链无效
+        blockchain.chain[2].transactions[0].outputs[0].amount = 1000;
+        assert!(!blockchain.is_valid());
+    }
+
+    #[test]
+    fn rejects_spending_someone_elses_output() {
+        let (alice, mallory) = (Wallet::generate(), Wallet::generate());
+        let mut blockchain = Blockchain::new(1);
+        let mint = fund(&mut blockchain, &alice);
+
+        let theft = pay(&mallory, &mint, &mallory, BLOCK_REWARD, 0);
+        assert!(matches!(
+            blockchain.submit_transaction(theft.clone()),
+            Err(MempoolError::NotOwner(_))
+        ));
+
+        // 直接塞进区块也无法通过校验
+        let mut block = Block::new(
+            2,
+            "theft".to_string(),
+            vec![theft],
+            blockchain.chain[1].hash.clone(),
+        );
+        block.mine_block(blockchain.next_difficulty());
+        blockchain.chain.push(block);
+        assert!(!blockchain.is_valid());
+    }
+
+    #[test]
+    fn coinbase_is_capped_and_only_allowed_first() {
+        let (alice, mallory) = (Wallet::generate(), Wallet::generate());
+        let mut blockchain = Blockchain::new(1);
+        let mint = fund(&mut blockchain, &alice);
+        blockchain
+            .submit_transaction(pay(&alice, &mint, &alice, 40, 10))
+            .unwrap();
+
+        // 铸币交易和没有输入的交易不能提交到交易池
+        let coinbase = Transaction::coinbase(2, mallory.address(), 1);
+        assert_eq!(
+            blockchain.submit_transaction(coinbase.clone()),
+            Err(MempoolError::Coinbase)
+        );
+        let unsigned = Transaction::new(Vec::new(), coinbase.outputs.clone(), 0);
+        assert_eq!(
+            blockchain.submit_transaction(unsigned),
+            Err(MempoolError::Invalid(TransactionError::NoInputs))
+        );
+
+        // 按 edit 改动下一个区块的交易后重新封装，返回接上它之后整条链的校验结果
+        blockchain.reward_address = Some(mallory.address());
+        let mut forge = |edit: &dyn Fn(&mut Vec<Transaction>)| {
+            let mut block = blockchain.prepare_block("forged".to_string());
+            edit(&mut block.transactions);
+            block.header.merkle_root = block.calculate_merkle_root();
+            block.mine_block(block.header.difficulty);
+            let mut chain = blockchain.chain.clone();
+            chain.push(block);
+            Blockchain::from_blocks(chain).validate()
+        };
+        let bad = Err(ValidationError::BadCoinbase { index: 2 });
+        // 奖励加手续费之外多领 1
+        let greedy = Transaction::coinbase(2, mallory.address(), BLOCK_REWARD + 11);
+        assert_eq!(forge(&|txs| txs[0] = greedy.clone()), bad);
+        // 重放第 1 个区块的铸币交易，高度不符
+        assert_eq!(forge(&|txs| txs[0] = mint.clone()), bad);
+        // 第二笔铸币交易
+        assert_eq!(forge(&|txs| txs.push(coinbase.clone())), bad);
+
+        blockchain.add_block("fees".to_string()).unwrap();
+        assert_eq!(blockchain.balance(&mallory.address()), BLOCK_REWARD + 10);
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn rejects_block_with_bad_signature() {
+        let (alice, bob) = (Wallet::generate(), Wallet::generate());
+        let mut blockchain = Blockchain::new(1);
+        let mint = fund(&mut blockchain, &alice);
+        blockchain
+            .submit_transaction(pay(&alice, &mint, &bob, BLOCK_REWARD, 0))
+            .unwrap();
+        blockchain.add_block("payments".to_string()).unwrap();
+        assert!(blockchain.is_valid());
+
+        // 换成别人的签名并重新计算 Merkle 根、重新挖矿，只剩签名校验能发现问题
+        let block = &mut blockchain.chain[2];
+        let forged = bob.sign_message(&block.transactions[0].signing_hash());
+        block.transactions[0].inputs[0].signature = forged;
+        block.header.merkle_root = block.calculate_merkle_root();
+        block.hash = block.calculate_hash();
+        block.mine_block(block.header.difficulty);
+        let txid = block.transactions[0].id.clone();
+        assert_eq!(
+            blockchain.validate(),
+            Err(ValidationError::InvalidTransaction {
+                index: 2,
+                txid,
+                error: MempoolError::Invalid(TransactionError::BadSignature(0)),
+            })
+        );
+    }
+
+    #[test]
+    fn heavier_fork_replaces_chain_and_restores_orphaned_transactions() {
+        let (alice, bob) = (Wallet::generate(), Wallet::generate());
+        let mut ours = Blockchain::new(1);
+        let mut theirs = Blockchain::new(1);
+        assert_eq!(ours.chain[0].hash, theirs.chain[0].hash);
+
+        // 两条链共享发放奖励的第 1 个区块，之后分叉
+        let mint = fund(&mut ours, &alice);
+        assert!(theirs.append_block(ours.tip().clone()).unwrap());
+        let payment = pay(&alice, &mint, &bob, BLOCK_REWARD, 0);
+        ours.submit_transaction(payment.clone()).unwrap();
+        ours.add_block("ours".to_string()).unwrap();
+        theirs.add_block("theirs 2".to_string()).unwrap();
+
+        // 工作量相同不切换
+        assert!(!ours.replace_chain(theirs.chain.clone()).unwrap());
+
+        theirs.add_block("theirs 3".to_string()).unwrap();
+        let mut forged = theirs.chain.clone();
+        forged[3].data = "forged".to_string();
+        assert!(!ours.replace_chain(forged).unwrap());
+
+        // 校验候选链期间本链追上了同样的工作量，不再切换
+        let mut candidate = ours.candidate(theirs.chain.clone()).unwrap();
+        candidate.verify().unwrap();
+        ours.add_block("ours 3".to_string()).unwrap();
+        assert!(!ours.adopt(candidate).unwrap());
+
+        theirs.add_block("theirs 4".to_string()).unwrap();
+        assert!(ours.replace_chain(theirs.chain.clone()).unwrap());
+        assert_eq!(ours.tip().hash, theirs.tip().hash);
+        // 被丢弃区块里的交易回到交易池
+        assert!(ours.mempool.contains(&payment.id));
+    }
+
+    #[test]
+    fn append_block_rejects_blocks_without_work() {
+        let mut ours = Blockchain::new(2);
+        let mut block = ours.prepare_block("lazy".to_string());
+        block.header.difficulty = 2;
+        block.hash = block.calculate_hash();
+        while meets_difficulty(&block.hash, 2) {
+            block.header.nonce += 1;
+            block.hash = block.calculate_hash();
+        }
+        assert!(!ours.append_block(block.clone()).unwrap());
+
+        block.mine_block(2);
+        assert!(ours.append_block(block).unwrap());
+        assert_eq!(ours.chain.len(), 2);
+    }
+
+    #[test]
+    fn stale_pool_transactions_are_dropped_when_mining() {
+        let (alice, bob, miner) = (Wallet::generate(), Wallet::generate(), Wallet::generate());
+        let mut blockchain = Blockchain::new(1);
+        let mint = fund(&mut blockchain, &alice);
+        let payment = pay(&alice, &mint, &bob, 40, 10);
+        blockchain.submit_transaction(payment.clone()).unwrap();
+        // 花费从未上链的输出，例如被别处的区块抢先花掉后留下的交易，以及它的后代
+        let phantom = Transaction::coinbase(9, alice.address(), BLOCK_REWARD);
+        let stale = pay(&alice, &phantom, &bob, 40, 7);
+        let orphan = pay(&bob, &stale, &alice, 30, 3);
+        blockchain.mempool.add(stale.clone()).unwrap();
+        blockchain.mempool.add(orphan.clone()).unwrap();
+
+        blockchain.reward_address = Some(miner.address());
+        blockchain.add_block("mined".to_string()).unwrap();
+        assert_eq!(blockchain.tip().transactions[1..], [payment]);
+        // 手续费只算实际打包的交易
+        assert_eq!(blockchain.balance(&miner.address()), BLOCK_REWARD + 10);
+        assert!(blockchain.mempool.is_empty());
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn append_block_validates_against_tip_state() {
+        let alice = Wallet::generate();
+        let mut blockchain = Blockchain::new(1);
+        fund(&mut blockchain, &alice);
+        blockchain.add_block("b".to_string()).unwrap();
+        let replayed = WorldState::replay(&blockchain.chain).unwrap();
+        assert_eq!(
+            blockchain.verified_state().map(WorldState::root),
+            Some(replayed.root())
+        );
+
+        // 没有校验过的链先完整校验一次，之后同样增量校验
+        let mut copy = Blockchain::from_blocks(blockchain.chain.clone());
+        assert!(copy.verified_state().is_none());
+        let block = blockchain.prepare_block("c".to_string());
+        let mut forged = block.clone();
+        forged.header.state_root = WorldState::default().root();
+        forged.mine_block(forged.header.difficulty);
+        assert!(!copy.append_block(forged).unwrap());
+        let mut block = block;
+        block.mine_block(block.header.difficulty);
+        assert!(copy.append_block(block).unwrap());
+        assert_eq!(copy.balance(&alice.address()), BLOCK_REWARD);
+        assert_eq!(
+            copy.verified_state().map(WorldState::root),
+            Some(copy.tip().header.state_root.clone())
+        );
+
+        // 直接改动链尾后缓存的状态作废，回到重放
+        copy.chain.pop();
+        assert!(copy.verified_state().is_none());
+        assert_eq!(copy.state().unwrap().root(), replayed.root());
+    }
+
+    // 按指定时间间隔在链尾挖出 count 个区块
+    fn mine_spaced(blockchain: &mut Blockchain, count: usize, spacing_secs: i64) {
+        for _ in 0..count {
+            let previous = blockchain.tip().header.time().unwrap();
+            let mut block = blockchain.prepare_block("spaced".to_string());
+            block.header.timestamp =
+                (previous + chrono::Duration::seconds(spacing_secs)).to_rfc3339();
+            block.mine_block(blockchain.next_difficulty());
+            assert!(blockchain.append_block(block).unwrap());
         }
-        true
+    }
+
+    #[test]
+    fn difficulty_retargets_toward_target_block_time() {
+        let mut blockchain = Blockchain::new(1);
+        blockchain.retarget_interval = 3;
+        blockchain.target_block_time = Duration::from_secs(60);
+
+        // 出块远快于目标，第 6 个区块难度加 1
+        mine_spaced(&mut blockchain, 5, 1);
+        assert_eq!(blockchain.tip().header.difficulty, 1);
+        mine_spaced(&mut blockchain, 1, 1);
+        assert_eq!(blockchain.tip().header.difficulty, 2);
+
+        // 出块远慢于目标，第 9 个区块难度减 1
+        mine_spaced(&mut blockchain, 2, 1000);
+        mine_spaced(&mut blockchain, 1, 1000);
+        assert_eq!(blockchain.tip().header.difficulty, 1);
+        assert!(blockchain.is_valid());
+    }
+
+    #[test]
+    fn rejects_forged_block_without_proof_of_work() {
+        let mut blockchain = Blockchain::new(3);
+        blockchain.add_block("honest".to_string()).unwrap();
+
+        // 声明了正确难度、哈希自洽但没有做工作量证明的区块
+        let mut forged = blockchain.prepare_block("forged".to_string());
+        forged.header.difficulty = 3;
+        forged.hash = forged.calculate_hash();
+        while meets_difficulty(&forged.hash, 3) {
+            forged.header.nonce += 1;
+            forged.hash = forged.calculate_hash();
+        }
+        blockchain.chain.push(forged);
+        assert_eq!(
+            blockchain.validate(),
+            Err(ValidationError::InsufficientWork { index: 2 })
+        );
+        blockchain.chain.pop();
+
+        // 声明低于规则要求的难度
+        let mut cheap = blockchain.prepare_block("cheap".to_string());
+        cheap.mine_block(1);
+        blockchain.chain.push(cheap);
+        assert_eq!(
+            blockchain.validate(),
+            Err(ValidationError::DifficultyMismatch {
+                index: 2,
+                expected: Some(3),
+                found: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn validation_reports_offending_block_and_rule() {
+        let mut blockchain = Blockchain::new(1);
+        for data in ["a", "b", "c"] {
+            blockchain.add_block(data.to_string()).unwrap();
+        }
+        assert_eq!(blockchain.validate(), Ok(()));
+
+        // 在链的副本上篡改，返回校验结果
+        let tamper = |edit: &dyn Fn(&mut Vec<Block>)| {
+            let mut chain = blockchain.chain.clone();
+            edit(&mut chain);
+            Blockchain::from_blocks(chain).validate()
+        };
+        // 篡改区块头后重新挖矿，只留下要测试的那条规则不满足
+        let remine = |block: &mut Block| {
+            block.header.nonce = 0;
+            block.mine_block(block.header.difficulty);
+        };
+
+        assert_eq!(
+            tamper(&|chain| chain.clear()),
+            Err(ValidationError::EmptyChain)
+        );
+        assert_eq!(
+            tamper(&|chain| chain[0].header.previous_hash = "1".to_string()),
+            Err(ValidationError::Genesis)
+        );
+        assert_eq!(
+            tamper(&|chain| chain[2].hash = chain[1].hash.clone()),
+            Err(ValidationError::HashMismatch { index: 2 })
+        );
+        assert_eq!(
+            tamper(&|chain| chain[2].data = "forged".to_string()),
+            Err(ValidationError::MerkleRootMismatch { index: 2 })
+        );
+        assert_eq!(
+            tamper(&|chain| {
+                chain[2].header.index = 7;
+                remine(&mut chain[2]);
+            }),
+            Err(ValidationError::IndexMismatch { index: 2, found: 7 })
+        );
+        assert_eq!(
+            tamper(&|chain| {
+                chain[2].header.previous_hash = chain[0].hash.clone();
+                remine(&mut chain[2]);
+            }),
+            Err(ValidationError::PreviousHashMismatch { index: 2 })
+        );
+        assert_eq!(
+            tamper(&|chain| {
+                chain[2].header.timestamp = GENESIS_TIMESTAMP.to_string();
+                remine(&mut chain[2]);
+            }),
+            Err(ValidationError::TimestampNotIncreasing { index: 2 })
+        );
+        assert_eq!(
+            tamper(&|chain| {
+                chain[3].header.timestamp = "yesterday".to_string();
+                remine(&mut chain[3]);
+            }),
+            Err(ValidationError::BadTimestamp { index: 3 })
+        );
+    }
+
+    #[test]
+    fn calls_update_state_root_and_are_validated() {
+        let mut blockchain = Blockchain::new(1);
+        let (alice, mallory) = (Wallet::generate(), Wallet::generate());
+        blockchain.reward_address = Some(alice.address());
+        blockchain.add_block("alice".to_string()).unwrap();
+        blockchain.add_block("alice".to_string()).unwrap();
+        // 每次调用用一个区块奖励支付 gas，INCREMENT 消耗 30
+        let increment = |wallet: &Wallet, block: &Block| {
+            let input = OutPoint {
+                txid: block.transactions[0].id.clone(),
+                index: 0,
+            };
+            let mut call = Call::new(
+                "counter".to_string(),
+                "PUSH 0 SLOAD PUSH 1 ADD PUSH 0 SWAP SSTORE".to_string(),
+                50,
+                1,
+                vec![input],
+            );
+            wallet.sign_call(&mut call);
+            call
+        };
+        let (first, second) = (
+            increment(&alice, &blockchain.chain[1]),
+            increment(&alice, &blockchain.chain[2]),
+        );
+        blockchain.submit_call(first.clone()).unwrap();
+        blockchain.submit_call(second.clone()).unwrap();
+        assert_eq!(
+            blockchain.submit_call(second.clone()),
+            Err(CallError::Duplicate(second.id()))
+        );
+        assert!(matches!(
+            blockchain.submit_call(Call {
+                code: "FLY".to_string(),
+                ..second.clone()
+            }),
+            Err(CallError::Invalid(_))
+        ));
+        blockchain.reward_address = Some(mallory.address());
+        blockchain.add_block("calls".to_string()).unwrap();
+        assert!(blockchain.pending_calls.is_empty());
+        assert_eq!(blockchain.validate(), Ok(()));
+
+        let state = blockchain.state().unwrap();
+        assert_eq!(state.storage["counter"].get(&0), Some(&2));
+        assert_eq!(state.balance(&alice.address()), 2 * BLOCK_REWARD - 2 * 30);
+        assert_eq!(blockchain.tip().header.state_root, state.root());
+        // 已上链的调用不能再次提交，合约只接受所有者的调用
+        assert!(blockchain.submit_call(first).is_err());
+        assert_eq!(
+            blockchain.submit_call(increment(&mallory, blockchain.tip())),
+            Err(CallError::NotOwner(alice.address()))
+        );
+
+        // 状态根与执行结果不符
+        let mut forged = blockchain.chain.clone();
+        forged[3].header.state_root = WorldState::default().root();
+        let difficulty = forged[3].header.difficulty;
+        forged[3].mine_block(difficulty);
+        assert_eq!(
+            Blockchain::from_blocks(forged).validate(),
+            Err(ValidationError::StateRootMismatch { index: 3 })
+        );
     }
 }
@@ -0,0 +1,309 @@
+// src/consensus.rs
+// 可替换的共识规则。Blockchain 只负责链接、交易和存储，区块如何封装、
+// 哪些区块头合法、分叉时每个区块的权重由 Consensus 决定。
+//   ProofOfWork:  原有的工作量证明，难度按出块时间调整
+//   ProofOfStake: 按权益加权抽取每个高度的提议者，提议者对区块哈希签名
+use crate::block::{block_work, meets_difficulty, Block};
+use crate::blockchain::{Blockchain, ValidationError};
+use crate::miner::{CancelToken, Miner};
+use crate::wallet::{self, Wallet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+pub trait Consensus: fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // 填写候选区块中与共识相关的区块头字段，之后区块内容不再改变
+    fn prepare(&self, chain: &Blockchain, block: &mut Block);
+
+    // 封装区块并计算最终哈希，cancel 被触发而放弃时返回 Ok(false)
+    fn seal(&self, block: &mut Block, cancel: &CancelToken) -> Result<bool, ConsensusError>;
+
+    // 检查第 index 个区块(index > 0)满足共识规则，只依赖它和之前的区块
+    fn verify(&self, chain: &Blockchain, index: usize) -> Result<(), ValidationError>;
+
+    // 分叉选择时区块的权重，累计权重大的链胜出
+    fn weight(&self, block: &Block) -> u128;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsensusError {
+    // 本节点没有配置签名密钥
+    NoSigner,
+    // 这个高度轮不到本节点出块
+    NotProposer { expected: String },
+}
+
+impl fmt::Display for ConsensusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsensusError::NoSigner => write!(f, "没有配置验证者密钥"),
+            ConsensusError::NotProposer { expected } => {
+                write!(f, "这个高度应由验证者 {} 出块", expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConsensusError {}
+
+// 工作量证明，难度规则见 Blockchain::expected_difficulty
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProofOfWork;
+
+impl Consensus for ProofOfWork {
+    fn name(&self) -> &'static str {
+        "pow"
+    }
+
+    fn prepare(&self, chain: &Blockchain, block: &mut Block) {
+        block.header.difficulty = chain.next_difficulty();
+    }
+
+    fn seal(&self, block: &mut Block, cancel: &CancelToken) -> Result<bool, ConsensusError> {
+        let miner = Miner::default().with_cancel_token(cancel.clone());
+        let Some(stats) = miner.mine(block, block.header.difficulty) else {
+            return Ok(false);
+        };
+        println!("Block mined: {} ({:.0} H/s)", block.hash, stats.hashrate());
+        Ok(true)
+    }
+
+    // 难度必须符合调整规则，哈希必须满足区块声明的难度
+    fn verify(&self, chain: &Blockchain, index: usize) -> Result<(), ValidationError> {
+        let block = &chain.chain[index];
+        let expected = chain.expected_difficulty(index);
+        if Some(block.header.difficulty) != expected {
+            return Err(ValidationError::DifficultyMismatch {
+                index,
+                expected,
+                found: block.header.difficulty,
+            });
+        }
+        if !meets_difficulty(&block.hash, block.header.difficulty) {
+            return Err(ValidationError::InsufficientWork { index });
+        }
+        Ok(())
+    }
+
+    fn weight(&self, block: &Block) -> u128 {
+        block_work(block.header.difficulty)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validator {
+    // 十六进制 Ed25519 公钥
+    pub public_key: String,
+    pub stake: u64,
+}
+
+// 权益证明：每个高度的提议者由上一个区块的哈希和高度决定，被选中的概率与权益成正比。
+// 区块难度固定为 0，每个区块权重为 1，分叉时区块多的链胜出。
+#[derive(Debug, Clone)]
+pub struct ProofOfStake {
+    // 按公钥排序、权益大于 0 的验证者，所有节点的抽取顺序一致
+    validators: Vec<Validator>,
+    signer: Option<Wallet>,
+}
+
+impl ProofOfStake {
+    pub fn new(mut validators: Vec<Validator>) -> Self {
+        validators.retain(|validator| validator.stake > 0);
+        validators.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+        validators.dedup_by(|a, b| a.public_key == b.public_key);
+        ProofOfStake {
+            validators,
+            signer: None,
+        }
+    }
+
+    // 本节点作为验证者出块时使用的密钥
+    pub fn with_signer(mut self, signer: Wallet) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    pub fn validators(&self) -> &[Validator] {
+        &self.validators
+    }
+
+    pub fn total_stake(&self) -> u128 {
+        self.validators.iter().map(|v| v.stake as u128).sum()
+    }
+
+    // 第 index 个区块的提议者，没有验证者时返回 None
+    pub fn proposer(&self, previous_hash: &str, index: u64) -> Option<&Validator> {
+        let total = self.total_stake();
+        if total == 0 {
+            return None;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update((previous_hash.len() as u64).to_le_bytes());
+        hasher.update(previous_hash.as_bytes());
+        hasher.update(index.to_le_bytes());
+        let seed: [u8; 32] = hasher.finalize().into();
+        let mut ticket = u128::from_le_bytes(seed[..16].try_into().unwrap()) % total;
+
+        self.validators.iter().find(|validator| {
+            if ticket < validator.stake as u128 {
+                return true;
+            }
+            ticket -= validator.stake as u128;
+            false
+        })
+    }
+}
+
+impl Consensus for ProofOfStake {
+    fn name(&self) -> &'static str {
+        "pos"
+    }
+
+    fn prepare(&self, chain: &Blockchain, block: &mut Block) {
+        block.header.difficulty = 0;
+        block.header.proposer = self
+            .proposer(&chain.tip().hash, block.header.index)
+            .map(|validator| validator.public_key.clone())
+            .unwrap_or_default();
+    }
+
+    fn seal(&self, block: &mut Block, _cancel: &CancelToken) -> Result<bool, ConsensusError> {
+        let signer = self.signer.as_ref().ok_or(ConsensusError::NoSigner)?;
+        if signer.public_key_hex() != block.header.proposer {
+            return Err(ConsensusError::NotProposer {
+                expected: block.header.proposer.clone(),
+            });
+        }
+        block.hash = block.calculate_hash();
+        block.signature = signer.sign_message(block.hash.as_bytes());
+        Ok(true)
+    }
+
+    fn verify(&self, chain: &Blockchain, index: usize) -> Result<(), ValidationError> {
+        let block = &chain.chain[index];
+        if block.header.difficulty != 0 {
+            return Err(ValidationError::DifficultyMismatch {
+                index,
+                expected: Some(0),
+                found: block.header.difficulty,
+            });
+        }
+        let previous = &chain.chain[index - 1];
+        let expected = self
+            .proposer(&previous.hash, block.header.index)
+            .map(|validator| validator.public_key.as_str())
+            .unwrap_or_default();
+        if block.header.proposer != expected {
+            return Err(ValidationError::WrongProposer {
+                index,
+                expected: expected.to_string(),
+            });
+        }
+        if !wallet::verify(
+            &block.header.proposer,
+            block.hash.as_bytes(),
+            &block.signature,
+        ) {
+            return Err(ValidationError::BadBlockSignature { index });
+        }
+        Ok(())
+    }
+
+    fn weight(&self, _block: &Block) -> u128 {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn validators(stakes: &[u64]) -> (Vec<Wallet>, ProofOfStake) {
+        let wallets: Vec<_> = stakes.iter().map(|_| Wallet::generate()).collect();
+        let set = wallets
+            .iter()
+            .zip(stakes)
+            .map(|(wallet, &stake)| Validator {
+                public_key: wallet.public_key_hex(),
+                stake,
+            })
+            .collect();
+        (wallets, ProofOfStake::new(set))
+    }
+
+    // 由被选中的验证者在链尾出一个区块
+    fn propose(chain: &mut Blockchain, pos: &ProofOfStake, wallets: &[Wallet], data: &str) {
+        let mut block = chain.prepare_block(data.to_string());
+        let signer = wallets
+            .iter()
+            .find(|wallet| wallet.public_key_hex() == block.header.proposer)
+            .unwrap();
+        let sealer = pos.clone().with_signer(signer.clone());
+        assert!(sealer.seal(&mut block, &CancelToken::new()).unwrap());
+        assert!(chain.append_block(block).unwrap());
+    }
+
+    #[test]
+    fn selection_is_weighted_by_stake() {
+        let (wallets, pos) = validators(&[1, 3]);
+        let heavy = wallets[1].public_key_hex();
+        let picks = (0..2000u64)
+            .filter(|&i| pos.proposer(&format!("{:x}", i), i).unwrap().public_key == heavy)
+            .count();
+        assert!((1300..1700).contains(&picks), "picks = {}", picks);
+    }
+
+    #[test]
+    fn stake_chain_accepts_only_the_selected_proposer() {
+        let (wallets, pos) = validators(&[5, 3, 2]);
+        let mut chain = Blockchain::new(0);
+        chain.consensus = Arc::new(pos.clone());
+        for i in 0..5 {
+            propose(&mut chain, &pos, &wallets, &format!("block {}", i));
+        }
+        assert_eq!(chain.validate(), Ok(()));
+        assert_eq!(chain.cumulative_work(), 6);
+
+        // 没轮到的验证者无法封装
+        let mut block = chain.prepare_block("usurp".to_string());
+        let other = wallets
+            .iter()
+            .find(|wallet| wallet.public_key_hex() != block.header.proposer)
+            .unwrap();
+        let sealer = pos.clone().with_signer(other.clone());
+        assert!(matches!(
+            sealer.seal(&mut block, &CancelToken::new()),
+            Err(ConsensusError::NotProposer { .. })
+        ));
+
+        // 冒充提议者：改写区块头并自己签名
+        let expected = block.header.proposer.clone();
+        block.header.proposer = other.public_key_hex();
+        block.hash = block.calculate_hash();
+        block.signature = other.sign_message(block.hash.as_bytes());
+        assert!(!chain.append_block(block.clone()).unwrap());
+        chain.chain.push(block.clone());
+        assert_eq!(
+            chain.validate(),
+            Err(ValidationError::WrongProposer { index: 6, expected })
+        );
+        chain.chain.pop();
+
+        // 提议者正确但签名不是提议者的
+        block.header.proposer = pos
+            .proposer(&chain.tip().hash, 6)
+            .unwrap()
+            .public_key
+            .clone();
+        block.hash = block.calculate_hash();
+        block.signature = other.sign_message(block.hash.as_bytes());
+        chain.chain.push(block);
+        assert_eq!(
+            chain.validate(),
+            Err(ValidationError::BadBlockSignature { index: 6 })
+        );
+    }
+}
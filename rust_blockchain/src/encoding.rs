@@ -0,0 +1,333 @@
+// src/encoding.rs
+// 区块的规范二进制编码：整数一律小端定长，字符串和列表带 u64 长度前缀，
+// 不同字段的内容不会拼接出同样的字节。区块头哈希和导出文件都使用这套编码。
+//
+// 导出文件格式:
+//   [魔数 "RBCH"][版本: u16 LE][区块数: u64 LE][区块...]
+use crate::block::{Block, BlockHeader};
+use crate::state::Call;
+use crate::transaction::{OutPoint, Transaction, TxInput, TxOutput};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"RBCH";
+// 编码或区块哈希的规则改变时加 1，旧版本导出的链在新规则下无法通过校验
+pub const FORMAT_VERSION: u16 = 1;
+
+pub fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+pub fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    put_u64(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+// 区块头除 nonce 以外的字段，nonce 的 8 个字节追加在最后
+pub fn header_prefix(header: &BlockHeader) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(64 + header.timestamp.len() + 2 * 64);
+    put_u64(&mut buf, header.index);
+    put_bytes(&mut buf, header.timestamp.as_bytes());
+    put_bytes(&mut buf, header.previous_hash.as_bytes());
+    put_bytes(&mut buf, header.merkle_root.as_bytes());
+    put_bytes(&mut buf, header.state_root.as_bytes());
+    put_u64(&mut buf, header.difficulty as u64);
+    put_bytes(&mut buf, header.proposer.as_bytes());
+    buf
+}
+
+pub fn encode_block(buf: &mut Vec<u8>, block: &Block) {
+    buf.extend_from_slice(&header_prefix(&block.header));
+    put_u64(buf, block.header.nonce);
+    put_bytes(buf, block.hash.as_bytes());
+    put_bytes(buf, block.signature.as_bytes());
+    put_bytes(buf, block.data.as_bytes());
+    put_u64(buf, block.transactions.len() as u64);
+    for tx in &block.transactions {
+        encode_transaction(buf, tx);
+    }
+    put_u64(buf, block.calls.len() as u64);
+    for call in &block.calls {
+        buf.extend_from_slice(&call.encode());
+    }
+}
+
+fn encode_transaction(buf: &mut Vec<u8>, tx: &Transaction) {
+    put_bytes(buf, tx.id.as_bytes());
+    put_u64(buf, tx.inputs.len() as u64);
+    for input in &tx.inputs {
+        put_bytes(buf, input.prev_txid.as_bytes());
+        buf.extend_from_slice(&input.output_index.to_le_bytes());
+        put_bytes(buf, input.public_key.as_bytes());
+        put_bytes(buf, input.signature.as_bytes());
+    }
+    put_u64(buf, tx.outputs.len() as u64);
+    for output in &tx.outputs {
+        put_bytes(buf, output.address.as_bytes());
+        put_u64(buf, output.amount);
+    }
+    put_u64(buf, tx.fee);
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// 在字节切片上顺序读取，越界或格式错误时返回 InvalidData
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Decoder { bytes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(invalid("数据被截断"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    pub fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = usize::try_from(self.u64()?).map_err(|_| invalid("长度过大"))?;
+        self.take(len)
+    }
+
+    pub fn string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| invalid("字符串不是 UTF-8"))
+    }
+
+    // 列表长度，按剩余字节数限制预分配的容量，防止伪造的长度耗尽内存
+    fn count(&mut self) -> io::Result<(usize, usize)> {
+        let count = usize::try_from(self.u64()?).map_err(|_| invalid("长度过大"))?;
+        Ok((count, count.min(self.bytes.len())))
+    }
+
+    pub fn block(&mut self) -> io::Result<Block> {
+        let header = BlockHeader {
+            index: self.u64()?,
+            timestamp: self.string()?,
+            previous_hash: self.string()?,
+            merkle_root: self.string()?,
+            state_root: self.string()?,
+            difficulty: usize::try_from(self.u64()?).map_err(|_| invalid("难度过大"))?,
+            proposer: self.string()?,
+            nonce: self.u64()?,
+        };
+        let hash = self.string()?;
+        let signature = self.string()?;
+        let data = self.string()?;
+        let (count, capacity) = self.count()?;
+        let mut transactions = Vec::with_capacity(capacity);
+        for _ in 0..count {
+            transactions.push(self.transaction()?);
+        }
+        let (count, capacity) = self.count()?;
+        let mut calls = Vec::with_capacity(capacity);
+        for _ in 0..count {
+            calls.push(self.call()?);
+        }
+        Ok(Block {
+            header,
+            data,
+            transactions,
+            calls,
+            hash,
+            signature,
+        })
+    }
+
+    fn call(&mut self) -> io::Result<Call> {
+        let contract = self.string()?;
+        let code = self.string()?;
+        let gas_limit = self.u64()?;
+        let gas_price = self.u64()?;
+        let (count, capacity) = self.count()?;
+        let mut inputs = Vec::with_capacity(capacity);
+        for _ in 0..count {
+            inputs.push(OutPoint {
+                txid: self.string()?,
+                index: self.u32()?,
+            });
+        }
+        Ok(Call {
+            contract,
+            code,
+            gas_limit,
+            gas_price,
+            inputs,
+            public_key: self.string()?,
+            signature: self.string()?,
+        })
+    }
+
+    fn transaction(&mut self) -> io::Result<Transaction> {
+        let id = self.string()?;
+        let (count, capacity) = self.count()?;
+        let mut inputs = Vec::with_capacity(capacity);
+        for _ in 0..count {
+            inputs.push(TxInput {
+                prev_txid: self.string()?,
+                output_index: self.u32()?,
+                public_key: self.string()?,
+                signature: self.string()?,
+            });
+        }
+        let (count, capacity) = self.count()?;
+        let mut outputs = Vec::with_capacity(capacity);
+        for _ in 0..count {
+            outputs.push(TxOutput {
+                address: self.string()?,
+                amount: self.u64()?,
+            });
+        }
+        Ok(Transaction {
+            id,
+            inputs,
+            outputs,
+            fee: self.u64()?,
+        })
+    }
+}
+
+pub fn encode_chain(blocks: &[Block]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    put_u64(&mut buf, blocks.len() as u64);
+    for block in blocks {
+        encode_block(&mut buf, block);
+    }
+    buf
+}
+
+// 只解析格式，区块是否有效由调用方校验
+pub fn decode_chain(bytes: &[u8]) -> io::Result<Vec<Block>> {
+    let mut decoder = Decoder::new(bytes);
+    if decoder.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err(invalid("不是区块链导出文件"));
+    }
+    let version = decoder.u16()?;
+    if version != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("不支持的导出格式版本 {}", version),
+        ));
+    }
+    let (count, capacity) = decoder.count()?;
+    let mut blocks = Vec::with_capacity(capacity);
+    for _ in 0..count {
+        blocks.push(decoder.block()?);
+    }
+    if !decoder.is_empty() {
+        return Err(invalid("导出文件末尾有多余数据"));
+    }
+    Ok(blocks)
+}
+
+// 先写临时文件再改名，导出中途失败不会留下半个文件
+pub fn export_chain<P: AsRef<Path>>(path: P, blocks: &[Block]) -> io::Result<()> {
+    let path = path.as_ref();
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(&encode_chain(blocks))?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)
+}
+
+pub fn import_chain<P: AsRef<Path>>(path: P) -> io::Result<Vec<Block>> {
+    decode_chain(&fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(index: u64, timestamp: &str) -> BlockHeader {
+        BlockHeader {
+            index,
+            timestamp: timestamp.to_string(),
+            previous_hash: "0".to_string(),
+            merkle_root: String::new(),
+            state_root: String::new(),
+            difficulty: 0,
+            proposer: String::new(),
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn header_fields_cannot_be_shifted_across_boundaries() {
+        // 直接拼接时 1 + "23" 和 12 + "3" 得到相同的字符串
+        let a = header(1, "23");
+        let b = header(12, "3");
+        assert_ne!(header_prefix(&a), header_prefix(&b));
+        assert_ne!(a.calculate_hash(), b.calculate_hash());
+    }
+
+    #[test]
+    fn chain_round_trips_and_rejects_bad_files() {
+        let tx = Transaction {
+            id: "tx".to_string(),
+            inputs: vec![TxInput::new("prev".to_string(), 3)],
+            outputs: vec![TxOutput {
+                address: "alice".to_string(),
+                amount: 7,
+            }],
+            fee: 1,
+        };
+        let mut block = Block::new(1, "数据".to_string(), vec![tx], "0".to_string());
+        let mut call = Call::new(
+            "counter".to_string(),
+            "PUSH 1".to_string(),
+            10,
+            2,
+            vec![OutPoint {
+                txid: "prev".to_string(),
+                index: 1,
+            }],
+        );
+        call.public_key = "key".to_string();
+        call.signature = "signature".to_string();
+        block.calls.push(call);
+        let bytes = encode_chain(&[block.clone(), block.clone()]);
+
+        let decoded = decode_chain(&bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[1].header, block.header);
+        assert_eq!(decoded[1].hash, block.hash);
+        assert_eq!(decoded[1].transactions, block.transactions);
+        assert_eq!(decoded[1].calls, block.calls);
+
+        assert!(decode_chain(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_chain(b"JSON{}").is_err());
+        let mut future = bytes.clone();
+        future[4] = 9;
+        assert!(decode_chain(&future).is_err());
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(decode_chain(&trailing).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+// src/lib.rs
+pub mod api;
+pub mod block;
+pub mod blockchain;
+pub mod consensus;
+pub mod encoding;
+pub mod light;
+pub mod mempool;
+pub mod merkle;
+pub mod miner;
+pub mod node;
+pub mod state;
+pub mod storage;
+pub mod transaction;
+pub mod vm;
+pub mod wallet;
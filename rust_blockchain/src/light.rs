@@ -0,0 +1,390 @@
+// src/light.rs
+// 轻节点(SPV)：只同步和保存区块头，检查高度、链接、时间戳、难度和工作量证明，
+// 单个条目用全节点提供的 Merkle 包含证明对照区块头中的 Merkle 根校验。
+// 轻节点看不到区块内容，只能确认条目被打包进了工作量足够的链，不能确认交易本身有效。
+// 目前只支持工作量证明的链。
+use crate::block::{block_work, meets_difficulty, transaction_leaf, BlockHeader};
+use crate::blockchain::{
+    self, Blockchain, ValidationError, DEFAULT_RETARGET_INTERVAL, DEFAULT_TARGET_BLOCK_TIME,
+};
+use crate::merkle::{Hash, MerkleProof};
+use crate::node::{self, Message, MAX_HEADERS};
+use crate::transaction::Transaction;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+// 全节点返回的交易包含证明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionProof {
+    // 交易所在区块的高度
+    pub index: u64,
+    pub transaction: Transaction,
+    pub proof: MerkleProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpvError {
+    Header(ValidationError),
+    // 这批区块头接不到已知的链上
+    Disconnected { from: u64 },
+    // 本地还没有这个高度的区块头
+    UnknownBlock(u64),
+    // 证明里的交易不是请求的那一笔
+    TransactionMismatch,
+    BadProof { index: u64 },
+}
+
+impl fmt::Display for SpvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpvError::Header(e) => write!(f, "区块头无效: {}", e),
+            SpvError::Disconnected { from } => {
+                write!(f, "从高度 {} 开始的区块头接不上本地的链", from)
+            }
+            SpvError::UnknownBlock(index) => write!(f, "还没有高度 {} 的区块头", index),
+            SpvError::TransactionMismatch => write!(f, "证明中的交易与请求的不符"),
+            SpvError::BadProof { index } => {
+                write!(f, "包含证明与区块 #{} 的 Merkle 根不符", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpvError {}
+
+impl From<ValidationError> for SpvError {
+    fn from(e: ValidationError) -> Self {
+        SpvError::Header(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LightClient {
+    headers: Vec<BlockHeader>,
+    // 与 headers 一一对应的区块哈希
+    hashes: Vec<String>,
+    // 难度调整规则，必须与全节点一致
+    pub retarget_interval: u64,
+    pub target_block_time: Duration,
+}
+
+impl LightClient {
+    // 从写死的创世区块开始，difficulty 是全节点创世区块中记录的初始难度，不同时创世区块哈希也不同
+    pub fn new(difficulty: usize) -> Self {
+        let genesis = Blockchain::create_genesis_block(difficulty);
+        LightClient {
+            headers: vec![genesis.header],
+            hashes: vec![genesis.hash],
+            retarget_interval: DEFAULT_RETARGET_INTERVAL,
+            target_block_time: DEFAULT_TARGET_BLOCK_TIME,
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.headers.len() as u64 - 1
+    }
+
+    pub fn tip_hash(&self) -> &str {
+        self.hashes.last().unwrap()
+    }
+
+    pub fn header(&self, index: u64) -> Option<&BlockHeader> {
+        self.headers.get(usize::try_from(index).ok()?)
+    }
+
+    pub fn cumulative_work(&self) -> u128 {
+        self.headers
+            .iter()
+            .map(|header| block_work(header.difficulty))
+            .fold(0u128, u128::saturating_add)
+    }
+
+    // 接收从 headers[0].index 开始的连续区块头。全部有效且得到的链累计工作量更大时
+    // 切换过去(可能丢掉本地分叉上的区块头)并返回 true
+    pub fn apply_headers(&mut self, headers: Vec<BlockHeader>) -> Result<bool, SpvError> {
+        let Some(from) = headers.first().map(|header| header.index) else {
+            return Ok(false);
+        };
+        if from == 0 || from > self.height() + 1 {
+            return Err(SpvError::Disconnected { from });
+        }
+
+        let keep = from as usize;
+        let mut candidate = LightClient {
+            headers: self.headers[..keep].to_vec(),
+            hashes: self.hashes[..keep].to_vec(),
+            ..*self
+        };
+        for header in headers {
+            candidate.push(header)?;
+        }
+        if candidate.cumulative_work() <= self.cumulative_work() {
+            return Ok(false);
+        }
+        *self = candidate;
+        Ok(true)
+    }
+
+    // 检查区块头能接在链尾，规则与 Blockchain::validate 中不依赖区块内容的部分相同
+    fn push(&mut self, header: BlockHeader) -> Result<(), ValidationError> {
+        let index = self.headers.len();
+        let previous = &self.headers[index - 1];
+        if header.index != index as u64 {
+            return Err(ValidationError::IndexMismatch {
+                index,
+                found: header.index,
+            });
+        }
+        if header.previous_hash != self.hashes[index - 1] {
+            return Err(ValidationError::PreviousHashMismatch { index });
+        }
+        let time = header
+            .time()
+            .ok_or(ValidationError::BadTimestamp { index })?;
+        if previous.time().is_some_and(|previous| time < previous) {
+            return Err(ValidationError::TimestampNotIncreasing { index });
+        }
+
+        let expected = blockchain::expected_difficulty(
+            index,
+            self.headers[0].difficulty,
+            self.retarget_interval,
+            self.target_block_time,
+            |i| &self.headers[i],
+        );
+        if Some(header.difficulty) != expected {
+            return Err(ValidationError::DifficultyMismatch {
+                index,
+                expected,
+                found: header.difficulty,
+            });
+        }
+        let hash = header.calculate_hash();
+        if !meets_difficulty(&hash, header.difficulty) {
+            return Err(ValidationError::InsufficientWork { index });
+        }
+
+        self.headers.push(header);
+        self.hashes.push(hash);
+        Ok(())
+    }
+
+    // 校验叶子哈希为 leaf 的条目包含在第 index 个区块中
+    pub fn verify_entry(
+        &self,
+        index: u64,
+        leaf: Hash,
+        proof: &MerkleProof,
+    ) -> Result<(), SpvError> {
+        let header = self.header(index).ok_or(SpvError::UnknownBlock(index))?;
+        if !header.verify_inclusion(leaf, proof) {
+            return Err(SpvError::BadProof { index });
+        }
+        Ok(())
+    }
+
+    // 校验交易 txid 已上链，返回确认数(所在区块及之后的区块数)
+    pub fn verify_transaction(
+        &self,
+        txid: &str,
+        proof: &TransactionProof,
+    ) -> Result<u64, SpvError> {
+        if proof.transaction.id != txid {
+            return Err(SpvError::TransactionMismatch);
+        }
+        // 叶子按交易类型计算，区块 data 或合约调用的证明即使字节相同也对不上
+        self.verify_entry(
+            proof.index,
+            transaction_leaf(&proof.transaction),
+            &proof.proof,
+        )?;
+        Ok(self.height() - proof.index + 1)
+    }
+
+    // 从全节点同步区块头直到追上对方，链发生切换时返回 true。
+    // 第一个区块头接不上本地链尾说明本地在另一条分叉上，每次往回多取一倍的区块头
+    pub async fn sync(&mut self, peer: &mut Peer) -> io::Result<bool> {
+        let mut from = self.height() + 1;
+        let mut back = 1;
+        let mut switched = false;
+        loop {
+            let headers = peer.headers(from).await?;
+            let full = headers.len() >= MAX_HEADERS;
+            match self.apply_headers(headers) {
+                Ok(true) => switched = true,
+                Ok(false) => return Ok(switched),
+                Err(SpvError::Header(ValidationError::PreviousHashMismatch { index }))
+                    if index as u64 == from && from > 1 =>
+                {
+                    from = from.saturating_sub(back).max(1);
+                    back *= 2;
+                    continue;
+                }
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+            }
+            if !full {
+                return Ok(switched);
+            }
+            from = self.height() + 1;
+        }
+    }
+}
+
+// 与全节点的连接。只发请求、等对应的回复，全节点推送的其他消息直接丢弃
+pub struct Peer {
+    framed: Framed<TcpStream, LengthDelimitedCodec>,
+}
+
+impl Peer {
+    pub async fn connect(addr: &str) -> io::Result<Self> {
+        Ok(Peer {
+            framed: node::framed(TcpStream::connect(addr).await?),
+        })
+    }
+
+    async fn request<T>(
+        &mut self,
+        message: &Message,
+        mut reply: impl FnMut(Message) -> Option<T>,
+    ) -> io::Result<T> {
+        node::send(&mut self.framed, message).await?;
+        loop {
+            let frame = self.framed.next().await.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "全节点断开了连接")
+            })??;
+            if let Some(value) = reply(serde_json::from_slice(&frame)?) {
+                return Ok(value);
+            }
+        }
+    }
+
+    pub async fn headers(&mut self, from: u64) -> io::Result<Vec<BlockHeader>> {
+        self.request(&Message::GetHeaders { from }, |message| match message {
+            Message::Headers(headers) => Some(headers),
+            _ => None,
+        })
+        .await
+    }
+
+    // 全节点返回的证明，需要再用 LightClient::verify_transaction 校验
+    pub async fn transaction_proof(&mut self, txid: &str) -> io::Result<Option<TransactionProof>> {
+        let request = Message::GetProof {
+            txid: txid.to_string(),
+        };
+        self.request(&request, |message| match message {
+            Message::Proof { txid: id, proof } if id == txid => Some(proof),
+            _ => None,
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::Node;
+    use tokio::net::TcpListener;
+
+    // 每个区块包含一笔铸币交易
+    fn full_chain(blocks: u64) -> (Blockchain, Vec<Transaction>) {
+        let mut chain = Blockchain::new(1);
+        chain.reward_address = Some("alice".to_string());
+        let mut txs = Vec::new();
+        for i in 1..=blocks {
+            chain.add_block(format!("block {}", i)).unwrap();
+            txs.push(chain.tip().transactions[0].clone());
+        }
+        (chain, txs)
+    }
+
+    #[test]
+    fn follows_headers_and_checks_proofs() {
+        let (mut chain, txs) = full_chain(4);
+        let mut client = LightClient::new(1);
+        assert!(client.apply_headers(chain.headers(1, MAX_HEADERS)).unwrap());
+        assert_eq!(client.height(), 4);
+        assert_eq!(client.tip_hash(), chain.tip().hash);
+        assert_eq!(client.cumulative_work(), chain.cumulative_work());
+
+        let proof = chain.transaction_proof(&txs[1].id).unwrap();
+        assert_eq!(client.verify_transaction(&txs[1].id, &proof), Ok(3));
+        assert_eq!(
+            client.verify_transaction(&txs[0].id, &proof),
+            Err(SpvError::TransactionMismatch)
+        );
+        // 全节点篡改交易内容
+        let mut forged = proof.clone();
+        forged.transaction.outputs[0].amount = 1_000;
+        assert_eq!(
+            client.verify_transaction(&txs[1].id, &forged),
+            Err(SpvError::BadProof { index: 2 })
+        );
+        // 换成同一区块中 data 条目的证明
+        let data_proof = TransactionProof {
+            proof: chain.chain[2].inclusion_proof(0).unwrap(),
+            ..proof.clone()
+        };
+        assert_eq!(
+            client.verify_transaction(&txs[1].id, &data_proof),
+            Err(SpvError::BadProof { index: 2 })
+        );
+
+        // 工作量不足的区块头
+        let mut header = chain.prepare_block("fake".to_string()).header;
+        while meets_difficulty(&header.calculate_hash(), header.difficulty) {
+            header.nonce += 1;
+        }
+        assert_eq!(
+            client.apply_headers(vec![header.clone()]),
+            Err(SpvError::Header(ValidationError::InsufficientWork {
+                index: 5
+            }))
+        );
+        header.index = 9;
+        assert_eq!(
+            client.apply_headers(vec![header]),
+            Err(SpvError::Disconnected { from: 9 })
+        );
+        assert_eq!(client.height(), 4);
+    }
+
+    #[test]
+    fn switches_only_to_heavier_fork() {
+        let (short, _) = full_chain(2);
+        let (long, _) = full_chain(3);
+        let mut client = LightClient::new(1);
+        client.apply_headers(short.headers(1, MAX_HEADERS)).unwrap();
+
+        assert!(client.apply_headers(long.headers(1, MAX_HEADERS)).unwrap());
+        assert_eq!(client.tip_hash(), long.tip().hash);
+        assert!(!client.apply_headers(short.headers(1, MAX_HEADERS)).unwrap());
+        assert_eq!(client.tip_hash(), long.tip().hash);
+    }
+
+    #[tokio::test]
+    async fn syncs_from_full_node() {
+        let (chain, txs) = full_chain(3);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(Node::new(chain).serve(listener));
+
+        // 本地先跟着另一条较短的分叉
+        let (fork, _) = full_chain(1);
+        let mut client = LightClient::new(1);
+        client.apply_headers(fork.headers(1, MAX_HEADERS)).unwrap();
+
+        let mut peer = Peer::connect(&addr).await.unwrap();
+        assert!(client.sync(&mut peer).await.unwrap());
+        assert_eq!(client.height(), 3);
+        assert!(!client.sync(&mut peer).await.unwrap());
+
+        let proof = peer.transaction_proof(&txs[2].id).await.unwrap().unwrap();
+        assert_eq!(client.verify_transaction(&txs[2].id, &proof), Ok(1));
+        assert_eq!(peer.transaction_proof("missing").await.unwrap(), None);
+    }
+}
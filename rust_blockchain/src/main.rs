@@ -1,19 +1,371 @@
 // src/main.rs
-mod block;
-mod blockchain;
+// 命令行入口。init/add/mine/show/validate/export/import/tamper 操作 --file 指定的数据文件，
+// 每条命令向标准输出写一个 JSON 对象(失败时为 {"error": ...})，退出码见下方常量。
+// 不带子命令时进入交互菜单；node 和 serve 运行 P2P 节点和 HTTP 服务。
+use clap::{Parser, Subcommand};
+use rust_blockchain::api;
+use rust_blockchain::block::Block;
+use rust_blockchain::blockchain::Blockchain;
+use rust_blockchain::light::{LightClient, Peer};
+use rust_blockchain::miner::Miner;
+use rust_blockchain::node;
+use rust_blockchain::storage::{self, ChainStore};
+use rust_blockchain::transaction::{Transaction, TxInput, TxOutput};
+use rust_blockchain::wallet::Keystore;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::{io, process};
 
-use blockchain::Blockchain;
-use std::io;
+// 退出码: 0 成功，2 参数错误(由 clap 输出用法)
+// 区块链无效，或导入、初始化等操作被拒绝
+const EXIT_REJECTED: i32 = 1;
+// 读写数据文件失败
+const EXIT_IO: i32 = 3;
+// 要查看或篡改的区块不存在
+const EXIT_NOT_FOUND: i32 = 4;
+
+#[derive(Parser)]
+#[command(name = "rust_blockchain", version, about = "简单的区块链")]
+struct Cli {
+    /// 区块链数据文件
+    #[arg(short, long, global = true, default_value = "blockchain.db")]
+    file: PathBuf,
+    /// 新建数据文件时第 1 个区块的难度，记录在创世区块中，已有的文件以其中记录的为准
+    #[arg(short, long, global = true, default_value_t = 4)]
+    difficulty: usize,
+    /// 挖出的区块把出块奖励和手续费发给这个地址，不指定时不铸币
+    #[arg(long, global = true)]
+    reward: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 创建只含创世区块的数据文件
+    Init,
+    /// 挖出一个包含 DATA 的区块
+    Add { data: String },
+    /// 连续挖出多个区块
+    Mine {
+        #[arg(short, long, default_value_t = 1)]
+        count: usize,
+        #[arg(long, default_value = "")]
+        data: String,
+    },
+    /// 按高度或哈希输出区块
+    Show { id: String },
+    /// 校验数据文件，无效时退出码为 1
+    Validate,
+    /// 导出为二进制文件
+    Export { output: PathBuf },
+    /// 导入导出文件，累计工作量更大时才替换本地链
+    Import { input: PathBuf },
+    /// 直接改写磁盘上某个区块的数据(演示用)，之后 validate 会失败
+    Tamper {
+        id: String,
+        #[arg(long, default_value = "tampered")]
+        data: String,
+        /// 同时重算 Merkle 根和哈希，让篡改在后续区块的链接处才被发现
+        #[arg(long)]
+        rehash: bool,
+    },
+    /// 作为轻节点从全节点同步区块头，可选校验一笔交易已上链
+    Spv {
+        peer: String,
+        #[arg(long)]
+        txid: Option<String>,
+    },
+    /// 交互菜单(默认)
+    Interactive {
+        #[arg(default_value = "wallet.json")]
+        wallet: PathBuf,
+    },
+    /// 运行 P2P 节点，标准输入的每一行挖出一个区块
+    Node { listen: String, peers: Vec<String> },
+    /// 运行 HTTP 接口和区块浏览器，可选同时运行 P2P 节点
+    Serve {
+        http: String,
+        p2p: Option<String>,
+        peers: Vec<String>,
+    },
+}
 
 fn main() {
-    let mut blockchain = Blockchain::new(4); // 工作量证明的难度
+    let cli = Cli::parse();
+    let (path, difficulty, reward) = (cli.file.as_path(), cli.difficulty, cli.reward);
+    let command = cli.command.unwrap_or(Command::Interactive {
+        wallet: PathBuf::from("wallet.json"),
+    });
+    match command {
+        Command::Init => run_init(path, difficulty),
+        Command::Add { data } => run_mine(path, difficulty, reward, 1, &data),
+        Command::Mine { count, data } => run_mine(path, difficulty, reward, count, &data),
+        Command::Show { id } => run_show(path, &id),
+        Command::Validate => run_validate(path),
+        Command::Export { output } => run_export(path, difficulty, &output),
+        Command::Import { input } => run_import(path, &input),
+        Command::Tamper { id, data, rehash } => run_tamper(path, &id, data, rehash),
+        Command::Spv { peer, txid } => run_spv(difficulty, &peer, txid),
+        Command::Interactive { wallet } => run_interactive(path, difficulty, reward, &wallet),
+        Command::Node { listen, peers } => run_node(path, difficulty, reward, &listen, peers),
+        Command::Serve { http, p2p, peers } => {
+            run_server(path, difficulty, reward, &http, p2p, peers)
+        }
+    }
+}
+
+fn emit(value: Value) {
+    println!("{}", value);
+}
+
+fn fail(code: i32, message: impl ToString) -> ! {
+    emit(json!({ "error": message.to_string() }));
+    process::exit(code);
+}
+
+// 数据校验失败属于区块链无效，其余是读写错误
+fn fail_io(e: io::Error) -> ! {
+    let code = match e.kind() {
+        io::ErrorKind::InvalidData => EXIT_REJECTED,
+        _ => EXIT_IO,
+    };
+    fail(code, e)
+}
+
+// 打开已存在的数据文件并完整校验，不存在时不会自动创建
+fn open_existing(path: &Path, difficulty: usize) -> Blockchain {
+    if !path.exists() {
+        fail(EXIT_IO, format!("{} 不存在，先运行 init", path.display()));
+    }
+    open(path, difficulty).unwrap_or_else(|e| fail_io(e))
+}
+
+// 打开数据文件，截断了上次崩溃留下的不完整记录时在标准错误提示
+fn open(path: &Path, difficulty: usize) -> io::Result<Blockchain> {
+    let blockchain = Blockchain::open(path, difficulty)?;
+    if blockchain.discarded_bytes() > 0 {
+        eprintln!(
+            "{}: 丢弃尾部 {} 字节的不完整记录",
+            path.display(),
+            blockchain.discarded_bytes()
+        );
+    }
+    Ok(blockchain)
+}
+
+// 只读加载，不校验，用于查看和检查可能已损坏的文件
+fn read_existing(path: &Path) -> (Vec<Block>, usize) {
+    storage::read_blocks(path).unwrap_or_else(|e| fail_io(e))
+}
+
+fn summary(block: &Block) -> Value {
+    json!({
+        "index": block.header.index,
+        "hash": block.hash,
+        "previous_hash": block.header.previous_hash,
+        "timestamp": block.header.timestamp,
+        "difficulty": block.header.difficulty,
+        "nonce": block.header.nonce,
+        "data": block.data,
+        "transactions": block.transactions.len(),
+    })
+}
+
+fn run_init(path: &Path, difficulty: usize) {
+    if path.metadata().is_ok_and(|meta| meta.len() > 0) {
+        fail(EXIT_REJECTED, format!("{} 已存在", path.display()));
+    }
+    let blockchain = open(path, difficulty).unwrap_or_else(|e| fail_io(e));
+    emit(json!({
+        "file": path,
+        "height": blockchain.tip().header.index,
+        "genesis": blockchain.chain[0].hash,
+    }));
+}
+
+fn run_mine(path: &Path, difficulty: usize, reward: Option<String>, count: usize, data: &str) {
+    let mut blockchain = open_existing(path, difficulty);
+    blockchain.reward_address = reward;
+    let mut mined = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut block = blockchain.prepare_block(data.to_string());
+        let difficulty = block.header.difficulty;
+        let stats = Miner::default()
+            .mine(&mut block, difficulty)
+            .expect("未设置取消标记的挖矿不会被取消");
+        let mut entry = summary(&block);
+        entry["attempts"] = json!(stats.attempts);
+        entry["elapsed_ms"] = json!(stats.elapsed.as_millis() as u64);
+        entry["hashrate"] = json!(stats.hashrate().round());
+        match blockchain.append_block(block) {
+            Ok(true) => mined.push(entry),
+            Ok(false) => fail(EXIT_REJECTED, "挖出的区块未通过校验"),
+            Err(e) => fail_io(e),
+        }
+    }
+    emit(json!({
+        "height": blockchain.tip().header.index,
+        "blocks": mined,
+    }));
+}
+
+fn run_show(path: &Path, id: &str) {
+    let (blocks, _) = read_existing(path);
+    let blockchain = Blockchain::from_blocks(blocks);
+    match blockchain.find_block(id) {
+        Some(block) => emit(json!(block)),
+        None => fail(EXIT_NOT_FOUND, format!("区块 {} 不存在", id)),
+    }
+}
+
+fn run_validate(path: &Path) {
+    let (blocks, discarded) = read_existing(path);
+    let blockchain = Blockchain::from_blocks(blocks);
+    let transactions: usize = blockchain
+        .chain
+        .iter()
+        .map(|block| block.transactions.len())
+        .sum();
+    let result = blockchain.validate();
+    let error = result.as_ref().err().map(|e| {
+        json!({
+            "index": e.index(),
+            "message": e.to_string(),
+        })
+    });
+    emit(json!({
+        "valid": result.is_ok(),
+        "blocks": blockchain.chain.len(),
+        "transactions": transactions,
+        // u128 可能超出 JSON 数字的范围
+        "work": blockchain.cumulative_work().to_string(),
+        // 尾部写了一半、未参与校验的字节数
+        "discarded_bytes": discarded,
+        "error": error,
+    }));
+    if result.is_err() {
+        process::exit(EXIT_REJECTED);
+    }
+}
+
+fn run_export(path: &Path, difficulty: usize, output: &Path) {
+    let blockchain = open_existing(path, difficulty);
+    blockchain.export(output).unwrap_or_else(|e| fail_io(e));
+    emit(json!({
+        "output": output,
+        "blocks": blockchain.chain.len(),
+    }));
+}
+
+// 导入的链经过完整校验，并且按分叉选择规则与本地链比较，累计工作量更大时才替换本地链。
+// 本地文件不存在时按导入链的创世区块新建
+fn run_import(path: &Path, input: &Path) {
+    let imported = Blockchain::import(input).unwrap_or_else(|e| fail_io(e));
+    let difficulty = imported.chain[0].header.difficulty;
+    let mut blockchain = open(path, difficulty).unwrap_or_else(|e| fail_io(e));
+    let replaced = blockchain
+        .replace_chain(imported.chain)
+        .unwrap_or_else(|e| fail_io(e));
+    emit(json!({
+        "imported": replaced,
+        "blocks": blockchain.chain.len(),
+    }));
+    if !replaced {
+        process::exit(EXIT_REJECTED);
+    }
+}
+
+// 绕过校验直接重写日志，只用于演示校验如何发现篡改
+fn run_tamper(path: &Path, id: &str, data: String, rehash: bool) {
+    if !path.exists() {
+        fail(EXIT_IO, format!("{} 不存在，先运行 init", path.display()));
+    }
+    let (mut store, mut blocks, _) = ChainStore::open(path).unwrap_or_else(|e| fail_io(e));
+    // 文件可能已被篡改，按区块头里的高度或哈希查找，不依赖它在文件中的位置
+    let height = id.parse::<u64>().ok();
+    let Some(index) = blocks
+        .iter()
+        .position(|block| Some(block.header.index) == height || block.hash == id)
+    else {
+        fail(EXIT_NOT_FOUND, format!("区块 {} 不存在", id));
+    };
+    let block = &mut blocks[index];
+    block.data = data;
+    if rehash {
+        block.header.merkle_root = block.calculate_merkle_root();
+        block.hash = block.calculate_hash();
+    }
+    store.replace_all(&blocks).unwrap_or_else(|e| fail_io(e));
+    emit(summary(&blocks[index]));
+}
+
+// 轻节点不读写数据文件，每次从创世区块开始同步
+fn run_spv(difficulty: usize, peer: &str, txid: Option<String>) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let mut client = LightClient::new(difficulty);
+        let mut peer = Peer::connect(peer).await.unwrap_or_else(|e| fail_io(e));
+        client.sync(&mut peer).await.unwrap_or_else(|e| fail_io(e));
+        let mut report = json!({
+            "height": client.height(),
+            "tip": client.tip_hash(),
+            "work": client.cumulative_work().to_string(),
+        });
+        if let Some(txid) = txid {
+            let proof = peer
+                .transaction_proof(&txid)
+                .await
+                .unwrap_or_else(|e| fail_io(e));
+            let Some(proof) = proof else {
+                fail(EXIT_NOT_FOUND, format!("交易 {} 不在链上", txid));
+            };
+            match client.verify_transaction(&txid, &proof) {
+                Ok(confirmations) => {
+                    report["transaction"] = json!({
+                        "txid": txid,
+                        "block": proof.index,
+                        "confirmations": confirmations,
+                    });
+                }
+                Err(e) => fail(EXIT_REJECTED, e),
+            }
+        }
+        emit(report);
+    });
+}
+
+fn run_interactive(path: &Path, difficulty: usize, reward: Option<String>, wallet_path: &Path) {
+    let mut blockchain = match open(path, difficulty) {
+        Ok(blockchain) => blockchain,
+        Err(e) => {
+            eprintln!("无法加载区块链 {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+    blockchain.reward_address = reward;
+    println!(
+        "已从 {} 加载 {} 个区块",
+        path.display(),
+        blockchain.chain.len()
+    );
+    let mut keystore = match Keystore::open(wallet_path) {
+        Ok(keystore) => keystore,
+        Err(e) => {
+            eprintln!("无法加载钱包 {}: {}", wallet_path.display(), e);
+            process::exit(1);
+        }
+    };
 
     loop {
         println!("\n选择一个选项:");
         println!("1. 添加区块");
         println!("2. 查看区块链");
         println!("3. 验证区块链");
-        println!("4. 退出");
+        println!("4. 提交交易");
+        println!("5. 创建钱包");
+        println!("6. 查看钱包");
+        println!("7. 退出");
 
         let mut choice = String::new();
         io::stdin().read_line(&mut choice).unwrap();
@@ -23,22 +375,67 @@ fn main() {
                 println!("输入区块的数据:");
                 let mut data = String::new();
                 io::stdin().read_line(&mut data).unwrap();
-                blockchain.add_block(data.trim().to_string());
-                println!("区块已添加!");
+                match blockchain.add_block(data.trim().to_string()) {
+                    Ok(()) => println!("区块已添加!"),
+                    Err(e) => println!("区块保存失败: {}", e),
+                }
             }
             "2" => {
                 for block in &blockchain.chain {
                     println!("{:?}", block);
                 }
             }
-            "3" => {
-                if blockchain.is_valid() {
-                    println!("区块链有效。");
-                } else {
-                    println!("区块链无效!");
+            "3" => match blockchain.validate() {
+                Ok(()) => println!("区块链有效。"),
+                Err(e) => println!("区块链无效! {}", e),
+            },
+            "4" => {
+                println!("输入交易的输入(txid:序号，空格分隔):");
+                let inputs = read_line();
+                println!("输入交易的输出(地址:金额，空格分隔):");
+                let outputs = read_line();
+                println!("输入手续费:");
+                let fee = read_line();
+                println!("输入签名的钱包名:");
+                let signer = read_line();
+                match parse_transaction(&inputs, &outputs, &fee) {
+                    Some(mut tx) => {
+                        match keystore.get(&signer) {
+                            Some(wallet) => wallet.sign(&mut tx),
+                            None => {
+                                println!("钱包 {} 不存在。", signer);
+                                continue;
+                            }
+                        }
+                        let txid = tx.id.clone();
+                        match blockchain.submit_transaction(tx) {
+                            Ok(()) => println!(
+                                "交易 {} 已进入交易池，等待打包 (共 {} 笔)",
+                                txid,
+                                blockchain.mempool.len()
+                            ),
+                            Err(e) => println!("交易被拒绝: {}", e),
+                        }
+                    }
+                    None => println!("交易格式错误。"),
                 }
             }
-            "4" => {
+            "5" => {
+                println!("输入钱包名:");
+                let name = read_line();
+                match keystore.create(&name) {
+                    Ok(wallet) => println!("钱包 {} 的地址: {}", name, wallet.address()),
+                    Err(e) => println!("钱包保存失败: {}", e),
+                }
+            }
+            "6" => {
+                for (name, wallet) in keystore.wallets() {
+                    let address = wallet.address();
+                    let balance = blockchain.balance(&address);
+                    println!("{}: {} 余额 {}", name, address, balance);
+                }
+            }
+            "7" => {
                 println!("退出... 再见，区块链伙伴!");
                 break;
             }
@@ -46,3 +443,89 @@ fn main() {
         }
     }
 }
+
+fn run_node(
+    path: &Path,
+    difficulty: usize,
+    reward: Option<String>,
+    listen: &str,
+    peers: Vec<String>,
+) {
+    let mut blockchain = match open(path, difficulty) {
+        Ok(blockchain) => blockchain,
+        Err(e) => {
+            eprintln!("无法加载区块链 {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+    blockchain.reward_address = reward;
+    println!(
+        "已从 {} 加载 {} 个区块，输入一行数据即可挖出新区块",
+        path.display(),
+        blockchain.chain.len()
+    );
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    if let Err(e) = runtime.block_on(node::run(listen, blockchain, peers)) {
+        eprintln!("节点退出: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run_server(
+    path: &Path,
+    difficulty: usize,
+    reward: Option<String>,
+    http: &str,
+    p2p: Option<String>,
+    peers: Vec<String>,
+) {
+    let mut blockchain = match open(path, difficulty) {
+        Ok(blockchain) => blockchain,
+        Err(e) => {
+            eprintln!("无法加载区块链 {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+    blockchain.reward_address = reward;
+    println!(
+        "已从 {} 加载 {} 个区块",
+        path.display(),
+        blockchain.chain.len()
+    );
+
+    tracing_subscriber::fmt::init();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    if let Err(e) = runtime.block_on(api::run(http, blockchain, p2p, peers)) {
+        eprintln!("服务退出: {}", e);
+        process::exit(1);
+    }
+}
+
+fn read_line() -> String {
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).unwrap();
+    line.trim().to_string()
+}
+
+fn parse_transaction(inputs: &str, outputs: &str, fee: &str) -> Option<Transaction> {
+    let inputs = inputs
+        .split_whitespace()
+        .map(|item| {
+            let (txid, index) = item.split_once(':')?;
+            Some(TxInput::new(txid.to_string(), index.parse().ok()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let outputs = outputs
+        .split_whitespace()
+        .map(|item| {
+            let (address, amount) = item.split_once(':')?;
+            Some(TxOutput {
+                address: address.to_string(),
+                amount: amount.parse().ok()?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let fee = if fee.is_empty() { 0 } else { fee.parse().ok()? };
+    Some(Transaction::new(inputs, outputs, fee))
+}
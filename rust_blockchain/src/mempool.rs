@@ -0,0 +1,294 @@
+// src/mempool.rs
+// 待打包交易池：按 id 去重，拒绝与池中交易花费同一输入的交易，出块时按手续费率挑选。
+use crate::transaction::{OutPoint, Transaction, TransactionError};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolError {
+    Invalid(TransactionError),
+    Duplicate(String),
+    // 与池中已有交易花费了同一个输入
+    Conflict { txid: String, input: OutPoint },
+    // 输入引用的输出不存在或已被花费
+    MissingInput(OutPoint),
+    // 输入的公钥与被花费输出的地址不符
+    NotOwner(OutPoint),
+    InsufficientFunds { available: u64, required: u64 },
+    // 铸币交易只能由出块者放进自己的区块
+    Coinbase,
+}
+
+impl fmt::Display for MempoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MempoolError::Invalid(e) => write!(f, "交易无效: {}", e),
+            MempoolError::Duplicate(txid) => write!(f, "交易 {} 已存在", txid),
+            MempoolError::Conflict { txid, input } => {
+                write!(f, "输入 {} 已被交易 {} 花费", input, txid)
+            }
+            MempoolError::MissingInput(input) => write!(f, "输入 {} 不存在或已被花费", input),
+            MempoolError::NotOwner(input) => write!(f, "无权花费输入 {}", input),
+            MempoolError::InsufficientFunds {
+                available,
+                required,
+            } => write!(f, "余额不足: 可用 {}, 需要 {}", available, required),
+            MempoolError::Coinbase => write!(f, "铸币交易不能提交到交易池"),
+        }
+    }
+}
+
+impl std::error::Error for MempoolError {}
+
+impl From<TransactionError> for MempoolError {
+    fn from(e: TransactionError) -> Self {
+        MempoolError::Invalid(e)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Mempool {
+    transactions: HashMap<String, Transaction>,
+    // 输入 -> 花费它的交易 id
+    spent: HashMap<OutPoint, String>,
+}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Mempool::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    pub fn contains(&self, txid: &str) -> bool {
+        self.transactions.contains_key(txid)
+    }
+
+    pub fn get(&self, txid: &str) -> Option<&Transaction> {
+        self.transactions.get(txid)
+    }
+
+    pub fn transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.transactions.values()
+    }
+
+    pub fn add(&mut self, tx: Transaction) -> Result<(), MempoolError> {
+        tx.validate()?;
+        if tx.is_coinbase() {
+            return Err(MempoolError::Coinbase);
+        }
+        if self.transactions.contains_key(&tx.id) {
+            return Err(MempoolError::Duplicate(tx.id));
+        }
+        for input in &tx.inputs {
+            let outpoint = input.outpoint();
+            if let Some(txid) = self.spent.get(&outpoint) {
+                return Err(MempoolError::Conflict {
+                    txid: txid.clone(),
+                    input: outpoint,
+                });
+            }
+        }
+
+        for input in &tx.inputs {
+            self.spent.insert(input.outpoint(), tx.id.clone());
+        }
+        self.transactions.insert(tx.id.clone(), tx);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, txid: &str) -> Option<Transaction> {
+        let tx = self.transactions.remove(txid)?;
+        for input in &tx.inputs {
+            self.spent.remove(&input.outpoint());
+        }
+        Some(tx)
+    }
+
+    // 移除交易以及池中所有直接或间接花费它的输出的交易，这些交易已经不可能上链
+    pub fn evict(&mut self, txid: &str) {
+        let mut pending = vec![txid.to_string()];
+        while let Some(txid) = pending.pop() {
+            if self.remove(&txid).is_none() {
+                continue;
+            }
+            pending.extend(
+                self.spent
+                    .iter()
+                    .filter(|(outpoint, _)| outpoint.txid == txid)
+                    .map(|(_, child)| child.clone()),
+            );
+        }
+    }
+
+    // 区块上链后移除其中的交易，以及与它们冲突的池中交易和这些交易的后代
+    pub fn remove_confirmed(&mut self, confirmed: &[Transaction]) {
+        for tx in confirmed {
+            self.remove(&tx.id);
+            for input in &tx.inputs {
+                if let Some(txid) = self.spent.get(&input.outpoint()).cloned() {
+                    self.evict(&txid);
+                }
+            }
+        }
+    }
+
+    // 按手续费率(fee / size)从高到低挑选交易，总大小不超过 max_size。
+    // 花费池中其他交易输出的交易只会排在其父交易之后。
+    pub fn select(&self, max_size: usize) -> Vec<Transaction> {
+        let mut candidates: Vec<(&Transaction, usize)> = self
+            .transactions
+            .values()
+            .map(|tx| (tx, tx.size()))
+            .collect();
+        candidates.sort_by(|(a, a_size), (b, b_size)| {
+            compare_fee_rate(b.fee, *b_size, a.fee, *a_size).then_with(|| a.id.cmp(&b.id))
+        });
+
+        let mut selected = Vec::new();
+        let mut included = HashSet::new();
+        let mut used = 0;
+        loop {
+            let mut progressed = false;
+            for (tx, size) in &candidates {
+                if included.contains(&tx.id) || used + size > max_size {
+                    continue;
+                }
+                let parents_ready = tx.inputs.iter().all(|input| {
+                    !self.transactions.contains_key(&input.prev_txid)
+                        || included.contains(&input.prev_txid)
+                });
+                if !parents_ready {
+                    continue;
+                }
+                used += size;
+                included.insert(tx.id.clone());
+                selected.push((*tx).clone());
+                progressed = true;
+            }
+            if !progressed {
+                break;
+            }
+        }
+        selected
+    }
+}
+
+// 比较 fee_a / size_a 与 fee_b / size_b，交叉相乘避免浮点
+fn compare_fee_rate(fee_a: u64, size_a: usize, fee_b: u64, size_b: usize) -> Ordering {
+    (fee_a as u128 * size_b as u128).cmp(&(fee_b as u128 * size_a as u128))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{TxInput, TxOutput};
+    use crate::wallet::Wallet;
+
+    fn spend(prev_txid: &str, fee: u64) -> Transaction {
+        let mut tx = Transaction::new(
+            vec![TxInput::new(prev_txid.to_string(), 0)],
+            vec![TxOutput {
+                address: "bob".to_string(),
+                amount: 10,
+            }],
+            fee,
+        );
+        Wallet::generate().sign(&mut tx);
+        tx
+    }
+
+    #[test]
+    fn rejects_duplicates_and_conflicts() {
+        let mut pool = Mempool::new();
+        let tx = spend("a", 1);
+        pool.add(tx.clone()).unwrap();
+        assert_eq!(pool.add(tx.clone()), Err(MempoolError::Duplicate(tx.id)));
+
+        let conflicting = spend("a", 5);
+        assert!(matches!(
+            pool.add(conflicting),
+            Err(MempoolError::Conflict { .. })
+        ));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn selects_by_fee_rate_within_size_cap() {
+        let mut pool = Mempool::new();
+        let low = spend("a", 1);
+        let high = spend("b", 100);
+        let mid = spend("c", 50);
+        let cap = high.size() + mid.size();
+        pool.add(low.clone()).unwrap();
+        pool.add(high.clone()).unwrap();
+        pool.add(mid.clone()).unwrap();
+
+        let selected = pool.select(cap);
+        assert_eq!(selected, vec![high, mid]);
+    }
+
+    #[test]
+    fn child_follows_parent() {
+        let mut pool = Mempool::new();
+        let parent = spend("a", 1);
+        let child = spend(&parent.id, 100);
+        pool.add(parent.clone()).unwrap();
+        pool.add(child.clone()).unwrap();
+
+        assert_eq!(pool.select(usize::MAX), vec![parent, child]);
+    }
+
+    #[test]
+    fn rejects_unsigned_transactions() {
+        let mut pool = Mempool::new();
+        let mut tx = spend("a", 1);
+        tx.inputs[0].signature.clear();
+        assert_eq!(
+            pool.add(tx.clone()),
+            Err(MempoolError::Invalid(TransactionError::BadSignature(0)))
+        );
+
+        // 没有输入的交易和铸币交易都不能绕过签名进入交易池
+        let outputs = tx.outputs.clone();
+        assert_eq!(
+            pool.add(Transaction::new(Vec::new(), outputs.clone(), 0)),
+            Err(MempoolError::Invalid(TransactionError::NoInputs))
+        );
+        let coinbase = Transaction::coinbase(1, outputs[0].address.clone(), 50);
+        assert_eq!(pool.add(coinbase), Err(MempoolError::Coinbase));
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn confirmed_transactions_are_removed() {
+        let mut pool = Mempool::new();
+        let tx = spend("a", 1);
+        pool.add(tx.clone()).unwrap();
+        pool.remove_confirmed(&[spend("a", 7)]);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn descendants_of_conflicting_transactions_are_removed() {
+        let mut pool = Mempool::new();
+        let parent = spend("m", 1);
+        let child = spend(&parent.id, 1);
+        let grandchild = spend(&child.id, 1);
+        let unrelated = spend("n", 1);
+        for tx in [&parent, &child, &grandchild, &unrelated] {
+            pool.add(tx.clone()).unwrap();
+        }
+        // 区块确认了另一笔花费 m 的交易，parent 的后代都不能再被选中
+        pool.remove_confirmed(&[spend("m", 7)]);
+        assert_eq!(pool.select(usize::MAX), vec![unrelated]);
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+// src/merkle.rs
+// 区块条目的 Merkle 树。叶子和内部节点使用不同前缀(RFC 6962)，防止把内部节点伪装成叶子；
+// 叶子还带有条目类型，同样的字节作为不同类型的条目得到不同的叶子。
+// 某一层节点数为奇数时，落单的节点直接提升到上一层，不复制自身。
+// 根同时承诺叶子个数，证明只携带兄弟哈希，每一步的左右由条目位置和叶子个数推出，
+// 所以证明无法把一个条目说成在别的位置。
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const ROOT_PREFIX: u8 = 0x02;
+
+pub fn leaf_hash(kind: u8, entry: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX, kind]);
+    hasher.update(entry);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn root_hash(leaf_count: usize, top: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([ROOT_PREFIX]);
+    hasher.update((leaf_count as u64).to_le_bytes());
+    hasher.update(top);
+    hasher.finalize().into()
+}
+
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    // levels[0] 是叶子，最后一层只有根
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    // 叶子个数和树顶的哈希，没有叶子时树顶为空串的 SHA-256
+    pub fn root(&self) -> Hash {
+        let top = match self.levels.last().unwrap().first() {
+            Some(top) => *top,
+            None => Sha256::digest(b"").into(),
+        };
+        root_hash(self.leaf_count(), &top)
+    }
+
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(hash) = level.get(position ^ 1) {
+                siblings.push(hex::encode(hash));
+            }
+            position /= 2;
+        }
+        Some(MerkleProof {
+            index,
+            leaf_count: self.leaf_count(),
+            siblings,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    // 条目在区块中的位置和区块的条目数，两者一起决定路径上每一步的左右
+    pub index: usize,
+    pub leaf_count: usize,
+    // 从叶子往上的十六进制兄弟节点哈希，落单提升的层没有兄弟节点
+    pub siblings: Vec<String>,
+}
+
+impl MerkleProof {
+    // 沿着证明路径从叶子算到根。位置越界、兄弟哈希格式错误或个数与路径不符时返回 None
+    pub fn root_for(&self, leaf: Hash) -> Option<Hash> {
+        if self.index >= self.leaf_count {
+            return None;
+        }
+        let mut siblings = self.siblings.iter();
+        let (mut acc, mut position, mut width) = (leaf, self.index, self.leaf_count);
+        while width > 1 {
+            if position ^ 1 < width {
+                let sibling: Hash = hex::decode(siblings.next()?).ok()?.try_into().ok()?;
+                acc = if position % 2 == 1 {
+                    node_hash(&sibling, &acc)
+                } else {
+                    node_hash(&acc, &sibling)
+                };
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        if siblings.next().is_some() {
+            return None;
+        }
+        Some(root_hash(self.leaf_count, &acc))
+    }
+
+    pub fn verify(&self, leaf: Hash, root: &Hash) -> bool {
+        self.root_for(leaf).as_ref() == Some(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n)
+            .map(|i| leaf_hash(0, format!("entry {}", i).as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn every_leaf_has_a_valid_proof() {
+        for n in 1..=9 {
+            let leaves = leaves(n);
+            let tree = MerkleTree::new(leaves.clone());
+            let root = tree.root();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert!(proof.verify(*leaf, &root), "n={} i={}", n, i);
+            }
+            assert!(tree.proof(n).is_none());
+        }
+    }
+
+    #[test]
+    fn proof_rejects_other_leaf() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::new(leaves.clone());
+        let proof = tree.proof(2).unwrap();
+        assert!(!proof.verify(leaves[3], &tree.root()));
+        assert!(!proof.verify(leaf_hash(0, b"forged"), &tree.root()));
+        assert!(!proof.verify(leaf_hash(1, b"entry 2"), &tree.root()));
+    }
+
+    #[test]
+    fn proof_is_bound_to_its_position() {
+        for n in 1..=9 {
+            let leaves = leaves(n);
+            let tree = MerkleTree::new(leaves.clone());
+            let root = tree.root();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                for index in (0..n).filter(|&index| index != i) {
+                    let moved = MerkleProof {
+                        index,
+                        ..proof.clone()
+                    };
+                    assert!(!moved.verify(*leaf, &root), "n={} i={} -> {}", n, i, index);
+                }
+            }
+        }
+
+        // 3 个叶子中的第 2 个和 2 个叶子中的第 1 个路径形状相同，只能靠叶子个数区分
+        let tree = MerkleTree::new(leaves(3));
+        let proof = tree.proof(2).unwrap();
+        let shrunk = MerkleProof {
+            index: 1,
+            leaf_count: 2,
+            ..proof.clone()
+        };
+        assert!(!shrunk.verify(leaves(3)[2], &tree.root()));
+        let mut padded = proof;
+        padded.siblings.push(padded.siblings[0].clone());
+        assert!(!padded.verify(leaves(3)[2], &tree.root()));
+    }
+
+    #[test]
+    fn single_leaf_proof_is_empty() {
+        let leaves = leaves(1);
+        let tree = MerkleTree::new(leaves.clone());
+        let proof = tree.proof(0).unwrap();
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(leaves[0], &tree.root()));
+        // 根承诺了叶子个数，不等于唯一的叶子本身
+        assert_ne!(tree.root(), leaves[0]);
+    }
+}
@@ -0,0 +1,179 @@
+// src/miner.rs
+// 多线程工作量证明：线程 t 依次尝试 nonce = t, t + n, t + 2n ...，
+// 区块头除 nonce 以外的部分预先吸收进 SHA-256 状态，每次尝试只追加 nonce 的 8 个字节，
+// 直接比较摘要字节，不做十六进制编码。
+use crate::block::Block;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 每个线程每尝试这么多次检查一次是否已被取消或已有线程找到解
+const CHECK_INTERVAL: u64 = 1024;
+
+// 可以跨线程共享的取消标记，设置后正在进行的挖矿尽快返回
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MiningStats {
+    pub attempts: u64,
+    pub elapsed: Duration,
+}
+
+impl MiningStats {
+    // 每秒尝试的哈希次数
+    pub fn hashrate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return self.attempts as f64;
+        }
+        self.attempts as f64 / secs
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Miner {
+    threads: usize,
+    cancel: CancelToken,
+}
+
+impl Default for Miner {
+    // 线程数取 CPU 核数
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Miner::new(threads)
+    }
+}
+
+impl Miner {
+    pub fn new(threads: usize) -> Self {
+        Miner {
+            threads: threads.max(1),
+            cancel: CancelToken::new(),
+        }
+    }
+
+    // 与其他任务共用取消标记
+    pub fn with_cancel_token(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    // 按 difficulty 挖矿，找到后写回区块的 nonce 和 hash；被取消时返回 None，区块保持不变
+    pub fn mine(&self, block: &mut Block, difficulty: usize) -> Option<MiningStats> {
+        block.header.difficulty = difficulty;
+        let mut prefix = Sha256::new();
+        prefix.update(block.header.hash_prefix());
+
+        let start = Instant::now();
+        let found = AtomicBool::new(false);
+        let attempts = AtomicU64::new(0);
+        let winner: Mutex<Option<(u64, [u8; 32])>> = Mutex::new(None);
+        let step = self.threads as u64;
+
+        thread::scope(|scope| {
+            for first in 0..step {
+                let (prefix, found, attempts, winner) = (&prefix, &found, &attempts, &winner);
+                scope.spawn(move || {
+                    let mut nonce = first;
+                    let mut local = 0u64;
+                    loop {
+                        if local.is_multiple_of(CHECK_INTERVAL)
+                            && (found.load(Ordering::Relaxed) || self.cancel.is_cancelled())
+                        {
+                            break;
+                        }
+                        let mut hasher = prefix.clone();
+                        hasher.update(nonce.to_le_bytes());
+                        let digest: [u8; 32] = hasher.finalize().into();
+                        local += 1;
+                        if digest_meets_difficulty(&digest, difficulty) {
+                            let mut winner = winner.lock().unwrap();
+                            if winner.is_none() {
+                                *winner = Some((nonce, digest));
+                            }
+                            found.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        nonce = match nonce.checked_add(step) {
+                            Some(next) => next,
+                            None => break,
+                        };
+                    }
+                    attempts.fetch_add(local, Ordering::Relaxed);
+                });
+            }
+        });
+
+        let (nonce, digest) = winner.into_inner().unwrap()?;
+        block.header.nonce = nonce;
+        block.hash = hex::encode(digest);
+        Some(MiningStats {
+            attempts: attempts.into_inner(),
+            elapsed: start.elapsed(),
+        })
+    }
+}
+
+// 摘要的前 difficulty 个十六进制位(半字节)都为 0
+fn digest_meets_difficulty(digest: &[u8; 32], difficulty: usize) -> bool {
+    if difficulty > 64 {
+        return false;
+    }
+    let full_bytes = difficulty / 2;
+    digest[..full_bytes].iter().all(|&b| b == 0)
+        && (difficulty.is_multiple_of(2) || digest[full_bytes] >> 4 == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::meets_difficulty;
+
+    #[test]
+    fn mined_hash_matches_header_hash() {
+        let mut block = Block::new(1, "data".to_string(), Vec::new(), "0".to_string());
+        let stats = Miner::new(4).mine(&mut block, 3).unwrap();
+        assert_eq!(block.hash, block.calculate_hash());
+        assert!(meets_difficulty(&block.hash, 3));
+        assert_eq!(block.header.difficulty, 3);
+        assert!(stats.attempts > 0);
+    }
+
+    #[test]
+    fn mining_can_be_cancelled() {
+        let mut block = Block::new(1, "data".to_string(), Vec::new(), "0".to_string());
+        let original = block.hash.clone();
+        let miner = Miner::new(2);
+        let cancel = miner.cancel_token();
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            cancel.cancel();
+        });
+        // 难度 64 不可能在测试时间内找到
+        assert!(miner.mine(&mut block, 64).is_none());
+        canceller.join().unwrap();
+        assert_eq!(block.hash, original);
+    }
+}
@@ -0,0 +1,367 @@
+// src/node.rs
+// P2P 节点：监听 TCP，与配置的对等节点交换链头、区块和交易，按累计工作量选择分叉。
+// 帧格式沿用 ai/14-getinfo 的 LengthDelimitedCodec，每一帧是一条 JSON 编码的 Message。
+use crate::block::{Block, BlockHeader};
+use crate::blockchain::Blockchain;
+use crate::light::TransactionProof;
+use crate::mempool::MempoolError;
+use crate::miner::CancelToken;
+use crate::state::{Call, CallError};
+use crate::transaction::Transaction;
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+// 整条链可能放在一帧里发送，放宽默认 8MB 的帧长度限制
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+pub const MAX_HEADERS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Message {
+    // 链头摘要，连接建立和链切换时发送
+    Head {
+        height: u64,
+        work: u128,
+        hash: String,
+    },
+    GetChain,
+    Chain(Vec<Block>),
+    NewBlock(Block),
+    NewTransaction(Transaction),
+    NewCall(Call),
+    // 轻节点请求从 from 高度开始的区块头，每次最多回复 MAX_HEADERS 个
+    GetHeaders {
+        from: u64,
+    },
+    Headers(Vec<BlockHeader>),
+    // 轻节点请求交易的包含证明，交易不在链上时 proof 为 None
+    GetProof {
+        txid: String,
+    },
+    Proof {
+        txid: String,
+        proof: Option<TransactionProof>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    chain: Arc<Mutex<Blockchain>>,
+    // 需要转发给所有对等节点的消息
+    events: broadcast::Sender<Message>,
+    // 正在进行的挖矿，链尾被其他节点的区块改变时取消
+    mining: Arc<Mutex<Option<CancelToken>>>,
+}
+
+impl Node {
+    pub fn new(blockchain: Blockchain) -> Self {
+        let (events, _) = broadcast::channel(256);
+        Node {
+            chain: Arc::new(Mutex::new(blockchain)),
+            events,
+            mining: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn chain(&self) -> Arc<Mutex<Blockchain>> {
+        self.chain.clone()
+    }
+
+    // 订阅节点广播的消息：新区块、新交易和链切换后的链头
+    pub fn subscribe(&self) -> broadcast::Receiver<Message> {
+        self.events.subscribe()
+    }
+
+    fn head(&self) -> Message {
+        head_of(&self.chain.lock().unwrap())
+    }
+
+    fn broadcast(&self, message: Message) {
+        // 没有连接时发送失败，忽略即可
+        let _ = self.events.send(message);
+    }
+
+    pub async fn serve(self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, addr) = listener.accept().await?;
+            let node = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = node.handle(stream).await {
+                    println!("与 {} 的连接出错: {}", addr, e);
+                }
+            });
+        }
+    }
+
+    // 在后台保持与对等节点的连接，断开后定时重连
+    pub fn connect(&self, peer: String) {
+        let node = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Ok(stream) = TcpStream::connect(&peer).await {
+                    println!("已连接对等节点 {}", peer);
+                    if let Err(e) = node.handle(stream).await {
+                        println!("与 {} 的连接出错: {}", peer, e);
+                    }
+                    println!("与 {} 的连接断开", peer);
+                }
+                tokio::time::sleep(RECONNECT_INTERVAL).await;
+            }
+        });
+    }
+
+    fn cancel_mining(&self) {
+        if let Some(token) = self.mining.lock().unwrap().take() {
+            token.cancel();
+        }
+    }
+
+    // 按链的共识规则出一个新区块并广播。封装期间链尾被其他节点的区块替换时立即停止并返回 None
+    pub async fn mine(&self, data: String) -> io::Result<Option<Block>> {
+        let cancel = CancelToken::new();
+        let (mut candidate, consensus) = {
+            let mut chain = self.chain.lock().unwrap();
+            // 在持有链锁时登记，之后到达的区块一定能看到这次挖矿
+            if let Some(previous) = self.mining.lock().unwrap().replace(cancel.clone()) {
+                previous.cancel();
+            }
+            (chain.prepare_block(data), chain.consensus.clone())
+        };
+        let sealed = tokio::task::spawn_blocking(move || {
+            consensus
+                .seal(&mut candidate, &cancel)
+                .map(|sealed| sealed.then_some(candidate))
+        })
+        .await
+        .map_err(io::Error::other)?
+        .map_err(io::Error::other)?;
+        let Some(block) = sealed else {
+            return Ok(None);
+        };
+
+        if !self.chain.lock().unwrap().append_block(block.clone())? {
+            return Ok(None);
+        }
+        self.broadcast(Message::NewBlock(block.clone()));
+        Ok(Some(block))
+    }
+
+    pub fn submit_transaction(&self, tx: Transaction) -> Result<(), MempoolError> {
+        self.chain.lock().unwrap().submit_transaction(tx.clone())?;
+        self.broadcast(Message::NewTransaction(tx));
+        Ok(())
+    }
+
+    pub fn submit_call(&self, call: Call) -> Result<(), CallError> {
+        self.chain.lock().unwrap().submit_call(call.clone())?;
+        self.broadcast(Message::NewCall(call));
+        Ok(())
+    }
+
+    async fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        let mut framed = framed(stream);
+        let mut events = self.subscribe();
+
+        send(&mut framed, &self.head()).await?;
+        loop {
+            tokio::select! {
+                frame = framed.next() => {
+                    let Some(frame) = frame else {
+                        return Ok(());
+                    };
+                    let message: Message = serde_json::from_slice(&frame?)?;
+                    for reply in self.process(message).await? {
+                        send(&mut framed, &reply).await?;
+                    }
+                }
+                event = events.recv() => match event {
+                    Ok(message) => send(&mut framed, &message).await?,
+                    // 积压太多时只告诉对方链头，由对方决定是否拉取整条链
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        send(&mut framed, &self.head()).await?
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+
+    // 处理一条消息，返回要回复给发送方的消息
+    async fn process(&self, message: Message) -> io::Result<Vec<Message>> {
+        let message = match message {
+            Message::Chain(blocks) => return self.receive_chain(blocks).await.map(|_| Vec::new()),
+            message => message,
+        };
+        let mut chain = self.chain.lock().unwrap();
+        match message {
+            Message::Head { work, .. } => {
+                if work > chain.cumulative_work() {
+                    return Ok(vec![Message::GetChain]);
+                }
+            }
+            Message::GetChain => return Ok(vec![Message::Chain(chain.chain.clone())]),
+            Message::Chain(_) => unreachable!("由 receive_chain 处理"),
+            Message::NewBlock(block) => {
+                if chain.chain.iter().any(|known| known.hash == block.hash) {
+                    return Ok(Vec::new());
+                }
+                let index = block.header.index;
+                if chain.append_block(block.clone())? {
+                    println!("收到区块 #{} {}", index, block.hash);
+                    drop(chain);
+                    self.cancel_mining();
+                    self.broadcast(Message::NewBlock(block));
+                } else if index > chain.tip().header.index {
+                    // 对方领先或处在另一条分叉上，拉取整条链比较工作量
+                    return Ok(vec![Message::GetChain]);
+                }
+            }
+            Message::NewTransaction(tx) => {
+                if chain.submit_transaction(tx.clone()).is_ok() {
+                    drop(chain);
+                    self.broadcast(Message::NewTransaction(tx));
+                }
+            }
+            Message::NewCall(call) => {
+                if chain.submit_call(call.clone()).is_ok() {
+                    drop(chain);
+                    self.broadcast(Message::NewCall(call));
+                }
+            }
+            Message::GetHeaders { from } => {
+                return Ok(vec![Message::Headers(chain.headers(from, MAX_HEADERS))]);
+            }
+            Message::GetProof { txid } => {
+                let proof = chain.transaction_proof(&txid);
+                return Ok(vec![Message::Proof { txid, proof }]);
+            }
+            // 只有轻节点关心这些回复
+            Message::Headers(_) | Message::Proof { .. } => {}
+        }
+        Ok(Vec::new())
+    }
+
+    // 候选链的完整校验在阻塞线程池里进行，不持有链锁，只在最后替换时加锁
+    async fn receive_chain(&self, blocks: Vec<Block>) -> io::Result<()> {
+        let Some(mut candidate) = self.chain.lock().unwrap().candidate(blocks) else {
+            return Ok(());
+        };
+        let verified = tokio::task::spawn_blocking(move || candidate.verify().map(|_| candidate))
+            .await
+            .map_err(io::Error::other)?;
+        let Ok(candidate) = verified else {
+            return Ok(());
+        };
+        let mut chain = self.chain.lock().unwrap();
+        if chain.adopt(candidate)? {
+            println!(
+                "切换到累计工作量更大的链，高度 {}",
+                chain.tip().header.index
+            );
+            let head = head_of(&chain);
+            drop(chain);
+            self.cancel_mining();
+            self.broadcast(head);
+        }
+        Ok(())
+    }
+}
+
+fn head_of(chain: &Blockchain) -> Message {
+    Message::Head {
+        height: chain.tip().header.index,
+        work: chain.cumulative_work(),
+        hash: chain.tip().hash.clone(),
+    }
+}
+
+pub(crate) fn framed(stream: TcpStream) -> Framed<TcpStream, LengthDelimitedCodec> {
+    let codec = LengthDelimitedCodec::builder()
+        .max_frame_length(MAX_FRAME_LEN)
+        .new_codec();
+    Framed::new(stream, codec)
+}
+
+pub(crate) async fn send(
+    framed: &mut Framed<TcpStream, LengthDelimitedCodec>,
+    message: &Message,
+) -> io::Result<()> {
+    let bytes = serde_json::to_vec(message)?;
+    framed.send(Bytes::from(bytes)).await
+}
+
+// 节点模式入口：监听 listen，连接 peers，标准输入的每一行作为数据挖一个新区块
+pub async fn run(listen: &str, blockchain: Blockchain, peers: Vec<String>) -> io::Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+    println!("节点监听 {}", listener.local_addr()?);
+
+    let node = Node::new(blockchain);
+    for peer in peers {
+        node.connect(peer);
+    }
+
+    let miner = node.clone();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match miner.mine(line.trim().to_string()).await {
+                Ok(Some(block)) => println!("已挖出并广播区块 #{}", block.header.index),
+                Ok(None) => println!("挖矿期间链已更新，区块作废"),
+                Err(e) => println!("区块保存失败: {}", e),
+            }
+        }
+    });
+
+    node.serve(listener).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_for<F: Fn() -> bool>(condition: F) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("节点没有在预期时间内收敛");
+    }
+
+    fn tip_hash(node: &Node) -> String {
+        node.chain().lock().unwrap().tip().hash.clone()
+    }
+
+    #[tokio::test]
+    async fn nodes_converge_on_the_heaviest_chain() {
+        let a = Node::new(Blockchain::new(1));
+        let b = Node::new(Blockchain::new(1));
+
+        // a 先独自挖出两个区块，b 在另一条分叉上挖出一个
+        a.mine("a1".to_string()).await.unwrap().unwrap();
+        a.mine("a2".to_string()).await.unwrap().unwrap();
+        b.mine("b1".to_string()).await.unwrap().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(a.clone().serve(listener));
+        b.connect(addr.to_string());
+
+        wait_for(|| tip_hash(&a) == tip_hash(&b)).await;
+        assert_eq!(b.chain().lock().unwrap().chain.len(), 3);
+
+        // 之后 b 挖出的区块直接传播给 a
+        let block = b.mine("b2".to_string()).await.unwrap().unwrap();
+        wait_for(|| tip_hash(&a) == block.hash).await;
+        assert!(a.chain().lock().unwrap().is_valid());
+    }
+}
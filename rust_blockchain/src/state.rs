@@ -0,0 +1,579 @@
+// src/state.rs
+// 由区块推导出的世界状态：按地址汇总的余额、每个合约的键值存储和所有者。
+// 按顺序对每个区块应用交易(转账)和合约调用(在 vm 中执行程序)，
+// 状态根是状态规范编码的 SHA-256，记录在区块头中。
+use crate::block::Block;
+use crate::encoding::{put_bytes, put_u64};
+use crate::mempool::MempoolError;
+use crate::transaction::{OutPoint, Transaction, TransactionError, TxOutput};
+use crate::vm::{self, Storage, VmError};
+use crate::wallet;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+// 单次调用和整个区块的 gas 上限
+pub const MAX_CALL_GAS: u64 = 100_000;
+pub const BLOCK_GAS_LIMIT: u64 = 1_000_000;
+
+// 用户提交的合约调用：在 contract 的存储上执行 code。
+// 调用者对调用签名，并用自己的输出预付 gas_limit * gas_price；执行后按实际用掉的 gas 扣费，
+// 余额作为 (调用 id, 0) 的输出还给调用者。扣掉的费用销毁，不计入出块奖励。
+// 合约的所有者是第一个调用它的地址，之后只接受所有者的调用。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Call {
+    pub contract: String,
+    pub code: String,
+    pub gas_limit: u64,
+    pub gas_price: u64,
+    // 支付 gas 费用的输出，必须属于调用者
+    pub inputs: Vec<OutPoint>,
+    // 十六进制编码的调用者公钥和签名，由 Wallet::sign_call 填写
+    #[serde(default)]
+    pub public_key: String,
+    #[serde(default)]
+    pub signature: String,
+}
+
+impl Call {
+    pub fn new(
+        contract: String,
+        code: String,
+        gas_limit: u64,
+        gas_price: u64,
+        inputs: Vec<OutPoint>,
+    ) -> Self {
+        Call {
+            contract,
+            code,
+            gas_limit,
+            gas_price,
+            inputs,
+            public_key: String::new(),
+            signature: String::new(),
+        }
+    }
+
+    // 签名覆盖的字段，不包含公钥和签名本身
+    fn encode_unsigned(&self, buf: &mut Vec<u8>) {
+        put_bytes(buf, self.contract.as_bytes());
+        put_bytes(buf, self.code.as_bytes());
+        put_u64(buf, self.gas_limit);
+        put_u64(buf, self.gas_price);
+        put_u64(buf, self.inputs.len() as u64);
+        for outpoint in &self.inputs {
+            put_bytes(buf, outpoint.txid.as_bytes());
+            buf.extend_from_slice(&outpoint.index.to_le_bytes());
+        }
+    }
+
+    // 包含公钥和签名的规范编码，作为区块 Merkle 树的条目和导出文件的内容
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode_unsigned(&mut buf);
+        put_bytes(&mut buf, self.public_key.as_bytes());
+        put_bytes(&mut buf, self.signature.as_bytes());
+        buf
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut buf = Vec::new();
+        self.encode_unsigned(&mut buf);
+        Sha256::digest(buf).into()
+    }
+
+    // 调用 id 不覆盖签名，签名前后不变
+    pub fn id(&self) -> String {
+        hex::encode(self.signing_hash())
+    }
+
+    // 调用者的地址，公钥格式错误时为 None
+    pub fn caller(&self) -> Option<String> {
+        wallet::address_from_public_key(&self.public_key)
+    }
+
+    // 预付的费用，溢出时为 None
+    pub fn max_fee(&self) -> Option<u64> {
+        self.gas_limit.checked_mul(self.gas_price)
+    }
+
+    // 不依赖链上状态的检查：gas 上限合理、程序能解析、带着支付费用的输入并且签名有效。
+    // 执行失败不算无效，只是不改变存储
+    pub fn check(&self) -> Result<(), CallError> {
+        if self.gas_limit == 0 || self.gas_limit > MAX_CALL_GAS {
+            return Err(CallError::Invalid(VmError::OutOfGas));
+        }
+        vm::parse(&self.code).map_err(CallError::Invalid)?;
+        if self.gas_price == 0 || self.inputs.is_empty() || self.max_fee().is_none() {
+            return Err(CallError::NoFee);
+        }
+        let mut seen = HashSet::new();
+        for outpoint in &self.inputs {
+            if !seen.insert(outpoint) {
+                return Err(CallError::Unfunded(MempoolError::Invalid(
+                    TransactionError::DuplicateInput(outpoint.clone()),
+                )));
+            }
+        }
+        if !wallet::verify(&self.public_key, &self.signing_hash(), &self.signature) {
+            return Err(CallError::BadSignature);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    Invalid(VmError),
+    Duplicate(String),
+    // gas 价格为 0 或者没有支付费用的输入
+    NoFee,
+    BadSignature,
+    // 合约已属于另一个地址
+    NotOwner(String),
+    // 支付费用的输入不存在、不属于调用者或金额不足
+    Unfunded(MempoolError),
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Invalid(e) => write!(f, "合约调用无效: {}", e),
+            CallError::Duplicate(id) => write!(f, "合约调用 {} 已存在", id),
+            CallError::NoFee => write!(f, "合约调用必须设置 gas 价格并带上支付费用的输入"),
+            CallError::BadSignature => write!(f, "合约调用的签名无效"),
+            CallError::NotOwner(owner) => write!(f, "只有合约的所有者 {} 可以调用", owner),
+            CallError::Unfunded(e) => write!(f, "无法支付合约调用的费用: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+// 区块中无法应用到状态上的交易或合约调用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyError {
+    Transaction { txid: String, error: MempoolError },
+    Call { id: String, error: CallError },
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApplyError::Transaction { txid, error } => write!(f, "交易 {} 无效: {}", txid, error),
+            ApplyError::Call { id, error } => write!(f, "合约调用 {} 无效: {}", id, error),
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+// 合约调用的执行结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    pub call_id: String,
+    pub gas_used: u64,
+    // 执行失败的原因，成功时为 None，失败的调用不修改存储
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorldState {
+    // 余额为 0 的地址不保存
+    pub balances: BTreeMap<String, u64>,
+    // 空存储不保存
+    pub storage: BTreeMap<String, Storage>,
+    // 合约 -> 所有者地址
+    pub owners: BTreeMap<String, String>,
+    // 未花费的输出，用来确定交易输入花费的是谁的多少钱，不计入状态根
+    unspent: HashMap<OutPoint, TxOutput>,
+}
+
+impl WorldState {
+    // 从空状态依次应用 blocks
+    pub fn replay<'a>(blocks: impl IntoIterator<Item = &'a Block>) -> Result<Self, ApplyError> {
+        let mut state = WorldState::default();
+        for block in blocks {
+            state.apply_block(block)?;
+        }
+        Ok(state)
+    }
+
+    pub fn unspent(&self) -> &HashMap<OutPoint, TxOutput> {
+        &self.unspent
+    }
+
+    pub fn balance(&self, address: &str) -> u64 {
+        self.balances.get(address).copied().unwrap_or(0)
+    }
+
+    // 先按顺序应用交易，再执行合约调用。交易或调用无效时返回错误，状态可能只应用了一部分
+    pub fn apply_block(&mut self, block: &Block) -> Result<Vec<Receipt>, ApplyError> {
+        for tx in &block.transactions {
+            self.apply_transaction(tx)
+                .map_err(|error| ApplyError::Transaction {
+                    txid: tx.id.clone(),
+                    error,
+                })?;
+        }
+        block
+            .calls
+            .iter()
+            .map(|call| {
+                self.apply_call(call).map_err(|error| ApplyError::Call {
+                    id: call.id(),
+                    error,
+                })
+            })
+            .collect()
+    }
+
+    // 检查交易结构、签名和输入，然后转移余额。铸币交易没有要花费的输入，
+    // 它的金额上限和位置由 Blockchain 按区块检查
+    pub fn apply_transaction(&mut self, tx: &Transaction) -> Result<(), MempoolError> {
+        tx.validate()?;
+        if !tx.is_coinbase() {
+            for (owner, amount) in spend(&mut self.unspent, tx)? {
+                self.debit(&owner, amount);
+            }
+        }
+        for output in &tx.outputs {
+            let balance = self.balances.entry(output.address.clone()).or_default();
+            *balance = balance.saturating_add(output.amount);
+        }
+        add_outputs(&mut self.unspent, tx);
+        Ok(())
+    }
+
+    // 检查签名、所有者和费用后执行调用。返回错误时状态不变；
+    // 执行失败时只扣除用掉的 gas 费用，存储不变
+    pub fn apply_call(&mut self, call: &Call) -> Result<Receipt, CallError> {
+        call.check()?;
+        let caller = call.caller().ok_or(CallError::BadSignature)?;
+        if let Some(owner) = self.owners.get(&call.contract) {
+            if *owner != caller {
+                return Err(CallError::NotOwner(owner.clone()));
+            }
+        }
+        let max_fee = call.max_fee().ok_or(CallError::NoFee)?;
+        let paid = take_outputs(&mut self.unspent, &call.inputs, &caller, max_fee)
+            .map_err(CallError::Unfunded)?;
+        self.owners
+            .entry(call.contract.clone())
+            .or_insert_with(|| caller.clone());
+
+        let call_id = call.id();
+        let receipt = self.execute(call, call_id.clone());
+        // gas_used 不超过 gas_limit，费用不超过预付的金额
+        let fee = receipt.gas_used * call.gas_price;
+        self.debit(&caller, fee);
+        if paid > fee {
+            let change = OutPoint {
+                txid: call_id,
+                index: 0,
+            };
+            self.unspent.insert(
+                change,
+                TxOutput {
+                    address: caller,
+                    amount: paid - fee,
+                },
+            );
+        }
+        Ok(receipt)
+    }
+
+    fn execute(&mut self, call: &Call, call_id: String) -> Receipt {
+        let empty = Storage::new();
+        let current = self.storage.get(&call.contract).unwrap_or(&empty);
+        let result = vm::parse(&call.code)
+            .map_err(|e| (e, 0))
+            .and_then(|program| vm::execute(&program, current, call.gas_limit));
+
+        match result {
+            Ok(execution) => {
+                if execution.storage.is_empty() {
+                    self.storage.remove(&call.contract);
+                } else {
+                    self.storage
+                        .insert(call.contract.clone(), execution.storage);
+                }
+                Receipt {
+                    call_id,
+                    gas_used: execution.gas_used,
+                    error: None,
+                }
+            }
+            Err((e, gas_used)) => Receipt {
+                call_id,
+                gas_used,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    fn debit(&mut self, address: &str, amount: u64) {
+        if let Some(balance) = self.balances.get_mut(address) {
+            *balance -= amount;
+            if *balance == 0 {
+                self.balances.remove(address);
+            }
+        }
+    }
+
+    // 状态根：余额、存储和合约所有者按键排序后的规范编码的哈希
+    pub fn root(&self) -> String {
+        let mut buf = Vec::new();
+        put_u64(&mut buf, self.balances.len() as u64);
+        for (address, amount) in &self.balances {
+            put_bytes(&mut buf, address.as_bytes());
+            put_u64(&mut buf, *amount);
+        }
+        put_u64(&mut buf, self.storage.len() as u64);
+        for (contract, storage) in &self.storage {
+            put_bytes(&mut buf, contract.as_bytes());
+            put_u64(&mut buf, storage.len() as u64);
+            for (key, value) in storage {
+                buf.extend_from_slice(&key.to_le_bytes());
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        put_u64(&mut buf, self.owners.len() as u64);
+        for (contract, owner) in &self.owners {
+            put_bytes(&mut buf, contract.as_bytes());
+            put_bytes(&mut buf, owner.as_bytes());
+        }
+        hex::encode(Sha256::digest(buf))
+    }
+}
+
+pub(crate) fn add_outputs(unspent: &mut HashMap<OutPoint, TxOutput>, tx: &Transaction) {
+    for (index, output) in tx.outputs.iter().enumerate() {
+        let outpoint = OutPoint {
+            txid: tx.id.clone(),
+            index: index as u32,
+        };
+        unspent.insert(outpoint, output.clone());
+    }
+}
+
+// 从 unspent 中移除交易花费的输出，检查签名公钥对应输出的地址、输入金额覆盖输出和手续费，
+// 返回被花费的 (地址, 金额)。签名本身由 Transaction::validate 校验。
+pub(crate) fn spend(
+    unspent: &mut HashMap<OutPoint, TxOutput>,
+    tx: &Transaction,
+) -> Result<Vec<(String, u64)>, MempoolError> {
+    let mut available: u64 = 0;
+    for input in &tx.inputs {
+        let outpoint = input.outpoint();
+        let output = unspent
+            .get(&outpoint)
+            .ok_or_else(|| MempoolError::MissingInput(outpoint.clone()))?;
+        if wallet::address_from_public_key(&input.public_key).as_deref()
+            != Some(output.address.as_str())
+        {
+            return Err(MempoolError::NotOwner(outpoint));
+        }
+        available = available.saturating_add(output.amount);
+    }
+    // validate() 已经保证不会溢出
+    let required = tx.output_total().unwrap_or(u64::MAX).saturating_add(tx.fee);
+    if available < required {
+        return Err(MempoolError::InsufficientFunds {
+            available,
+            required,
+        });
+    }
+
+    Ok(tx
+        .inputs
+        .iter()
+        .filter_map(|input| unspent.remove(&input.outpoint()))
+        .map(|output| (output.address, output.amount))
+        .collect())
+}
+
+// 移除 owner 用来支付费用的输出，金额之和不少于 required，返回金额之和。出错时不修改 unspent
+fn take_outputs(
+    unspent: &mut HashMap<OutPoint, TxOutput>,
+    outpoints: &[OutPoint],
+    owner: &str,
+    required: u64,
+) -> Result<u64, MempoolError> {
+    let mut available: u64 = 0;
+    for outpoint in outpoints {
+        let output = unspent
+            .get(outpoint)
+            .ok_or_else(|| MempoolError::MissingInput(outpoint.clone()))?;
+        if output.address != owner {
+            return Err(MempoolError::NotOwner(outpoint.clone()));
+        }
+        available = available.saturating_add(output.amount);
+    }
+    if available < required {
+        return Err(MempoolError::InsufficientFunds {
+            available,
+            required,
+        });
+    }
+    for outpoint in outpoints {
+        unspent.remove(outpoint);
+    }
+    Ok(available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TxInput;
+    use crate::wallet::Wallet;
+
+    const INCREMENT: &str = "PUSH 0 SLOAD PUSH 1 ADD PUSH 0 SWAP SSTORE";
+
+    // 用第 height 个区块的铸币交易给 wallet 发放 amount，返回这个输出
+    fn fund(state: &mut WorldState, wallet: &Wallet, height: u64, amount: u64) -> OutPoint {
+        let mint = Transaction::coinbase(height, wallet.address(), amount);
+        state.apply_transaction(&mint).unwrap();
+        OutPoint {
+            txid: mint.id,
+            index: 0,
+        }
+    }
+
+    // wallet 签名、用 input 支付的调用，gas 价格为 1
+    fn call(wallet: &Wallet, input: OutPoint, code: &str, gas_limit: u64) -> Call {
+        let mut call = Call::new(
+            "counter".to_string(),
+            code.to_string(),
+            gas_limit,
+            1,
+            vec![input],
+        );
+        wallet.sign_call(&mut call);
+        call
+    }
+
+    // 调用扣费后找零的输出
+    fn change(call: &Call) -> OutPoint {
+        OutPoint {
+            txid: call.id(),
+            index: 0,
+        }
+    }
+
+    #[test]
+    fn calls_update_storage_and_failures_change_nothing() {
+        let alice = Wallet::generate();
+        let mut state = WorldState::default();
+        let coin = fund(&mut state, &alice, 1, 1000);
+
+        let first = call(&alice, coin, INCREMENT, 100);
+        let receipt = state.apply_call(&first).unwrap();
+        assert_eq!(receipt.error, None);
+        // 按用掉的 gas 扣费，其余找零给调用者
+        let mut charged = receipt.gas_used;
+        assert_eq!(state.balance(&alice.address()), 1000 - charged);
+        assert_eq!(state.owners["counter"], alice.address());
+        let second = call(&alice, change(&first), INCREMENT, 100);
+        charged += state.apply_call(&second).unwrap().gas_used;
+        assert_eq!(state.storage["counter"].get(&0), Some(&2));
+        let storage = state.storage.clone();
+
+        // gas 不够和主动回滚都不修改存储，但用掉的 gas 照样扣费
+        let starved = call(&alice, change(&second), INCREMENT, 10);
+        let receipt = state.apply_call(&starved).unwrap();
+        assert_eq!(receipt.gas_used, 10);
+        assert!(receipt.error.is_some());
+        charged += receipt.gas_used;
+        let reverted = call(&alice, change(&starved), "PUSH 0 PUSH 9 SSTORE REVERT", 100);
+        let receipt = state.apply_call(&reverted).unwrap();
+        assert!(receipt.error.is_some());
+        charged += receipt.gas_used;
+        assert_eq!(state.storage, storage);
+        assert_eq!(state.balance(&alice.address()), 1000 - charged);
+        assert_eq!(state.unspent()[&change(&reverted)].amount, 1000 - charged);
+    }
+
+    #[test]
+    fn calls_need_the_owner_signature_and_a_fee() {
+        let (alice, mallory) = (Wallet::generate(), Wallet::generate());
+        let mut state = WorldState::default();
+        let coin = fund(&mut state, &alice, 1, 1000);
+        let loot = fund(&mut state, &mallory, 2, 1000);
+        let first = call(&alice, coin.clone(), INCREMENT, 100);
+        state.apply_call(&first).unwrap();
+        let root = state.root();
+
+        // 合约属于第一个调用它的 alice
+        assert_eq!(
+            state.apply_call(&call(&mallory, loot.clone(), INCREMENT, 100)),
+            Err(CallError::NotOwner(alice.address()))
+        );
+        let mut unsigned = call(&alice, change(&first), INCREMENT, 100);
+        unsigned.signature.clear();
+        assert_eq!(state.apply_call(&unsigned), Err(CallError::BadSignature));
+
+        // 在 mallory 自己的合约上调用
+        let own = |input: OutPoint, gas_price: u64| {
+            let mut call = call(&mallory, input, INCREMENT, 100);
+            call.contract = "mallory".to_string();
+            call.gas_price = gas_price;
+            mallory.sign_call(&mut call);
+            call
+        };
+        assert_eq!(
+            state.apply_call(&own(loot.clone(), 0)),
+            Err(CallError::NoFee)
+        );
+        // 支付费用的输出必须属于调用者、尚未花费并且够付 gas_limit * gas_price
+        assert_eq!(
+            state.apply_call(&own(change(&first), 1)),
+            Err(CallError::Unfunded(MempoolError::NotOwner(change(&first))))
+        );
+        assert_eq!(
+            state.apply_call(&call(&alice, coin.clone(), INCREMENT, 100)),
+            Err(CallError::Unfunded(MempoolError::MissingInput(coin)))
+        );
+        assert_eq!(
+            state.apply_call(&own(loot, 11)),
+            Err(CallError::Unfunded(MempoolError::InsufficientFunds {
+                available: 1000,
+                required: 1100,
+            }))
+        );
+        // 被拒绝的调用不改变状态
+        assert_eq!(state.root(), root);
+    }
+
+    #[test]
+    fn transfers_move_balances() {
+        let (alice, bob) = (Wallet::generate(), Wallet::generate());
+        let mut state = WorldState::default();
+        let mint = Transaction::coinbase(1, alice.address(), 100);
+        state.apply_transaction(&mint).unwrap();
+
+        let mut pay = Transaction::new(
+            vec![TxInput::new(mint.id.clone(), 0)],
+            vec![
+                TxOutput {
+                    address: bob.address(),
+                    amount: 60,
+                },
+                TxOutput {
+                    address: alice.address(),
+                    amount: 30,
+                },
+            ],
+            10,
+        );
+        alice.sign(&mut pay);
+        state.apply_transaction(&pay).unwrap();
+        assert_eq!(state.balance(&alice.address()), 30);
+        assert_eq!(state.balance(&bob.address()), 60);
+        assert!(matches!(
+            state.apply_transaction(&pay),
+            Err(MempoolError::MissingInput(_))
+        ));
+    }
+}
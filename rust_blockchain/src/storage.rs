@@ -0,0 +1,347 @@
+// src/storage.rs
+// 追加写入的区块日志。文件以 [魔数 "RBLG"][版本: u16 LE] 开头，之后每条记录的格式为:
+//   [payload 长度: u32 LE][payload 的 crc32: u32 LE][payload: 区块的 JSON]
+// 记录格式或区块哈希的规则改变时版本加 1，旧版本的日志无法通过校验，打开时直接报告版本不支持。
+// 每次追加后 fsync，进程在写入中途崩溃时，重新打开会把写了一半的尾部记录截断。
+// 只有最后一条记录可能是写了一半的；校验和不符的记录后面还有数据，说明文件中间损坏，
+// 直接报错而不截断，否则会连带丢掉之后所有完好的区块。
+use crate::block::Block;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"RBLG";
+pub const LOG_VERSION: u16 = 1;
+const FILE_HEADER_LEN: usize = MAGIC.len() + 2;
+const RECORD_HEADER_LEN: usize = 8;
+
+#[derive(Debug)]
+pub struct ChainStore {
+    path: PathBuf,
+    file: File,
+}
+
+impl ChainStore {
+    // 打开(或创建)日志文件，返回其中所有完整的区块和被截断的尾部字节数
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<(ChainStore, Vec<Block>, usize)> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        // 新文件，或者创建时只写了一部分文件头
+        if bytes.len() < FILE_HEADER_LEN && file_header().starts_with(&bytes) {
+            file.set_len(0)?;
+            file.write_all(&file_header())?;
+            file.sync_all()?;
+            bytes = file_header();
+        }
+
+        let (blocks, valid_len) = decode_log(&bytes)?;
+        if valid_len < bytes.len() {
+            file.set_len(valid_len as u64)?;
+            file.sync_all()?;
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        Ok((
+            ChainStore {
+                path: path.to_path_buf(),
+                file,
+            },
+            blocks,
+            bytes.len() - valid_len,
+        ))
+    }
+
+    pub fn append(&mut self, block: &Block) -> io::Result<()> {
+        let record = encode_record(block)?;
+        // 整条记录一次写入，落盘之后才算区块已保存
+        self.file.write_all(&record)?;
+        self.file.sync_data()
+    }
+
+    // 用另一条链整体替换日志(分叉切换时使用)：先完整写入临时文件再原子改名，
+    // 崩溃时磁盘上要么是旧链要么是新链
+    pub fn replace_all(&mut self, blocks: &[Block]) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&file_header())?;
+        for block in blocks {
+            file.write_all(&encode_record(block)?)?;
+        }
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, &self.path)?;
+
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+}
+
+// 只读地加载日志中所有完整的区块，不修改文件，用于检查可能已损坏的数据。
+// 同时返回尾部写了一半的字节数
+pub fn read_blocks<P: AsRef<Path>>(path: P) -> io::Result<(Vec<Block>, usize)> {
+    let bytes = fs::read(path)?;
+    let (blocks, valid_len) = decode_log(&bytes)?;
+    Ok((blocks, bytes.len() - valid_len))
+}
+
+fn file_header() -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.extend_from_slice(&LOG_VERSION.to_le_bytes());
+    header
+}
+
+// 检查文件头后解析其中的记录
+fn decode_log(bytes: &[u8]) -> io::Result<(Vec<Block>, usize)> {
+    if !bytes.starts_with(MAGIC) {
+        // 版本 1 之前的日志没有文件头，直接以记录开始
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "不是区块日志，或者是不支持的旧版本(没有版本头)的日志",
+        ));
+    }
+    let version = bytes
+        .get(MAGIC.len()..FILE_HEADER_LEN)
+        .map(|version| u16::from_le_bytes(version.try_into().unwrap()));
+    if version != Some(LOG_VERSION) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            match version {
+                Some(version) => format!(
+                    "不支持的区块日志版本 {}，当前版本为 {}",
+                    version, LOG_VERSION
+                ),
+                None => "区块日志的文件头不完整".to_string(),
+            },
+        ));
+    }
+    decode_records(bytes, FILE_HEADER_LEN)
+}
+
+fn encode_record(block: &Block) -> io::Result<Vec<u8>> {
+    let payload = serde_json::to_vec(block)?;
+    let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+// 顺序解析记录，返回已解析的区块和有效字节数。最后一条记录不完整或校验和不符时视为写了一半，
+// 在它之前停下；校验和不符的记录不在末尾，或者校验通过却无法解析(格式不兼容)时报错。
+// 记录头中的长度本身损坏、指向文件末尾之后时无法与写了一半区分，同样按尾部处理。
+fn decode_records(bytes: &[u8], mut offset: usize) -> io::Result<(Vec<Block>, usize)> {
+    let mut blocks = Vec::new();
+    while bytes.len() - offset >= RECORD_HEADER_LEN {
+        let header = &bytes[offset..offset + RECORD_HEADER_LEN];
+        let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(header[4..].try_into().unwrap());
+
+        let start = offset + RECORD_HEADER_LEN;
+        let Some(payload) = bytes.get(start..start + len) else {
+            break;
+        };
+        if crc32fast::hash(payload) != crc {
+            if start + len == bytes.len() {
+                break;
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "偏移 {} 处的区块记录校验和不符，之后还有 {} 字节，日志中间已损坏",
+                    offset,
+                    bytes.len() - start - len
+                ),
+            ));
+        }
+        let block = serde_json::from_slice::<Block>(payload).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("偏移 {} 处的区块记录无法解析: {}", offset, e),
+            )
+        })?;
+        blocks.push(block);
+        offset = start + len;
+    }
+    Ok((blocks, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(index: u64) -> Block {
+        Block::new(
+            index,
+            format!("block {}", index),
+            Vec::new(),
+            "0".to_string(),
+        )
+    }
+
+    #[test]
+    fn reopen_returns_appended_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let (mut store, blocks, _) = ChainStore::open(&path).unwrap();
+        assert!(blocks.is_empty());
+        store.append(&block(0)).unwrap();
+        store.append(&block(1)).unwrap();
+        drop(store);
+
+        let (_, blocks, _) = ChainStore::open(&path).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].data, "block 1");
+    }
+
+    #[test]
+    fn torn_tail_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let (mut store, _, _) = ChainStore::open(&path).unwrap();
+        store.append(&block(0)).unwrap();
+        let good_len = std::fs::metadata(&path).unwrap().len();
+        store.append(&block(1)).unwrap();
+        drop(store);
+
+        // 模拟写入第二条记录时崩溃：只留下前半截
+        let full_len = std::fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(good_len + (full_len - good_len) / 2).unwrap();
+        drop(file);
+
+        // 只读加载报告尾部的字节数，不修改文件
+        let torn_len = std::fs::metadata(&path).unwrap().len();
+        let (blocks, discarded) = read_blocks(&path).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(discarded as u64, torn_len - good_len);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), torn_len);
+
+        let (mut store, blocks, discarded) = ChainStore::open(&path).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(discarded as u64, torn_len - good_len);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), good_len);
+
+        // 截断后可以继续追加
+        store.append(&block(1)).unwrap();
+        drop(store);
+        let (_, blocks, _) = ChainStore::open(&path).unwrap();
+        assert_eq!(blocks.len(), 2);
+    }
+
+    #[test]
+    fn corrupted_tail_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let (mut store, _, _) = ChainStore::open(&path).unwrap();
+        store.append(&block(0)).unwrap();
+        let good_len = std::fs::metadata(&path).unwrap().len();
+        store.append(&block(1)).unwrap();
+        drop(store);
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 2;
+        bytes[last] ^= 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let (_, blocks, _) = ChainStore::open(&path).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), good_len);
+    }
+
+    #[test]
+    fn corruption_before_the_tail_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let (mut store, _, _) = ChainStore::open(&path).unwrap();
+        for index in 0..3 {
+            store.append(&block(index)).unwrap();
+        }
+        drop(store);
+
+        // 翻转第一条记录 payload 中的一位，后面两条记录完好
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[FILE_HEADER_LEN + RECORD_HEADER_LEN + 1] ^= 0x01;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let err = ChainStore::open(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            read_blocks(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        // 文件没有被截断
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    }
+
+    #[test]
+    fn replace_all_rewrites_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let (mut store, _, _) = ChainStore::open(&path).unwrap();
+        store.append(&block(0)).unwrap();
+        store.append(&block(1)).unwrap();
+        store.replace_all(&[block(0), block(7)]).unwrap();
+        store.append(&block(8)).unwrap();
+        drop(store);
+
+        let (_, blocks, _) = ChainStore::open(&path).unwrap();
+        let data: Vec<_> = blocks.iter().map(|b| b.data.as_str()).collect();
+        assert_eq!(data, ["block 0", "block 7", "block 8"]);
+    }
+
+    #[test]
+    fn incompatible_record_is_an_error_not_a_torn_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let payload = br#"{"not":"a block"}"#;
+        let mut record = file_header();
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+        record.extend_from_slice(payload);
+        std::fs::write(&path, &record).unwrap();
+
+        let err = ChainStore::open(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&path).unwrap(), record);
+    }
+
+    #[test]
+    fn unsupported_versions_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.db");
+
+        let (mut store, _, _) = ChainStore::open(&path).unwrap();
+        store.append(&block(0)).unwrap();
+        drop(store);
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(MAGIC));
+
+        // 没有文件头的旧日志
+        std::fs::write(&path, &bytes[FILE_HEADER_LEN..]).unwrap();
+        let err = ChainStore::open(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("版本"), "{}", err);
+
+        let mut future = bytes.clone();
+        future[MAGIC.len()] = 9;
+        std::fs::write(&path, &future).unwrap();
+        let err = read_blocks(&path).unwrap_err();
+        assert!(err.to_string().contains("版本 9"), "{}", err);
+        // 两种情况都不修改文件
+        assert_eq!(std::fs::read(&path).unwrap(), future);
+    }
+}
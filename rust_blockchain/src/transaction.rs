@@ -0,0 +1,223 @@
+// src/transaction.rs
+// UTXO 风格的交易：输入引用之前交易的某个输出，输出把金额转给地址，差额作为手续费。
+// 新币只能由铸币交易(coinbase)发行：它只有一个不引用任何输出的输入，output_index 记录区块高度，
+// 只能作为区块的第一笔交易，领取出块奖励和区块内的手续费，由 Blockchain 检查金额上限。
+// 每个输入带有花费者的公钥和对交易签名哈希的 Ed25519 签名。
+use crate::wallet;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+
+// 某笔交易的某个输出
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OutPoint {
+    pub txid: String,
+    pub index: u32,
+}
+
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.txid, self.index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxInput {
+    pub prev_txid: String,
+    pub output_index: u32,
+    // 十六进制编码的公钥和签名，由 Wallet::sign 填写
+    #[serde(default)]
+    pub public_key: String,
+    #[serde(default)]
+    pub signature: String,
+}
+
+impl TxInput {
+    pub fn new(prev_txid: String, output_index: u32) -> Self {
+        TxInput {
+            prev_txid,
+            output_index,
+            public_key: String::new(),
+            signature: String::new(),
+        }
+    }
+
+    pub fn outpoint(&self) -> OutPoint {
+        OutPoint {
+            txid: self.prev_txid.clone(),
+            index: self.output_index,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutput {
+    pub address: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: String,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    pub fee: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    NoInputs,
+    NoOutputs,
+    ZeroAmount,
+    DuplicateInput(OutPoint),
+    IdMismatch,
+    AmountOverflow,
+    // 第 n 个输入缺少签名或签名校验失败
+    BadSignature(usize),
+    // 铸币交易带了手续费、公钥或签名
+    BadCoinbase,
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionError::NoInputs => write!(f, "交易没有输入"),
+            TransactionError::NoOutputs => write!(f, "交易没有输出"),
+            TransactionError::ZeroAmount => write!(f, "输出金额不能为 0"),
+            TransactionError::DuplicateInput(outpoint) => write!(f, "重复引用输入 {}", outpoint),
+            TransactionError::IdMismatch => write!(f, "交易 id 与内容不符"),
+            TransactionError::AmountOverflow => write!(f, "金额溢出"),
+            TransactionError::BadSignature(index) => write!(f, "第 {} 个输入的签名无效", index),
+            TransactionError::BadCoinbase => write!(f, "铸币交易不能带手续费和签名"),
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl Transaction {
+    pub fn new(inputs: Vec<TxInput>, outputs: Vec<TxOutput>, fee: u64) -> Self {
+        let mut tx = Transaction {
+            id: String::new(),
+            inputs,
+            outputs,
+            fee,
+        };
+        tx.id = tx.calculate_id();
+        tx
+    }
+
+    // 签名的消息：输入引用、输出和手续费，不包含公钥和签名本身
+    pub fn signing_hash(&self) -> [u8; 32] {
+        // 变长字段都带上长度前缀，避免不同内容拼接出同样的字节
+        let mut hasher = Sha256::new();
+        hasher.update((self.inputs.len() as u64).to_le_bytes());
+        for input in &self.inputs {
+            hasher.update((input.prev_txid.len() as u64).to_le_bytes());
+            hasher.update(input.prev_txid.as_bytes());
+            hasher.update(input.output_index.to_le_bytes());
+        }
+        hasher.update((self.outputs.len() as u64).to_le_bytes());
+        for output in &self.outputs {
+            hasher.update((output.address.len() as u64).to_le_bytes());
+            hasher.update(output.address.as_bytes());
+            hasher.update(output.amount.to_le_bytes());
+        }
+        hasher.update(self.fee.to_le_bytes());
+        hasher.finalize().into()
+    }
+
+    // 交易 id 不覆盖签名，签名前后 id 不变
+    pub fn calculate_id(&self) -> String {
+        hex::encode(self.signing_hash())
+    }
+
+    // 包含公钥和签名在内的完整交易哈希，作为区块 Merkle 树的条目，
+    // 使区块头也承诺交易的签名
+    pub fn full_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.signing_hash());
+        for input in &self.inputs {
+            hasher.update((input.public_key.len() as u64).to_le_bytes());
+            hasher.update(input.public_key.as_bytes());
+            hasher.update((input.signature.len() as u64).to_le_bytes());
+            hasher.update(input.signature.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    // 第 height 个区块的铸币交易，不需要签名
+    pub fn coinbase(height: u64, address: String, amount: u64) -> Self {
+        Transaction::new(
+            vec![TxInput::new(String::new(), height as u32)],
+            vec![TxOutput { address, amount }],
+            0,
+        )
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        matches!(self.inputs.as_slice(), [input] if input.prev_txid.is_empty())
+    }
+
+    // 交易序列化后的字节数，用于区块大小限制和手续费率
+    pub fn size(&self) -> usize {
+        serde_json::to_vec(self)
+            .map(|v| v.len())
+            .unwrap_or(usize::MAX)
+    }
+
+    pub fn output_total(&self) -> Option<u64> {
+        self.outputs
+            .iter()
+            .try_fold(0u64, |sum, output| sum.checked_add(output.amount))
+    }
+
+    // 只检查交易本身的结构和签名，输入是否存在、是否属于签名者、金额是否足够
+    // 由 Blockchain 结合链上状态检查
+    pub fn validate(&self) -> Result<(), TransactionError> {
+        if self.inputs.is_empty() {
+            return Err(TransactionError::NoInputs);
+        }
+        if self.outputs.is_empty() {
+            return Err(TransactionError::NoOutputs);
+        }
+        if self.outputs.iter().any(|output| output.amount == 0) {
+            return Err(TransactionError::ZeroAmount);
+        }
+        self.output_total()
+            .and_then(|total| total.checked_add(self.fee))
+            .ok_or(TransactionError::AmountOverflow)?;
+
+        let mut seen = HashSet::new();
+        for input in &self.inputs {
+            let outpoint = input.outpoint();
+            if seen.contains(&outpoint) {
+                return Err(TransactionError::DuplicateInput(outpoint));
+            }
+            seen.insert(outpoint);
+        }
+
+        if self.id != self.calculate_id() {
+            return Err(TransactionError::IdMismatch);
+        }
+        if self.is_coinbase() {
+            let input = &self.inputs[0];
+            if self.fee != 0 || !input.public_key.is_empty() || !input.signature.is_empty() {
+                return Err(TransactionError::BadCoinbase);
+            }
+            return Ok(());
+        }
+        self.verify_signatures()
+    }
+
+    pub fn verify_signatures(&self) -> Result<(), TransactionError> {
+        let message = self.signing_hash();
+        for (index, input) in self.inputs.iter().enumerate() {
+            if !wallet::verify(&input.public_key, &message, &input.signature) {
+                return Err(TransactionError::BadSignature(index));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+// src/vm.rs
+// 确定性的栈式虚拟机。程序是空白分隔的汇编文本，例如:
+//   PUSH 0 SLOAD PUSH 1 ADD PUSH 0 SWAP SSTORE
+// 只有 i64 整数，算术溢出、除以 0、栈越界都会使执行失败；每条指令按固定价格消耗 gas，
+// gas 用完即失败。存储写入先记在本次执行的副本里，成功结束后才由调用方提交。
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const MAX_PROGRAM_LEN: usize = 1024;
+pub const MAX_STACK_DEPTH: usize = 256;
+
+pub type Storage = BTreeMap<i64, i64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Push(i64),
+    Pop,
+    Dup,
+    Swap,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Lt,
+    Gt,
+    Not,
+    // 跳转到第 n 条指令；JumpIf 弹出条件，不为 0 时跳转
+    Jump(usize),
+    JumpIf(usize),
+    // 弹出键，压入存储中的值(不存在时为 0)
+    Load,
+    // 依次弹出值和键写入存储，写入 0 等于删除
+    Store,
+    Stop,
+    Revert,
+}
+
+impl Op {
+    fn gas(&self) -> u64 {
+        match self {
+            Op::Load => 5,
+            Op::Store => 20,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    // 第 n 个单词无法解析
+    Parse(usize),
+    ProgramTooLong,
+    OutOfGas,
+    StackUnderflow,
+    StackOverflow,
+    Overflow,
+    DivisionByZero,
+    BadJump(usize),
+    Reverted,
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::Parse(word) => write!(f, "第 {} 个单词无法解析", word),
+            VmError::ProgramTooLong => write!(f, "程序超过 {} 条指令", MAX_PROGRAM_LEN),
+            VmError::OutOfGas => write!(f, "gas 耗尽"),
+            VmError::StackUnderflow => write!(f, "栈为空"),
+            VmError::StackOverflow => write!(f, "栈深度超过 {}", MAX_STACK_DEPTH),
+            VmError::Overflow => write!(f, "整数溢出"),
+            VmError::DivisionByZero => write!(f, "除以 0"),
+            VmError::BadJump(target) => write!(f, "跳转目标 {} 越界", target),
+            VmError::Reverted => write!(f, "程序主动回滚"),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+pub fn parse(source: &str) -> Result<Vec<Op>, VmError> {
+    let mut words = source.split_whitespace().enumerate();
+    let mut program = Vec::new();
+    while let Some((position, word)) = words.next() {
+        let mut immediate = || {
+            words
+                .next()
+                .and_then(|(_, value)| value.parse::<i64>().ok())
+                .ok_or(VmError::Parse(position + 1))
+        };
+        let op = match word.to_ascii_uppercase().as_str() {
+            "PUSH" => Op::Push(immediate()?),
+            "POP" => Op::Pop,
+            "DUP" => Op::Dup,
+            "SWAP" => Op::Swap,
+            "ADD" => Op::Add,
+            "SUB" => Op::Sub,
+            "MUL" => Op::Mul,
+            "DIV" => Op::Div,
+            "MOD" => Op::Mod,
+            "EQ" => Op::Eq,
+            "LT" => Op::Lt,
+            "GT" => Op::Gt,
+            "NOT" => Op::Not,
+            "JUMP" => Op::Jump(jump_target(immediate()?)?),
+            "JUMPI" => Op::JumpIf(jump_target(immediate()?)?),
+            "SLOAD" => Op::Load,
+            "SSTORE" => Op::Store,
+            "STOP" => Op::Stop,
+            "REVERT" => Op::Revert,
+            _ => return Err(VmError::Parse(position)),
+        };
+        program.push(op);
+        if program.len() > MAX_PROGRAM_LEN {
+            return Err(VmError::ProgramTooLong);
+        }
+    }
+    Ok(program)
+}
+
+fn jump_target(value: i64) -> Result<usize, VmError> {
+    usize::try_from(value).map_err(|_| VmError::BadJump(usize::MAX))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Execution {
+    pub gas_used: u64,
+    // 结束时的栈，栈顶在最后
+    pub stack: Vec<i64>,
+    // 执行后的存储，由调用方决定是否提交
+    pub storage: Storage,
+}
+
+// 在 storage 的副本上执行程序，最多消耗 gas_limit。失败时同时返回已消耗的 gas
+pub fn execute(
+    program: &[Op],
+    storage: &Storage,
+    gas_limit: u64,
+) -> Result<Execution, (VmError, u64)> {
+    let mut vm = Vm {
+        stack: Vec::new(),
+        storage: storage.clone(),
+        gas_used: 0,
+    };
+    match vm.run(program, gas_limit) {
+        Ok(()) => Ok(Execution {
+            gas_used: vm.gas_used,
+            stack: vm.stack,
+            storage: vm.storage,
+        }),
+        Err(e) => Err((e, vm.gas_used)),
+    }
+}
+
+struct Vm {
+    stack: Vec<i64>,
+    storage: Storage,
+    gas_used: u64,
+}
+
+impl Vm {
+    fn run(&mut self, program: &[Op], gas_limit: u64) -> Result<(), VmError> {
+        let mut pc = 0;
+        while let Some(op) = program.get(pc) {
+            let gas = self.gas_used + op.gas();
+            if gas > gas_limit {
+                self.gas_used = gas_limit;
+                return Err(VmError::OutOfGas);
+            }
+            self.gas_used = gas;
+            pc += 1;
+
+            match *op {
+                Op::Push(value) => self.push(value)?,
+                Op::Pop => {
+                    self.pop()?;
+                }
+                Op::Dup => {
+                    let top = self.pop()?;
+                    self.push(top)?;
+                    self.push(top)?;
+                }
+                Op::Swap => {
+                    let (a, b) = self.pop2()?;
+                    self.push(b)?;
+                    self.push(a)?;
+                }
+                Op::Add => self.arithmetic(i64::checked_add)?,
+                Op::Sub => self.arithmetic(i64::checked_sub)?,
+                Op::Mul => self.arithmetic(i64::checked_mul)?,
+                Op::Div | Op::Mod => {
+                    let (a, b) = self.pop2()?;
+                    if b == 0 {
+                        return Err(VmError::DivisionByZero);
+                    }
+                    let result = if *op == Op::Div {
+                        a.checked_div(b)
+                    } else {
+                        a.checked_rem(b)
+                    };
+                    self.push(result.ok_or(VmError::Overflow)?)?;
+                }
+                Op::Eq => self.compare(|a, b| a == b)?,
+                Op::Lt => self.compare(|a, b| a < b)?,
+                Op::Gt => self.compare(|a, b| a > b)?,
+                Op::Not => {
+                    let value = self.pop()?;
+                    self.push((value == 0) as i64)?;
+                }
+                Op::Jump(target) => pc = checked_target(program, target)?,
+                Op::JumpIf(target) => {
+                    if self.pop()? != 0 {
+                        pc = checked_target(program, target)?;
+                    }
+                }
+                Op::Load => {
+                    let key = self.pop()?;
+                    let value = self.storage.get(&key).copied().unwrap_or(0);
+                    self.push(value)?;
+                }
+                Op::Store => {
+                    let (key, value) = self.pop2()?;
+                    if value == 0 {
+                        self.storage.remove(&key);
+                    } else {
+                        self.storage.insert(key, value);
+                    }
+                }
+                Op::Stop => return Ok(()),
+                Op::Revert => return Err(VmError::Reverted),
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, value: i64) -> Result<(), VmError> {
+        if self.stack.len() >= MAX_STACK_DEPTH {
+            return Err(VmError::StackOverflow);
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<i64, VmError> {
+        self.stack.pop().ok_or(VmError::StackUnderflow)
+    }
+
+    // 弹出两个值，返回 (次栈顶, 栈顶)
+    fn pop2(&mut self) -> Result<(i64, i64), VmError> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        Ok((a, b))
+    }
+
+    fn arithmetic(&mut self, op: fn(i64, i64) -> Option<i64>) -> Result<(), VmError> {
+        let (a, b) = self.pop2()?;
+        self.push(op(a, b).ok_or(VmError::Overflow)?)
+    }
+
+    fn compare(&mut self, op: fn(i64, i64) -> bool) -> Result<(), VmError> {
+        let (a, b) = self.pop2()?;
+        self.push(op(a, b) as i64)
+    }
+}
+
+// 允许跳到程序末尾(等于正常结束)
+fn checked_target(program: &[Op], target: usize) -> Result<usize, VmError> {
+    if target > program.len() {
+        return Err(VmError::BadJump(target));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str, gas_limit: u64) -> Result<Execution, (VmError, u64)> {
+        execute(&parse(source).unwrap(), &Storage::new(), gas_limit)
+    }
+
+    #[test]
+    fn arithmetic_and_storage() {
+        let result = run(
+            "PUSH 7 PUSH 3 SUB PUSH 4 MUL PUSH 1 SWAP SSTORE PUSH 1 SLOAD",
+            100,
+        )
+        .unwrap();
+        assert_eq!(result.stack, vec![16]);
+        assert_eq!(result.storage.get(&1), Some(&16));
+        // 8 条普通指令 + SSTORE + SLOAD
+        assert_eq!(result.gas_used, 8 + 20 + 5);
+    }
+
+    #[test]
+    fn loop_runs_until_out_of_gas_or_done() {
+        // 从 5 倒数到 0
+        let countdown = "PUSH 5 DUP JUMPI 4 STOP PUSH 1 SUB DUP JUMPI 4";
+        let result = run(countdown, 1_000).unwrap();
+        assert_eq!(result.stack, vec![0]);
+
+        let infinite = "PUSH 1 JUMP 0";
+        assert_eq!(run(infinite, 50).unwrap_err(), (VmError::OutOfGas, 50));
+    }
+
+    #[test]
+    fn failures_are_reported() {
+        assert_eq!(run("ADD", 10).unwrap_err().0, VmError::StackUnderflow);
+        assert_eq!(
+            run("PUSH 1 PUSH 0 DIV", 10).unwrap_err().0,
+            VmError::DivisionByZero
+        );
+        let overflow = format!("PUSH {} PUSH 1 ADD", i64::MAX);
+        assert_eq!(run(&overflow, 10).unwrap_err().0, VmError::Overflow);
+        assert_eq!(run("JUMP 9", 10).unwrap_err().0, VmError::BadJump(9));
+        assert_eq!(run("PUSH 1 REVERT", 10).unwrap_err().0, VmError::Reverted);
+        assert_eq!(parse("PUSH x"), Err(VmError::Parse(1)));
+        assert_eq!(parse("FLY"), Err(VmError::Parse(0)));
+    }
+}
@@ -0,0 +1,224 @@
+// src/wallet.rs
+// Ed25519 钱包：生成密钥对、由公钥推导地址、为交易输入和合约调用签名。
+// Keystore 把多个命名钱包的私钥保存在一个 JSON 文件里。
+use crate::state::Call;
+use crate::transaction::Transaction;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 地址取公钥 SHA-256 的前 20 字节
+const ADDRESS_LEN: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct Wallet {
+    signing_key: SigningKey,
+}
+
+impl Wallet {
+    pub fn generate() -> Self {
+        Wallet {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    pub fn from_secret_hex(secret: &str) -> Option<Self> {
+        let bytes: [u8; 32] = hex::decode(secret).ok()?.try_into().ok()?;
+        Some(Wallet {
+            signing_key: SigningKey::from_bytes(&bytes),
+        })
+    }
+
+    pub fn secret_hex(&self) -> String {
+        hex::encode(self.signing_key.to_bytes())
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.signing_key.verifying_key().to_bytes())
+    }
+
+    pub fn address(&self) -> String {
+        address_of(&self.signing_key.verifying_key())
+    }
+
+    pub fn sign_message(&self, message: &[u8]) -> String {
+        hex::encode(self.signing_key.sign(message).to_bytes())
+    }
+
+    // 用本钱包为交易的所有输入签名
+    pub fn sign(&self, tx: &mut Transaction) {
+        let signature = self.sign_message(&tx.signing_hash());
+        let public_key = self.public_key_hex();
+        for input in &mut tx.inputs {
+            input.public_key = public_key.clone();
+            input.signature = signature.clone();
+        }
+    }
+
+    // 以本钱包为调用者为合约调用签名
+    pub fn sign_call(&self, call: &mut Call) {
+        call.public_key = self.public_key_hex();
+        call.signature = self.sign_message(&call.signing_hash());
+    }
+}
+
+fn address_of(key: &VerifyingKey) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    hex::encode(&digest[..ADDRESS_LEN])
+}
+
+// 由十六进制公钥推导地址，公钥格式错误时返回 None
+pub fn address_from_public_key(public_key: &str) -> Option<String> {
+    parse_public_key(public_key).map(|key| address_of(&key))
+}
+
+fn parse_public_key(public_key: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = hex::decode(public_key).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+// 校验十六进制编码的签名，任何解析失败都视为签名无效
+pub fn verify(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let Some(key) = parse_public_key(public_key) else {
+        return false;
+    };
+    let Some(signature) = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+    else {
+        return false;
+    };
+    key.verify_strict(message, &signature).is_ok()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeystoreFile {
+    // 钱包名 -> 十六进制私钥
+    wallets: BTreeMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct Keystore {
+    path: PathBuf,
+    wallets: BTreeMap<String, Wallet>,
+}
+
+impl Keystore {
+    // 打开钱包文件，文件不存在时得到一个空的 Keystore
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<KeystoreFile>(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => KeystoreFile::default(),
+            Err(e) => return Err(e),
+        };
+
+        let mut wallets = BTreeMap::new();
+        for (name, secret) in file.wallets {
+            let wallet = Wallet::from_secret_hex(&secret).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("钱包 {} 私钥格式错误", name),
+                )
+            })?;
+            wallets.insert(name, wallet);
+        }
+        Ok(Keystore { path, wallets })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Wallet> {
+        self.wallets.get(name)
+    }
+
+    pub fn wallets(&self) -> impl Iterator<Item = (&String, &Wallet)> {
+        self.wallets.iter()
+    }
+
+    // 生成新钱包并立即写回文件，同名钱包已存在时返回已有的
+    pub fn create(&mut self, name: &str) -> io::Result<&Wallet> {
+        if !self.wallets.contains_key(name) {
+            self.wallets.insert(name.to_string(), Wallet::generate());
+            self.save()?;
+        }
+        Ok(&self.wallets[name])
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let file = KeystoreFile {
+            wallets: self
+                .wallets
+                .iter()
+                .map(|(name, wallet)| (name.clone(), wallet.secret_hex()))
+                .collect(),
+        };
+        // 先写临时文件再改名，避免写一半时损坏已有的私钥
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&file)?)?;
+        restrict_permissions(&tmp)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{TransactionError, TxInput, TxOutput};
+
+    #[test]
+    fn signed_transaction_verifies() {
+        let wallet = Wallet::generate();
+        let mut tx = Transaction::new(
+            vec![TxInput::new("a".to_string(), 0)],
+            vec![TxOutput {
+                address: "bob".to_string(),
+                amount: 1,
+            }],
+            0,
+        );
+        assert_eq!(
+            tx.verify_signatures(),
+            Err(TransactionError::BadSignature(0))
+        );
+
+        wallet.sign(&mut tx);
+        assert!(tx.validate().is_ok());
+        assert_eq!(
+            address_from_public_key(&tx.inputs[0].public_key),
+            Some(wallet.address())
+        );
+
+        // 签名后改动输出，签名失效
+        tx.outputs[0].amount = 2;
+        tx.id = tx.calculate_id();
+        assert_eq!(tx.validate(), Err(TransactionError::BadSignature(0)));
+    }
+
+    #[test]
+    fn keystore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallets.json");
+
+        let mut keystore = Keystore::open(&path).unwrap();
+        let address = keystore.create("alice").unwrap().address();
+
+        let keystore = Keystore::open(&path).unwrap();
+        assert_eq!(keystore.get("alice").unwrap().address(), address);
+        assert!(keystore.get("bob").is_none());
+    }
+}
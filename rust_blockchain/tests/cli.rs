@@ -0,0 +1,73 @@
+// 以子进程运行命令行，检查 JSON 输出和退出码
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+fn run(file: &Path, args: &[&str]) -> (i32, Value) {
+    run_with_difficulty(file, "1", args)
+}
+
+fn run_with_difficulty(file: &Path, difficulty: &str, args: &[&str]) -> (i32, Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_blockchain"))
+        .arg("--file")
+        .arg(file)
+        .args(["--difficulty", difficulty])
+        .args(args)
+        .output()
+        .unwrap();
+    let json = serde_json::from_slice(&output.stdout).unwrap();
+    (output.status.code().unwrap(), json)
+}
+
+#[test]
+fn scripted_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("chain.db");
+
+    let (code, out) = run(&file, &["add", "early"]);
+    assert_eq!(code, 3);
+    assert!(out["error"].is_string());
+
+    assert_eq!(run(&file, &["init"]).0, 0);
+    assert_eq!(run(&file, &["init"]).0, 1);
+
+    let (code, out) = run(&file, &["add", "hello", "--reward", "alice"]);
+    assert_eq!(code, 0);
+    // 只有铸币交易
+    assert_eq!(out["blocks"][0]["transactions"], 1);
+    let hash = out["blocks"][0]["hash"].as_str().unwrap().to_string();
+    let (code, out) = run(&file, &["mine", "--count", "2"]);
+    assert_eq!(code, 0);
+    assert_eq!(out["height"], 3);
+
+    let (code, out) = run(&file, &["show", &hash]);
+    assert_eq!(code, 0);
+    assert_eq!(out["data"], "hello");
+    assert_eq!(run(&file, &["show", "42"]).0, 4);
+
+    let (code, out) = run(&file, &["validate"]);
+    assert_eq!(code, 0);
+    assert_eq!(out["valid"], true);
+    assert_eq!(out["blocks"], 4);
+    // 已有文件以创世区块中记录的难度为准，--difficulty 只影响新建的文件
+    let (code, out) = run_with_difficulty(&file, "4", &["validate"]);
+    assert_eq!(code, 0);
+    assert_eq!(out["valid"], true);
+
+    let export = dir.path().join("chain.rbch");
+    assert_eq!(run(&file, &["export", export.to_str().unwrap()]).0, 0);
+
+    let (code, _) = run(&file, &["tamper", "2"]);
+    assert_eq!(code, 0);
+    let (code, out) = run(&file, &["validate"]);
+    assert_eq!(code, 1);
+    assert_eq!(out["valid"], false);
+    assert_eq!(out["error"]["index"], 2);
+
+    // 导出的链可以导入新文件，同样的链再导入一次不会替换
+    let copy = dir.path().join("copy.db");
+    let (code, out) = run_with_difficulty(&copy, "4", &["import", export.to_str().unwrap()]);
+    assert_eq!(code, 0);
+    assert_eq!(out["blocks"], 4);
+    assert_eq!(run(&copy, &["import", export.to_str().unwrap()]).0, 1);
+}